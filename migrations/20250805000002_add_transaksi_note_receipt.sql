@@ -0,0 +1,3 @@
+-- Add optional note and receipt reference to transaksi
+ALTER TABLE transaksi ADD COLUMN IF NOT EXISTS note TEXT;
+ALTER TABLE transaksi ADD COLUMN IF NOT EXISTS receipt_url TEXT;
@@ -0,0 +1,9 @@
+-- Per-user categories: user_id NULL berarti kategori sistem (berlaku untuk semua user)
+ALTER TABLE categories ADD COLUMN IF NOT EXISTS user_id UUID REFERENCES users(id) ON DELETE CASCADE;
+
+-- Nama kategori cukup unik per pemilik, bukan global
+ALTER TABLE categories DROP CONSTRAINT IF EXISTS categories_nama_key;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_categories_owner_nama
+    ON categories (COALESCE(user_id, '00000000-0000-0000-0000-000000000000'::uuid), nama);
+
+CREATE INDEX IF NOT EXISTS idx_categories_user_id ON categories(user_id);
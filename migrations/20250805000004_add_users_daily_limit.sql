@@ -0,0 +1,2 @@
+-- Optional daily spending cap across all categories
+ALTER TABLE users ADD COLUMN IF NOT EXISTS daily_limit INTEGER CHECK (daily_limit > 0);
@@ -0,0 +1,5 @@
+-- Jenis transaksi: pengeluaran (default, data lama) atau pemasukan
+ALTER TABLE transaksi ADD COLUMN IF NOT EXISTS tipe VARCHAR(20) NOT NULL DEFAULT 'pengeluaran'
+    CHECK (tipe IN ('pengeluaran', 'pemasukan'));
+
+CREATE INDEX IF NOT EXISTS idx_transaksi_user_tipe_tanggal ON transaksi(user_id, tipe, tanggal);
@@ -0,0 +1,19 @@
+-- Create tags table (tag bebas per user, disimpan dalam huruf kecil)
+CREATE TABLE IF NOT EXISTS tags (
+    id SERIAL PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    nama VARCHAR(60) NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE(user_id, nama)
+);
+
+-- Relasi many-to-many transaksi <-> tags
+CREATE TABLE IF NOT EXISTS transaksi_tags (
+    transaksi_id INTEGER NOT NULL REFERENCES transaksi(id) ON DELETE CASCADE,
+    tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (transaksi_id, tag_id)
+);
+
+-- Create indexes for better performance
+CREATE INDEX IF NOT EXISTS idx_tags_user_id ON tags(user_id);
+CREATE INDEX IF NOT EXISTS idx_transaksi_tags_tag_id ON transaksi_tags(tag_id);
@@ -0,0 +1,9 @@
+-- Preferensi tampilan per user (format uang dan tanggal)
+CREATE TABLE IF NOT EXISTS user_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    currency VARCHAR(3) NOT NULL DEFAULT 'IDR',
+    locale VARCHAR(35) NOT NULL DEFAULT 'id-ID',
+    date_format VARCHAR(20) NOT NULL DEFAULT 'DD/MM/YYYY',
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
@@ -0,0 +1,12 @@
+-- Jumlah uang disimpan sebagai bilangan bulat dalam satuan terkecil mata uang (minor unit).
+-- Kolom minor_unit mencatat eksponen mata uang saat data ditulis: 0 untuk IDR/JPY
+-- (jumlah 150 = Rp150), 2 untuk USD/EUR (jumlah 150 = $1.50).
+-- Data lama seluruhnya IDR, sehingga otomatis mendapat eksponen 0 dari DEFAULT
+-- dan nilainya tidak perlu dikonversi.
+ALTER TABLE transaksi
+    ADD COLUMN IF NOT EXISTS minor_unit SMALLINT NOT NULL DEFAULT 0
+    CHECK (minor_unit BETWEEN 0 AND 4);
+
+ALTER TABLE budgets
+    ADD COLUMN IF NOT EXISTS minor_unit SMALLINT NOT NULL DEFAULT 0
+    CHECK (minor_unit BETWEEN 0 AND 4);
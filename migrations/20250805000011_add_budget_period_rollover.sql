@@ -0,0 +1,8 @@
+-- Budget berlaku per periode bulanan. Saat periode berganti, task reset memindahkan
+-- period_start ke bulan berjalan dan menghitung ulang spent.
+-- Jika rollover aktif, sisa budget periode lalu (floor 0) disimpan di carry_over dan
+-- menambah budget efektif periode berikutnya (amount + carry_over).
+ALTER TABLE budgets
+    ADD COLUMN IF NOT EXISTS period_start DATE NOT NULL DEFAULT date_trunc('month', CURRENT_DATE)::date,
+    ADD COLUMN IF NOT EXISTS rollover BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN IF NOT EXISTS carry_over INTEGER NOT NULL DEFAULT 0 CHECK (carry_over >= 0);
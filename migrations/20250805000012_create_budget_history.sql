@@ -0,0 +1,14 @@
+-- Snapshot budget per periode, dicatat oleh task reset periode sebelum spent dihitung ulang
+CREATE TABLE IF NOT EXISTS budget_history (
+    id SERIAL PRIMARY KEY,
+    budget_id INTEGER NOT NULL REFERENCES budgets(id) ON DELETE CASCADE,
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    amount INTEGER NOT NULL,
+    carry_over INTEGER NOT NULL DEFAULT 0,
+    spent INTEGER NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE (budget_id, period_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_budget_history_budget_period ON budget_history(budget_id, period_start DESC);
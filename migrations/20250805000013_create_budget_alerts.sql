@@ -0,0 +1,12 @@
+-- Status alert budget per user per budget: threshold terakhir yang sudah di-acknowledge
+-- pada periode tertentu. Alert muncul lagi jika spent mencapai threshold berikutnya
+-- atau periode budget berganti.
+CREATE TABLE IF NOT EXISTS budget_alerts (
+    budget_id INTEGER PRIMARY KEY REFERENCES budgets(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    period_start DATE NOT NULL,
+    threshold INTEGER NOT NULL,
+    acknowledged_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_budget_alerts_user_id ON budget_alerts(user_id);
@@ -0,0 +1,2 @@
+-- Versi sesi user: dinaikkan saat password diganti/direset agar semua access token lama ditolak
+ALTER TABLE users ADD COLUMN IF NOT EXISTS token_version INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,20 @@
+-- Email dibandingkan tanpa membedakan huruf besar/kecil: simpan dalam bentuk trim + lowercase
+
+-- Akun yang bentrok setelah normalisasi: akun yang email-nya sudah lowercase (atau yang paling lama)
+-- mempertahankan alamatnya; akun lain diberi penanda "#duplicate-<id>" agar bisa diselesaikan manual
+WITH ranked AS (
+    SELECT id, ROW_NUMBER() OVER (
+        PARTITION BY LOWER(TRIM(email))
+        ORDER BY (email = LOWER(TRIM(email))) DESC, created_at ASC NULLS LAST, id
+    ) AS rn
+    FROM users
+)
+UPDATE users u
+SET email = LEFT(LOWER(TRIM(u.email)), 200) || '#duplicate-' || u.id::text, updated_at = NOW()
+FROM ranked r
+WHERE u.id = r.id AND r.rn > 1;
+
+UPDATE users SET email = LOWER(TRIM(email)), updated_at = NOW() WHERE email <> LOWER(TRIM(email));
+
+-- Satu email per akun, tidak peka huruf besar/kecil
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email_lower ON users (LOWER(email));
@@ -0,0 +1,16 @@
+-- Aturan kategorisasi otomatis: transaksi yang deskripsinya mengandung `pattern`
+-- (tanpa membedakan huruf besar/kecil) dimasukkan ke `kategori_id`
+CREATE TABLE IF NOT EXISTS categorization_rules (
+    id SERIAL PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    pattern VARCHAR(255) NOT NULL,
+    kategori_id INTEGER NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- Satu aturan per pattern per user
+CREATE UNIQUE INDEX IF NOT EXISTS idx_categorization_rules_user_pattern
+    ON categorization_rules (user_id, LOWER(pattern));
+
+CREATE INDEX IF NOT EXISTS idx_categorization_rules_kategori_id ON categorization_rules(kategori_id);
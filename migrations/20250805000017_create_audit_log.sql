@@ -0,0 +1,12 @@
+-- Audit log aktivitas penting per user (login, ganti password, hapus akun, transaksi besar).
+-- Tanpa foreign key ke users agar event penghapusan akun tetap tersimpan
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID NOT NULL,
+    event_type VARCHAR(50) NOT NULL,
+    ip_address VARCHAR(64),
+    detail JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_user_created_at ON audit_log(user_id, created_at DESC);
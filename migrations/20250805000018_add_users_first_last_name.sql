@@ -0,0 +1,13 @@
+-- Nama depan/belakang sebagai kolom sendiri; username menjadi display name yang bisa diubah terpisah
+ALTER TABLE users ADD COLUMN IF NOT EXISTS first_name VARCHAR(60);
+ALTER TABLE users ADD COLUMN IF NOT EXISTS last_name VARCHAR(60);
+
+-- Backfill dari username yang sebelumnya berisi "first last" (username = email berarti profile belum pernah diisi)
+UPDATE users
+SET first_name = LEFT(split_part(username, ' ', 1), 60),
+    last_name = LEFT(NULLIF(BTRIM(SUBSTRING(username FROM POSITION(' ' IN username) + 1)), ''), 60)
+WHERE first_name IS NULL AND username <> email AND POSITION(' ' IN username) > 0;
+
+UPDATE users
+SET first_name = LEFT(username, 60)
+WHERE first_name IS NULL AND username <> email;
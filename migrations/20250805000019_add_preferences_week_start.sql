@@ -0,0 +1,2 @@
+-- Hari pertama minggu untuk statistik mingguan berbasis kalender ('monday' atau 'sunday')
+ALTER TABLE user_preferences ADD COLUMN IF NOT EXISTS week_start VARCHAR(10) NOT NULL DEFAULT 'monday';
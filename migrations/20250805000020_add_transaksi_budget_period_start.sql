@@ -0,0 +1,3 @@
+-- Override periode budget per transaksi (mis. tagihan yang baru tercatat setelah periodenya lewat).
+-- NULL = periode ditentukan dari `tanggal`
+ALTER TABLE transaksi ADD COLUMN IF NOT EXISTS budget_period_start DATE;
@@ -0,0 +1,2 @@
+-- Urutan tampilan kategori pilihan user (PUT /api/kategori/reorder); kategori dengan nilai sama diurutkan per nama
+ALTER TABLE categories ADD COLUMN IF NOT EXISTS sort_order INTEGER NOT NULL DEFAULT 0;
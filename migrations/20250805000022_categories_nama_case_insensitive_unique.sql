@@ -0,0 +1,15 @@
+-- Nama kategori unik per pemilik tanpa membedakan huruf besar/kecil, sama seperti pengecekan di API.
+-- Duplikat lama (mis. "Transport" dan "transport") diberi akhiran id agar index bisa dibuat tanpa menghapus data
+UPDATE categories c
+SET nama = c.nama || ' (' || c.id || ')'
+WHERE EXISTS (
+    SELECT 1 FROM categories other
+    WHERE other.id < c.id
+        AND COALESCE(other.user_id, '00000000-0000-0000-0000-000000000000'::uuid)
+            = COALESCE(c.user_id, '00000000-0000-0000-0000-000000000000'::uuid)
+        AND LOWER(other.nama) = LOWER(c.nama)
+);
+
+DROP INDEX IF EXISTS idx_categories_owner_nama;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_categories_owner_nama
+    ON categories (COALESCE(user_id, '00000000-0000-0000-0000-000000000000'::uuid), LOWER(nama));
@@ -0,0 +1,80 @@
+use axum::http::HeaderMap;
+use serde_json::Value;
+use sqlx::{Postgres, Transaction};
+use std::env;
+use uuid::Uuid;
+
+use crate::database::Database;
+
+// Jenis event audit log
+pub const EVENT_LOGIN: &str = "login";
+pub const EVENT_PASSWORD_CHANGE: &str = "password_change";
+pub const EVENT_ACCOUNT_DELETED: &str = "account_deleted";
+pub const EVENT_LARGE_TRANSAKSI: &str = "large_transaksi";
+pub const EVENT_TRANSAKSI_UNDO: &str = "transaksi_undo";
+
+// Batas default transaksi besar (dalam minor unit), bisa diubah lewat LARGE_TRANSAKSI_THRESHOLD
+const DEFAULT_LARGE_TRANSAKSI_THRESHOLD: i32 = 1_000_000;
+
+// Panjang maksimum IP yang disimpan (sesuai kolom audit_log.ip_address)
+const MAX_IP_LEN: usize = 64;
+
+pub fn large_transaksi_threshold() -> i32 {
+    env::var("LARGE_TRANSAKSI_THRESHOLD")
+        .ok()
+        .and_then(|value| value.trim().parse::<i32>().ok())
+        .filter(|threshold| *threshold > 0)
+        .unwrap_or(DEFAULT_LARGE_TRANSAKSI_THRESHOLD)
+}
+
+// IP client dari header proxy (X-Forwarded-For lalu X-Real-IP). Header ini bisa dipalsukan
+// jika server tidak berada di belakang proxy, jadi hanya dipakai sebagai informasi
+pub fn client_ip(headers: &HeaderMap) -> Option<String> {
+    let forwarded_for = headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next());
+    let real_ip = headers.get("x-real-ip").and_then(|value| value.to_str().ok());
+
+    forwarded_for
+        .or(real_ip)
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty() && ip.len() <= MAX_IP_LEN)
+        .map(str::to_string)
+}
+
+// Catat event ke audit log. Best-effort: kegagalan hanya dicatat di log server
+// dan tidak pernah menggagalkan request utama
+pub async fn record(db: &Database, user_id: Uuid, event_type: &str, ip_address: Option<&str>, detail: Option<Value>) {
+    let result = sqlx::query("INSERT INTO audit_log (user_id, event_type, ip_address, detail) VALUES ($1, $2, $3, $4)")
+        .bind(user_id)
+        .bind(event_type)
+        .bind(ip_address)
+        .bind(detail)
+        .execute(db)
+        .await;
+
+    if let Err(err) = result {
+        eprintln!("Audit log error ({} user {}): {:?}", event_type, user_id, err);
+    }
+}
+
+// Catat event di dalam DB transaction yang sedang berjalan, untuk event yang ikut menentukan
+// logika bisnis (mis. undo) sehingga harus di-commit atau di-rollback bersama perubahan datanya
+pub async fn record_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    event_type: &str,
+    ip_address: Option<&str>,
+    detail: Option<Value>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO audit_log (user_id, event_type, ip_address, detail) VALUES ($1, $2, $3, $4)")
+        .bind(user_id)
+        .bind(event_type)
+        .bind(ip_address)
+        .bind(detail)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
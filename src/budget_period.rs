@@ -0,0 +1,312 @@
+use chrono::{Datelike, Local, Months, NaiveDate};
+use sqlx::{FromRow, PgExecutor, Postgres, Row, Transaction};
+use std::{env, time::Duration};
+use uuid::Uuid;
+
+use crate::database::Database;
+use crate::models::budget::{Budget, RecalculateSummary};
+
+// Interval default pengecekan pergantian periode, bisa diubah lewat BUDGET_RESET_INTERVAL_SECS
+const DEFAULT_RESET_INTERVAL_SECS: u64 = 3600;
+
+// Jumlah budget per DB transaction saat recalculate global, agar lock tidak ditahan terlalu lama
+const RECALCULATE_BATCH_SIZE: i64 = 500;
+
+// Transaksi dihitung ke periode budget dari `budget_period_start` jika diisi (override), selain itu
+// dari `tanggal`; semua query spent memakai COALESCE(t.budget_period_start, t.tanggal) dan dibatasi
+// ke [period_start, period_start + 1 bulan), jadi transaksi bertanggal di masa depan baru dihitung
+// saat periodenya tiba
+
+// Awal periode (bulanan) yang memuat tanggal tertentu
+pub fn period_start_for(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+// Tanggal terakhir periode yang dimulai pada `period_start`
+pub fn period_end_for(period_start: NaiveDate) -> NaiveDate {
+    period_start
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .unwrap_or(period_start)
+}
+
+// Awal efektif budget di periode berjalan: budget yang dibuat di tengah periode
+// dihitung sejak tanggal dibuat, bukan sejak awal bulan
+pub fn effective_period_start(budget: &Budget) -> NaiveDate {
+    let period_end = period_end_for(budget.period_start);
+    budget
+        .created_at
+        .map(|created_at| created_at.with_timezone(&Local).date_naive())
+        .unwrap_or(budget.period_start)
+        .clamp(budget.period_start, period_end)
+}
+
+// Bagian periode (0.0–1.0) yang sudah berjalan per `today`, dihitung dari awal efektif (inklusif)
+pub fn period_elapsed_fraction(effective_start: NaiveDate, period_end: NaiveDate, today: NaiveDate) -> f64 {
+    if today < effective_start {
+        return 0.0;
+    }
+    let total_days = (period_end - effective_start).num_days() + 1;
+    let elapsed_days = (today.min(period_end) - effective_start).num_days() + 1;
+    elapsed_days as f64 / total_days as f64
+}
+
+// Pindahkan semua budget yang periodenya sudah lewat ke periode berjalan, satu periode per langkah:
+// setiap periode yang terlewat (mis. server mati atau budget lama tidak tersentuh) mendapat snapshot
+// budget_history dan carry-over sendiri, lalu `spent` dihitung ulang untuk periode berikutnya.
+// Sisa dihitung dari budget efektif (amount + carry_over), jadi carry-over yang belum terpakai ikut terbawa lagi.
+// Mengembalikan jumlah budget yang dipindahkan
+pub async fn reset_expired_periods(db: &Database, today: NaiveDate) -> Result<u64, sqlx::Error> {
+    let current_start = period_start_for(today);
+    let mut moved = 0;
+
+    loop {
+        let result = sqlx::query(
+            r#"
+            WITH expired AS (
+                SELECT b.id, b.period_start, b.amount, b.carry_over, b.rollover,
+                    (b.period_start + INTERVAL '1 month')::date as next_start,
+                    prev.total as prev_spent, next.total as next_spent
+                FROM budgets b
+                CROSS JOIN LATERAL (
+                    SELECT COALESCE(SUM(t.jumlah), 0)::INT as total FROM transaksi t
+                    WHERE t.user_id = b.user_id AND t.kategori_id = b.kategori_id
+                        AND t.tipe = 'pengeluaran'
+                        AND COALESCE(t.budget_period_start, t.tanggal) >= b.period_start
+                        AND COALESCE(t.budget_period_start, t.tanggal) < b.period_start + INTERVAL '1 month'
+                ) prev
+                CROSS JOIN LATERAL (
+                    SELECT COALESCE(SUM(t.jumlah), 0)::INT as total FROM transaksi t
+                    WHERE t.user_id = b.user_id AND t.kategori_id = b.kategori_id
+                        AND t.tipe = 'pengeluaran'
+                        AND COALESCE(t.budget_period_start, t.tanggal) >= b.period_start + INTERVAL '1 month'
+                        AND COALESCE(t.budget_period_start, t.tanggal) < b.period_start + INTERVAL '2 months'
+                ) next
+                WHERE b.period_start < $1
+                FOR UPDATE OF b
+            ),
+            history AS (
+                INSERT INTO budget_history (budget_id, period_start, period_end, amount, carry_over, spent)
+                SELECT id, period_start, next_start - 1, amount, carry_over, prev_spent FROM expired
+                ON CONFLICT (budget_id, period_start) DO NOTHING
+            )
+            UPDATE budgets b SET
+                carry_over = CASE
+                    WHEN e.rollover THEN GREATEST(e.amount + e.carry_over - e.prev_spent, 0)
+                    ELSE 0
+                END,
+                spent = e.next_spent,
+                period_start = e.next_start,
+                updated_at = NOW()
+            FROM expired e
+            WHERE b.id = e.id
+            "#
+        )
+        .bind(current_start)
+        .execute(db)
+        .await?;
+
+        // Langkah pertama memindahkan semua budget yang kedaluwarsa; langkah berikutnya hanya
+        // budget yang masih tertinggal lebih dari satu periode
+        if moved == 0 {
+            moved = result.rows_affected();
+        }
+        if result.rows_affected() == 0 {
+            break;
+        }
+    }
+
+    Ok(moved)
+}
+
+// Hitung ulang `spent` semua budget dari transaksi periode berjalan (sama dengan perhitungan
+// saat reset periode). Diproses per batch berurutan id; setiap batch satu statement/transaksi.
+pub async fn recalculate_all_spent(db: &Database) -> Result<RecalculateSummary, sqlx::Error> {
+    let mut summary = RecalculateSummary::default();
+    let mut last_id = 0;
+
+    loop {
+        let (batch_last_id, checked, corrected, drift) = sqlx::query_as::<_, (Option<i32>, i64, i64, i64)>(
+            r#"
+            WITH batch AS (
+                SELECT b.id, COALESCE(b.spent, 0) as old_spent, actual.total as new_spent
+                FROM budgets b
+                CROSS JOIN LATERAL (
+                    SELECT COALESCE(SUM(t.jumlah), 0)::INT as total FROM transaksi t
+                    WHERE t.user_id = b.user_id AND t.kategori_id = b.kategori_id
+                        AND t.tipe = 'pengeluaran'
+                        AND COALESCE(t.budget_period_start, t.tanggal) >= b.period_start
+                        AND COALESCE(t.budget_period_start, t.tanggal) < b.period_start + INTERVAL '1 month'
+                ) actual
+                WHERE b.id > $1
+                ORDER BY b.id
+                LIMIT $2
+                FOR UPDATE OF b
+            ),
+            updated AS (
+                UPDATE budgets b SET spent = batch.new_spent, updated_at = NOW()
+                FROM batch
+                WHERE b.id = batch.id AND batch.old_spent <> batch.new_spent
+                RETURNING ABS(batch.new_spent - batch.old_spent)::BIGINT as drift
+            )
+            SELECT
+                (SELECT MAX(id) FROM batch),
+                (SELECT COUNT(*) FROM batch),
+                (SELECT COUNT(*) FROM updated),
+                (SELECT COALESCE(SUM(drift), 0)::BIGINT FROM updated)
+            "#
+        )
+        .bind(last_id)
+        .bind(RECALCULATE_BATCH_SIZE)
+        .fetch_one(db)
+        .await?;
+
+        summary.checked += checked;
+        summary.corrected += corrected;
+        summary.total_drift += drift;
+
+        match batch_last_id {
+            Some(id) => last_id = id,
+            None => break,
+        }
+    }
+
+    Ok(summary)
+}
+
+
+// Kunci baris budget user untuk kategori tertentu (urut id agar tidak deadlock) sampai DB transaction selesai.
+// Request lain yang mengubah spent kategori yang sama menunggu di sini; karena DB transaction memakai
+// READ COMMITTED, statement setelah lock didapat sudah melihat transaksi yang di-commit request tersebut
+pub async fn lock_budgets(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    kategori_ids: &[i32],
+) -> Result<Vec<Budget>, sqlx::Error> {
+    sqlx::query_as::<_, Budget>(
+        "SELECT * FROM budgets WHERE user_id = $1 AND kategori_id = ANY($2) ORDER BY id FOR UPDATE"
+    )
+    .bind(user_id)
+    .bind(kategori_ids)
+    .fetch_all(&mut **tx)
+    .await
+}
+
+// Hitung ulang spent budget user untuk kategori tertentu dari transaksi di periode berjalan
+// (aturan yang sama dengan recalculate_all_spent). Mengembalikan budget terbaru beserta spent sebelumnya
+pub async fn recalculate_spent(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    kategori_ids: &[i32],
+) -> Result<Vec<(Budget, i32)>, sqlx::Error> {
+    // Lock diambil di statement terpisah: SUM di bawah memakai snapshot awal statement, jadi jika lock
+    // baru didapat di statement yang sama, transaksi yang di-commit selama menunggu tidak ikut terhitung
+    lock_budgets(tx, user_id, kategori_ids).await?;
+
+    let rows = sqlx::query(
+        r#"
+        WITH target AS (
+            SELECT b.id, COALESCE(b.spent, 0) as old_spent, actual.total as new_spent
+            FROM budgets b
+            CROSS JOIN LATERAL (
+                SELECT COALESCE(SUM(t.jumlah), 0)::INT as total FROM transaksi t
+                WHERE t.user_id = b.user_id AND t.kategori_id = b.kategori_id
+                    AND t.tipe = 'pengeluaran'
+                    AND COALESCE(t.budget_period_start, t.tanggal) >= b.period_start
+                    AND COALESCE(t.budget_period_start, t.tanggal) < b.period_start + INTERVAL '1 month'
+            ) actual
+            WHERE b.user_id = $1 AND b.kategori_id = ANY($2)
+            FOR UPDATE OF b
+        )
+        UPDATE budgets b SET spent = target.new_spent, updated_at = NOW()
+        FROM target
+        WHERE b.id = target.id
+        RETURNING b.*, target.old_spent
+        "#
+    )
+    .bind(user_id)
+    .bind(kategori_ids)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    rows.iter()
+        .map(|row| Ok((Budget::from_row(row)?, row.try_get("old_spent")?)))
+        .collect()
+}
+
+// true jika `period_start` adalah periode berjalan atau periode lama (tercatat di budget_history)
+// dari budget user untuk kategori tersebut; dipakai untuk memvalidasi override budget_period_start
+pub async fn is_budget_period<'e>(
+    executor: impl PgExecutor<'e>,
+    user_id: Uuid,
+    kategori_id: i32,
+    period_start: NaiveDate,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM budgets b
+            WHERE b.user_id = $1 AND b.kategori_id = $2
+                AND (
+                    b.period_start = $3
+                    OR EXISTS(SELECT 1 FROM budget_history h WHERE h.budget_id = b.id AND h.period_start = $3)
+                )
+        )
+        "#
+    )
+    .bind(user_id)
+    .bind(kategori_id)
+    .bind(period_start)
+    .fetch_one(executor)
+    .await
+}
+
+// Hitung ulang spent snapshot budget_history periode `period_start` untuk kategori tertentu, setelah
+// transaksi dengan override ke periode tersebut dibuat, diubah, atau dihapus
+pub async fn recalculate_history_spent(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    kategori_ids: &[i32],
+    period_start: NaiveDate,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE budget_history h SET spent = (
+            SELECT COALESCE(SUM(t.jumlah), 0)::INT FROM transaksi t
+            WHERE t.user_id = b.user_id AND t.kategori_id = b.kategori_id
+                AND t.tipe = 'pengeluaran'
+                AND COALESCE(t.budget_period_start, t.tanggal) BETWEEN h.period_start AND h.period_end
+        )
+        FROM budgets b
+        WHERE h.budget_id = b.id AND b.user_id = $1 AND b.kategori_id = ANY($2) AND h.period_start = $3
+        "#
+    )
+    .bind(user_id)
+    .bind(kategori_ids)
+    .bind(period_start)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+// Jalankan reset periode saat startup lalu secara berkala di background
+pub fn spawn_reset_task(db: Database) {
+    let interval_secs = env::var("BUDGET_RESET_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_RESET_INTERVAL_SECS);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            match reset_expired_periods(&db, Local::now().naive_local().date()).await {
+                Ok(0) => {}
+                Ok(count) => println!("🔄 {} budget dipindahkan ke periode baru", count),
+                Err(err) => eprintln!("Budget period reset error: {:?}", err),
+            }
+        }
+    });
+}
@@ -1,25 +1,129 @@
-use sqlx::{postgres::PgPoolOptions, PgPool};
-use std::env;
+use sqlx::{
+    migrate::{MigrateError, Migration, Migrator},
+    postgres::PgPoolOptions,
+    PgPool, Postgres, Transaction,
+};
+use std::{env, time::Duration};
+use uuid::Uuid;
 
 pub type Database = PgPool;
 
+// Default retry koneksi saat startup, bisa diubah lewat DB_CONNECT_MAX_ATTEMPTS dan DB_CONNECT_BACKOFF_MS
+const DEFAULT_CONNECT_MAX_ATTEMPTS: u32 = 5;
+const DEFAULT_CONNECT_BACKOFF_MS: u64 = 500;
+const MAX_CONNECT_BACKOFF_MS: u64 = 30_000;
+// Batas waktu tiap percobaan (sqlx sendiri menunggu hingga 30 detik sebelum menyerah)
+const CONNECT_ATTEMPT_TIMEOUT_SECS: u64 = 5;
+
+// Hubungkan ke PostgreSQL dengan retry + exponential backoff, supaya app tidak langsung crash
+// jika database belum siap (mis. urutan startup di Docker Compose)
+pub async fn connect_with_retry(database_url: &str, max_connections: u32) -> Result<Database, sqlx::Error> {
+    let max_attempts = env::var("DB_CONNECT_MAX_ATTEMPTS")
+        .ok()
+        .and_then(|value| value.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_CONNECT_MAX_ATTEMPTS)
+        .max(1);
+    let mut backoff_ms = env::var("DB_CONNECT_BACKOFF_MS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_CONNECT_BACKOFF_MS);
+
+    let mut attempt = 1;
+    loop {
+        let connect = PgPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(database_url);
+        let result = tokio::time::timeout(Duration::from_secs(CONNECT_ATTEMPT_TIMEOUT_SECS), connect)
+            .await
+            .unwrap_or(Err(sqlx::Error::PoolTimedOut));
+
+        match result {
+            Ok(pool) => return Ok(pool),
+            Err(err) if attempt < max_attempts => {
+                eprintln!(
+                    "⚠️ Koneksi database gagal (percobaan {}/{}): {}. Mencoba lagi dalam {} ms",
+                    attempt, max_attempts, err, backoff_ms
+                );
+                tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
+                backoff_ms = (backoff_ms * 2).min(MAX_CONNECT_BACKOFF_MS);
+                attempt += 1;
+            }
+            Err(err) => {
+                eprintln!("❌ Koneksi database gagal setelah {} percobaan: {}", attempt, err);
+                return Err(err);
+            }
+        }
+    }
+}
+
 pub async fn create_database_connection() -> Result<Database, sqlx::Error> {
     dotenvy::dotenv().ok();
     
     let database_url = env::var("DATABASE_URL")
         .expect("DATABASE_URL must be set in .env file");
 
-    let pool = PgPoolOptions::new()
-        .max_connections(20)
-        .connect(&database_url)
-        .await?;
+    let pool = connect_with_retry(&database_url, 20).await?;
 
     println!("✅ Database connected successfully");
     Ok(pool)
 }
 
-pub async fn run_migrations(pool: &Database) -> Result<(), sqlx::Error> {
-    sqlx::migrate!("./migrations").run(pool).await?;
+// Kunci baris user sampai DB transaction selesai, supaya pengecekan batas per user (jumlah kategori
+// atau budget) dari request bersamaan berjalan bergantian. FOR NO KEY UPDATE tidak bentrok dengan
+// foreign key check saat insert ke tabel yang mereferensikan users, jadi insert lain tidak ikut tertahan
+pub async fn lock_user(tx: &mut Transaction<'_, Postgres>, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT 1 FROM users WHERE id = $1 FOR NO KEY UPDATE")
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+// Migrations yang di-embed saat compile dari folder ./migrations
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+pub async fn run_migrations(pool: &Database) -> Result<(), MigrateError> {
+    MIGRATOR.run(pool).await?;
     println!("✅ Migrations executed successfully");
     Ok(())
 }
+
+// Pesan error migration yang bisa langsung ditindaklanjuti: migration mana yang gagal dan cara memperbaikinya
+pub async fn migration_error_message(pool: &Database, err: &MigrateError) -> String {
+    match err {
+        MigrateError::Execute(source) => match failed_migration(pool).await {
+            Some(migration) => format!(
+                "Migration {} ({}) gagal dijalankan: {}. Perubahan migration ini di-rollback; perbaiki file SQL-nya lalu jalankan ulang server.",
+                migration.version, migration.description, source
+            ),
+            None => format!("Gagal menjalankan migrations: {}", source),
+        },
+        MigrateError::Dirty(version) => format!(
+            "Migration {} tercatat gagal sebagian (dirty). Periksa dan rapikan perubahan skema secara manual, \
+             lalu hapus barisnya dengan `DELETE FROM _sqlx_migrations WHERE version = {};` sebelum menjalankan ulang server.",
+            version, version
+        ),
+        MigrateError::VersionMismatch(version) => format!(
+            "Migration {} sudah dijalankan tetapi isi filenya berubah. Kembalikan file ke isi semula \
+             dan buat migration baru untuk perubahan tersebut.",
+            version
+        ),
+        MigrateError::VersionMissing(version) => format!(
+            "Migration {} sudah dijalankan di database tetapi filenya tidak ada di folder migrations. Kembalikan file tersebut.",
+            version
+        ),
+        other => format!("Gagal menjalankan migrations: {}", other),
+    }
+}
+
+// Migration pertama yang belum tercatat sukses; saat `Execute` gagal, itulah migration yang error
+async fn failed_migration(pool: &Database) -> Option<&'static Migration> {
+    let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+        .fetch_all(pool)
+        .await
+        .ok()?;
+
+    MIGRATOR
+        .iter()
+        .find(|migration| !migration.migration_type.is_down_migration() && !applied.contains(&migration.version))
+}
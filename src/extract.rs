@@ -0,0 +1,48 @@
+use axum::{
+    async_trait,
+    extract::{rejection::JsonRejection, FromRequest, Request},
+    http::StatusCode,
+    response::Json,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+// Pengganti extractor `Json` untuk body request: kesalahan Content-Type atau JSON
+// dijawab dengan envelope error standar, bukan teks polos bawaan axum
+pub struct JsonBody<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for JsonBody<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Json<Value>);
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match Json::<T>::from_request(request, state).await {
+            Ok(Json(value)) => Ok(JsonBody(value)),
+            Err(rejection) => Err(json_rejection_response(rejection)),
+        }
+    }
+}
+
+fn json_rejection_response(rejection: JsonRejection) -> (StatusCode, Json<Value>) {
+    let message = match &rejection {
+        JsonRejection::MissingJsonContentType(_) => {
+            "Header Content-Type harus 'application/json'.".to_string()
+        }
+        JsonRejection::JsonSyntaxError(_) => "Body request bukan JSON yang valid.".to_string(),
+        // Field wajib tidak ada atau tipenya salah; detail dari serde membantu integrator
+        JsonRejection::JsonDataError(err) => format!("Body JSON tidak sesuai format: {}", err.body_text()),
+        _ => "Body request tidak dapat dibaca.".to_string(),
+    };
+
+    (
+        rejection.status(),
+        Json(json!({
+            "status": "error",
+            "message": message
+        }))
+    )
+}
@@ -0,0 +1,148 @@
+use chrono::Weekday;
+
+use crate::models::profile::UserPreferences;
+
+// Simbol mata uang yang umum; kode lain ditampilkan apa adanya (mis. "CHF 1,500")
+fn currency_symbol(currency: &str) -> &str {
+    match currency {
+        "IDR" => "Rp",
+        "USD" => "$",
+        "EUR" => "€",
+        "GBP" => "£",
+        "JPY" => "¥",
+        "SGD" => "S$",
+        "MYR" => "RM",
+        other => other,
+    }
+}
+
+// Eksponen minor unit mata uang (ISO 4217): jumlah tersimpan = nilai x 10^eksponen
+pub fn currency_minor_unit(currency: &str) -> i16 {
+    match currency {
+        "IDR" | "JPY" | "KRW" | "VND" | "CLP" | "ISK" => 0,
+        "BHD" | "KWD" | "OMR" | "JOD" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+// Pemisah ribuan berdasarkan bahasa locale: "id-ID" -> '.', "en-US" -> ','
+fn thousands_separator(locale: &str) -> char {
+    let language = locale.split('-').next().unwrap_or("").to_ascii_lowercase();
+    match language.as_str() {
+        "id" | "de" | "es" | "it" | "nl" | "pt" | "tr" | "da" => '.',
+        "fr" | "sv" | "nb" | "fi" | "pl" | "cs" | "ru" => ' ',
+        _ => ',',
+    }
+}
+
+// Pemisah desimal berdasarkan bahasa locale: "id-ID"/"fr-FR" -> ',', "en-US" -> '.'
+fn decimal_separator(locale: &str) -> char {
+    let language = locale.split('-').next().unwrap_or("").to_ascii_lowercase();
+    match language.as_str() {
+        "id" | "de" | "es" | "it" | "nl" | "pt" | "tr" | "da"
+        | "fr" | "sv" | "nb" | "fi" | "pl" | "cs" | "ru" => ',',
+        _ => '.',
+    }
+}
+
+// Format jumlah dalam minor unit sesuai preferensi user,
+// mis. (1500000, 0) -> "Rp 1.500.000" dan (150, 2) dengan USD/en-US -> "$1.50"
+pub fn format_amount(amount: i64, minor_unit: i16, preferences: &UserPreferences) -> String {
+    let separator = thousands_separator(&preferences.locale);
+    let decimal_separator = decimal_separator(&preferences.locale);
+
+    let scale = 10u64.pow(minor_unit.max(0) as u32);
+    let digits = (amount.unsigned_abs() / scale).to_string();
+
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+
+    // Simbol berupa huruf diberi spasi ("Rp 1.000"), simbol non-huruf tidak ("$1,000")
+    let symbol = currency_symbol(&preferences.currency);
+    let spacing = if symbol.chars().last().is_some_and(|c| c.is_alphabetic()) { " " } else { "" };
+    let sign = if amount < 0 { "-" } else { "" };
+
+    if minor_unit > 0 {
+        let fraction = amount.unsigned_abs() % scale;
+        grouped.push(decimal_separator);
+        grouped.push_str(&format!("{:0width$}", fraction, width = minor_unit as usize));
+    }
+
+    format!("{}{}{}{}", sign, symbol, spacing, grouped)
+}
+
+// Label hari (mulai Senin) dan bulan per bahasa
+const HARI_ID: [&str; 7] = ["Sen", "Sel", "Rab", "Kam", "Jum", "Sab", "Min"];
+const HARI_EN: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const BULAN_ID: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "Mei", "Jun", "Jul", "Agu", "Sep", "Okt", "Nov", "Des"];
+const BULAN_EN: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+// Bahasa label tanggal untuk locale user: "en" untuk locale berbahasa Inggris, selain itu "id"
+pub fn label_language(locale: &str) -> &'static str {
+    let language = locale.split('-').next().unwrap_or("").to_ascii_lowercase();
+    match language.as_str() {
+        "en" => "en",
+        _ => "id",
+    }
+}
+
+// Singkatan nama hari sesuai locale, mis. Senin -> "Sen" (id-ID) atau "Mon" (en-US)
+pub fn weekday_label(weekday: Weekday, locale: &str) -> &'static str {
+    let labels = if label_language(locale) == "en" { &HARI_EN } else { &HARI_ID };
+    labels[weekday.num_days_from_monday() as usize]
+}
+
+// Singkatan nama bulan (1-12) sesuai locale, mis. 10 -> "Okt" (id-ID) atau "Oct" (en-US)
+pub fn month_label(month: u32, locale: &str) -> &'static str {
+    let labels = if label_language(locale) == "en" { &BULAN_EN } else { &BULAN_ID };
+    labels[(month.clamp(1, 12) - 1) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{currency_minor_unit, format_amount};
+    use crate::models::profile::UserPreferences;
+
+    fn preferences(currency: &str, locale: &str) -> UserPreferences {
+        UserPreferences { currency: currency.to_string(), locale: locale.to_string(), ..UserPreferences::default() }
+    }
+
+    fn format(amount: i64, currency: &str, locale: &str) -> String {
+        format_amount(amount, currency_minor_unit(currency), &preferences(currency, locale))
+    }
+
+    #[test]
+    fn formats_idr_without_fraction() {
+        assert_eq!(format(1_500_000, "IDR", "id-ID"), "Rp 1.500.000");
+        assert_eq!(format(999, "IDR", "id-ID"), "Rp 999");
+        assert_eq!(format(0, "IDR", "id-ID"), "Rp 0");
+        assert_eq!(format(-25_000, "IDR", "id-ID"), "-Rp 25.000");
+    }
+
+    #[test]
+    fn formats_usd_with_two_decimals() {
+        assert_eq!(format(150, "USD", "en-US"), "$1.50");
+        assert_eq!(format(123_456_789, "USD", "en-US"), "$1,234,567.89");
+        assert_eq!(format(5, "USD", "en-US"), "$0.05");
+        assert_eq!(format(-150, "USD", "en-US"), "-$1.50");
+        assert_eq!(format(150_050, "USD", "id-ID"), "$1.500,50");
+    }
+
+    #[test]
+    fn formats_three_decimal_currency() {
+        assert_eq!(format(1_500_250, "KWD", "en-US"), "KWD 1,500.250");
+        assert_eq!(format(7, "BHD", "en-US"), "BHD 0.007");
+    }
+
+    #[test]
+    fn space_grouped_locales_use_decimal_comma() {
+        assert_eq!(format(150_050, "EUR", "fr-FR"), "€1 500,50");
+        assert_eq!(format(150_050, "EUR", "pl-PL"), "€1 500,50");
+        assert_eq!(format(1_234_567_890, "EUR", "sv-SE"), "€12 345 678,90");
+    }
+}
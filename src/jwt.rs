@@ -0,0 +1,239 @@
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts},
+    http::{header, request::Parts, StatusCode},
+    response::Json,
+};
+use chrono::Utc;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::env;
+use uuid::Uuid;
+
+use crate::database::Database;
+
+// Masa berlaku access token default (menit), bisa diubah lewat JWT_EXPIRES_IN_MINUTES
+const DEFAULT_EXPIRES_IN_MINUTES: i64 = 60;
+// Masa berlaku refresh token default (hari), bisa diubah lewat JWT_REFRESH_EXPIRES_IN_DAYS
+const DEFAULT_REFRESH_EXPIRES_IN_DAYS: i64 = 30;
+
+// Secret contoh yang pernah/masih beredar (.env lama dan .env.example); hanya boleh dipakai saat APP_ENV=development
+const KNOWN_DEV_SECRETS: [&str; 2] = ["savior-dev-jwt-secret-change-me", "change-me"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: Uuid,
+    pub role: String,
+    pub iat: i64,
+    pub exp: i64,
+    #[serde(default)]
+    pub ver: i32, // token_version user saat token dibuat
+}
+
+fn jwt_secret() -> String {
+    env::var("JWT_SECRET").expect("JWT_SECRET tidak ditemukan di .env")
+}
+
+// Cek JWT_SECRET saat startup: wajib diisi, dan secret contoh ditolak kecuali APP_ENV=development,
+// karena siapa pun yang tahu secret tersebut bisa memalsukan token
+pub fn check_secret_config() -> Result<(), String> {
+    check_secret(env::var("JWT_SECRET").ok().as_deref(), env::var("APP_ENV").ok().as_deref())
+}
+
+fn check_secret(secret: Option<&str>, app_env: Option<&str>) -> Result<(), String> {
+    let secret = secret.map(str::trim).filter(|secret| !secret.is_empty());
+    let Some(secret) = secret else {
+        return Err("JWT_SECRET belum diset.".to_string());
+    };
+
+    let is_development = app_env.is_some_and(|app_env| app_env.trim().eq_ignore_ascii_case("development"));
+    if KNOWN_DEV_SECRETS.contains(&secret) && !is_development {
+        return Err("JWT_SECRET masih memakai nilai contoh; ganti dengan secret acak atau set APP_ENV=development.".to_string());
+    }
+
+    Ok(())
+}
+
+pub fn expires_in_minutes() -> i64 {
+    env::var("JWT_EXPIRES_IN_MINUTES")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_EXPIRES_IN_MINUTES)
+}
+
+pub fn refresh_expires_in_days() -> i64 {
+    env::var("JWT_REFRESH_EXPIRES_IN_DAYS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_REFRESH_EXPIRES_IN_DAYS)
+}
+
+// Buat access token untuk user; `token_version` diambil dari kolom users.token_version
+pub fn create_token(user_id: Uuid, role: &str, token_version: i32) -> Result<String, jsonwebtoken::errors::Error> {
+    let now = Utc::now().timestamp();
+    let claims = Claims {
+        sub: user_id,
+        role: role.to_string(),
+        iat: now,
+        exp: now + expires_in_minutes() * 60,
+        ver: token_version,
+    };
+
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(jwt_secret().as_bytes()))
+}
+
+// Validasi access token dan ambil claims-nya
+pub fn decode_token(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+    decode::<Claims>(token, &DecodingKey::from_secret(jwt_secret().as_bytes()), &Validation::default())
+        .map(|data| data.claims)
+}
+
+// Buat refresh token acak (opaque), hanya hash-nya yang disimpan di database
+pub fn generate_refresh_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+// Hash SHA-256 dari refresh token untuk disimpan/dicari di tabel refresh_tokens
+pub fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+// User yang sudah terautentikasi lewat header `Authorization: Bearer <token>`.
+// Token ditolak jika versinya tidak sama dengan token_version user (password sudah diganti).
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user_id: Uuid,
+    pub role: String, // Role saat ini dari tabel users, bukan dari klaim token
+
+    pub expires_at: i64, // Klaim `exp` token (unix timestamp, detik)
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    Database: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Json<Value>);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| {
+                (
+                    StatusCode::UNAUTHORIZED,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Token tidak ditemukan."
+                    }))
+                )
+            })?;
+
+        let claims = decode_token(token).map_err(|_| {
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({
+                    "status": "error",
+                    "message": "Token tidak valid atau sudah kedaluwarsa."
+                }))
+            )
+        })?;
+
+        let db = Database::from_ref(state);
+        // Role ikut dibaca dari database, jadi admin yang diturunkan langsung kehilangan akses
+        // tanpa menunggu access token kedaluwarsa
+        let user = sqlx::query_as::<_, (i32, String)>("SELECT token_version, role FROM users WHERE id = $1")
+            .bind(claims.sub)
+            .fetch_optional(&db)
+            .await
+            .map_err(|err| {
+                eprintln!("Database error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Terjadi kesalahan pada server."
+                    }))
+                )
+            })?;
+
+        let role = match user {
+            Some((token_version, role)) if token_version == claims.ver => role,
+            _ => {
+                return Err((
+                    StatusCode::UNAUTHORIZED,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Sesi sudah tidak berlaku. Silakan login kembali."
+                    }))
+                ));
+            }
+        };
+
+        Ok(AuthUser {
+            user_id: claims.sub,
+            role,
+            expires_at: claims.exp,
+        })
+    }
+}
+
+// User terautentikasi dengan role admin
+#[derive(Debug, Clone)]
+pub struct AdminUser(pub AuthUser);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AdminUser
+where
+    Database: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Json<Value>);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user = AuthUser::from_request_parts(parts, state).await?;
+
+        if user.role != "admin" {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(json!({
+                    "status": "error",
+                    "message": "Akses hanya untuk admin."
+                }))
+            ));
+        }
+
+        Ok(AdminUser(user))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_secret;
+
+    #[test]
+    fn missing_or_blank_secret_is_rejected() {
+        assert!(check_secret(None, Some("development")).is_err());
+        assert!(check_secret(Some("   "), None).is_err());
+    }
+
+    #[test]
+    fn known_dev_secret_is_only_allowed_in_development() {
+        assert!(check_secret(Some("savior-dev-jwt-secret-change-me"), None).is_err());
+        assert!(check_secret(Some("change-me"), Some("production")).is_err());
+        assert!(check_secret(Some("change-me"), Some("Development")).is_ok());
+    }
+
+    #[test]
+    fn random_secret_is_accepted() {
+        assert!(check_secret(Some("q4N7v0x2Lr9TfYc8"), None).is_ok());
+    }
+}
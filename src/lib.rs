@@ -0,0 +1,218 @@
+use axum::{
+    extract::Request,
+    routing::{get, post, put, delete},
+    http::{header, HeaderValue, StatusCode},
+    middleware,
+    response::{IntoResponse, Json, Response},
+    Router,
+};
+use serde_json::json;
+use sqlx::PgPool;
+use tower::ServiceExt;
+use tower_http::{
+    services::{ServeDir, ServeFile},
+    cors::{Any, CorsLayer},
+};
+
+pub mod audit;
+pub mod budget_period;
+pub mod database;
+pub mod extract;
+pub mod format;
+pub mod jwt;
+pub mod mail;
+pub mod metrics;
+pub mod models;
+pub mod pagination;
+pub mod password;
+pub mod quick_input;
+pub mod request_id;
+pub mod response;
+pub mod retention;
+pub mod routes;
+pub mod state;
+pub mod validate;
+pub mod webhook;
+
+use state::AppState;
+use routes::auth::{signup, signin, forgot_password, refresh, logout, validate_token};
+use routes::user::{get_user_by_id, get_user_by_email, get_users, delete_user};
+use routes::profile::{get_me, get_profile, update_profile, update_display_name, update_email, update_password, update_daily_limit, get_preferences, update_preferences, get_activity};
+use routes::kategori::{get_all_kategori, create_kategori, sync_kategori, reorder_kategori, update_kategori, delete_kategori, get_kategori_by_id};
+use routes::budget::{get_user_budgets, get_budget_page, create_budget, upsert_budget, bulk_create_budget, update_budget, delete_budget, get_budget_by_id, get_budget_report, get_budget_burndown, get_budget_history, get_budget_alerts, acknowledge_budget_alerts, recalculate_all_budgets, get_budget_suggestions};
+use routes::transaksi::{get_user_transaksi, create_transaksi, update_transaksi, delete_transaksi, get_transaksi_by_id, get_categories_used, get_recent_categories, get_transaksi_by_day, reassign_transaksi_kategori, auto_categorize_transaksi, undo_last_transaksi, clear_transaksi, split_transaksi, quick_transaksi, preview_transaksi};
+use routes::categorization_rule::{get_user_categorization_rules, create_categorization_rule, delete_categorization_rule};
+use routes::webhook::{get_user_webhooks, create_webhook, update_webhook, delete_webhook};
+use routes::statistik::{get_user_statistik, get_kategori_statistik, get_spending_ranges, get_user_monthly_spending, get_dashboard_data, get_spending_heatmap, get_daily_status, get_spending_streaks, get_top_descriptions, get_cashflow, get_day_of_month_spending, get_overview, get_spending_velocity, compare_statistik, get_largest_transaksi, get_unused_categories, get_laporan_bulanan};
+
+// Tabel routes API tanpa state; state disuntikkan oleh `app`
+pub fn api_router() -> Router<AppState> {
+    Router::new()
+        // Auth
+        .route("/signup", post(signup))
+        .route("/signin", post(signin))
+        .route("/forgot-password", post(forgot_password))
+        .route("/api/refresh", post(refresh))
+        .route("/api/logout", post(logout))
+        .route("/api/auth/validate", get(validate_token))
+
+        // User
+        .route("/api/user/:user_id", get(get_user_by_id))
+
+        // Admin
+        .route("/api/admin/users", get(get_users))
+        .route("/api/admin/users/by-email", get(get_user_by_email))
+        .route("/api/admin/users/:user_id", delete(delete_user))
+        .route("/api/admin/recalculate-budgets", post(recalculate_all_budgets))
+
+        // Profile
+        .route("/api/me", get(get_me))
+        .route("/api/profile/:user_id", get(get_profile))
+        .route("/api/profile/:user_id", put(update_profile))
+        .route("/api/profile/:user_id/display-name", put(update_display_name))
+        .route("/api/profile/:user_id/email", put(update_email))
+        .route("/api/profile/:user_id/password", put(update_password))
+        .route("/api/profile/:user_id/daily-limit", put(update_daily_limit))
+        .route("/api/profile/:user_id/preferences", get(get_preferences))
+        .route("/api/profile/:user_id/preferences", put(update_preferences))
+        .route("/api/profile/:user_id/activity", get(get_activity))
+
+        // Kategori
+        .route("/api/kategori", get(get_all_kategori))
+        .route("/api/kategori", post(create_kategori))
+        .route("/api/kategori/sync", post(sync_kategori))
+        .route("/api/kategori/reorder", put(reorder_kategori))
+        .route("/api/kategori/:id", get(get_kategori_by_id))
+        .route("/api/kategori/:id", put(update_kategori))
+        .route("/api/kategori/:id", delete(delete_kategori))
+
+        // Budget
+        .route("/api/budget/:user_id", get(get_user_budgets))
+        .route("/api/budget/:user_id", post(create_budget))
+        .route("/api/budget/:user_id/bulk", post(bulk_create_budget))
+        .route("/api/budget/:user_id/page", get(get_budget_page))
+        .route("/api/budget/:user_id/report", get(get_budget_report))
+        .route("/api/budget/:user_id/alerts", get(get_budget_alerts))
+        .route("/api/budget/:user_id/suggestions", get(get_budget_suggestions))
+        .route("/api/budget/:user_id/kategori/:kategori_id", put(upsert_budget))
+        .route("/api/budget/:user_id/alerts/ack", post(acknowledge_budget_alerts))
+        .route("/api/budget/:user_id/:budget_id", get(get_budget_by_id))
+        .route("/api/budget/:user_id/:budget_id", put(update_budget))
+        .route("/api/budget/:user_id/:budget_id", delete(delete_budget))
+        .route("/api/budget/:user_id/:budget_id/burndown", get(get_budget_burndown))
+        .route("/api/budget/:user_id/:budget_id/history", get(get_budget_history))
+
+        // Transaksi
+        .route("/api/transaksi/:user_id", get(get_user_transaksi))
+        .route("/api/transaksi/:user_id", post(create_transaksi))
+        .route("/api/transaksi/:user_id/categories-used", get(get_categories_used))
+        .route("/api/transaksi/:user_id/recent-categories", get(get_recent_categories))
+        .route("/api/transaksi/:user_id/day/:date", get(get_transaksi_by_day))
+        .route("/api/transaksi/:user_id/quick", post(quick_transaksi))
+        .route("/api/transaksi/:user_id/preview", post(preview_transaksi))
+        .route("/api/transaksi/:user_id/reassign", post(reassign_transaksi_kategori))
+        .route("/api/transaksi/:user_id/auto-categorize", post(auto_categorize_transaksi))
+        .route("/api/transaksi/:user_id/undo-last", post(undo_last_transaksi))
+        .route("/api/transaksi/:user_id/all", delete(clear_transaksi))
+        .route("/api/transaksi/:user_id/:transaksi_id", get(get_transaksi_by_id))
+        .route("/api/transaksi/:user_id/:transaksi_id", put(update_transaksi))
+        .route("/api/transaksi/:user_id/:transaksi_id", delete(delete_transaksi))
+        .route("/api/transaksi/:user_id/:transaksi_id/split", post(split_transaksi))
+
+        // Statistik
+        .route("/api/statistik/ranges", get(get_spending_ranges))
+        .route("/api/statistik/:user_id", get(get_user_statistik))
+        .route("/api/statistik/:user_id/monthly", get(get_user_monthly_spending))
+        .route("/api/statistik/:user_id/heatmap", get(get_spending_heatmap))
+        .route("/api/statistik/:user_id/daily-status", get(get_daily_status))
+        .route("/api/statistik/:user_id/streaks", get(get_spending_streaks))
+        .route("/api/statistik/:user_id/top-descriptions", get(get_top_descriptions))
+        .route("/api/statistik/:user_id/largest", get(get_largest_transaksi))
+        .route("/api/statistik/:user_id/unused-categories", get(get_unused_categories))
+        .route("/api/statistik/:user_id/cashflow", get(get_cashflow))
+        .route("/api/statistik/:user_id/day-of-month", get(get_day_of_month_spending))
+        .route("/api/statistik/:user_id/velocity", get(get_spending_velocity))
+        .route("/api/statistik/:user_id/compare", get(compare_statistik))
+        .route("/api/statistik/:user_id/report", get(get_laporan_bulanan))
+        .route("/api/statistik/:user_id/kategori/:kategori_id", get(get_kategori_statistik))
+        .route("/api/dashboard/:user_id", get(get_dashboard_data))
+        .route("/api/overview/:user_id", get(get_overview))
+
+        // Aturan kategori otomatis
+        .route("/api/categorization-rules/:user_id", get(get_user_categorization_rules))
+        .route("/api/categorization-rules/:user_id", post(create_categorization_rule))
+        .route("/api/categorization-rules/:user_id/:id", delete(delete_categorization_rule))
+
+        // Webhook
+        .route("/api/webhooks/:user_id", get(get_user_webhooks))
+        .route("/api/webhooks/:user_id", post(create_webhook))
+        .route("/api/webhooks/:user_id/:id", put(update_webhook))
+        .route("/api/webhooks/:user_id/:id", delete(delete_webhook))
+
+        // Test route
+        .route("/hello", get(|| async { "Hello from Axum!" }))
+}
+
+// Router lengkap (routes API + state + middleware + fallback frontend) tanpa bind socket,
+// sehingga bisa dipakai oleh `main` maupun integration test
+pub fn app(pool: PgPool) -> Router {
+    // Mailer: SMTP jika dikonfigurasi, selain itu hanya log
+    let state = AppState {
+        db: pool,
+        mailer: mail::from_env(),
+        spending_ranges_cache: Default::default(),
+        webhook_client: webhook::WebhookClient::new(),
+    };
+
+    // Middleware CORS
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(Any)
+        .allow_headers(Any)
+        .expose_headers([request_id::REQUEST_ID_HEADER]);
+
+    api_router()
+        .fallback(handle_fallback) // Path yang benar-benar tidak dikenal
+        .layer(middleware::from_fn(metrics::track_metrics))
+        .with_state(state)
+        .layer(middleware::map_response(method_not_allowed_body)) // 405 (dengan header Allow) dari method router
+        .layer(cors)
+        .layer(middleware::from_fn(request_id::propagate_request_id)) // X-Request-Id di setiap response
+}
+
+// Path /api yang tidak dikenal dijawab 404 JSON; selain itu diteruskan ke static file frontend
+async fn handle_fallback(request: Request) -> Response {
+    if request.uri().path() == "/api" || request.uri().path().starts_with("/api/") {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Endpoint tidak ditemukan."
+            }))
+        ).into_response();
+    }
+
+    let serve_dir = ServeDir::new("../fe/dist")
+        .not_found_service(ServeFile::new("../fe/dist/index.html"));
+    match serve_dir.oneshot(request).await {
+        Ok(response) => response.into_response(),
+        Err(err) => match err {},
+    }
+}
+
+// Beri body JSON pada 405 bawaan axum; header Allow (daftar method yang didukung) ditambahkan axum setelahnya
+async fn method_not_allowed_body(response: Response) -> Response {
+    if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+        return response;
+    }
+
+    let (mut parts, _) = response.into_parts();
+    parts.headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    parts.headers.remove(header::CONTENT_LENGTH);
+
+    let body = json!({
+        "status": "error",
+        "message": "Method tidak diizinkan untuk endpoint ini. Lihat header Allow."
+    });
+    Response::from_parts(parts, body.to_string().into())
+}
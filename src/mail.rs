@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use std::{env, sync::Arc};
+
+// Antarmuka pengirim email; implementasi dipilih saat startup lewat `from_env`
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> Result<(), String>;
+}
+
+// Default tanpa SMTP: email hanya dicatat ke log server
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> Result<(), String> {
+        println!("📧 Email ke {}: {}\n{}", to, subject, body);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "smtp")]
+pub struct SmtpMailer {
+    transport: lettre::AsyncSmtpTransport<lettre::Tokio1Executor>,
+    from: lettre::message::Mailbox,
+}
+
+#[cfg(feature = "smtp")]
+impl SmtpMailer {
+    // Konfigurasi dari SMTP_HOST, SMTP_PORT, SMTP_USERNAME, SMTP_PASSWORD, dan MAIL_FROM
+    pub fn from_env(host: &str) -> Result<Self, String> {
+        use lettre::transport::smtp::authentication::Credentials;
+
+        let from = env::var("MAIL_FROM")
+            .map_err(|_| "MAIL_FROM wajib diisi jika SMTP_HOST diset".to_string())?
+            .parse()
+            .map_err(|err| format!("MAIL_FROM tidak valid: {}", err))?;
+
+        let mut builder = lettre::AsyncSmtpTransport::<lettre::Tokio1Executor>::relay(host)
+            .map_err(|err| format!("SMTP_HOST tidak valid: {}", err))?;
+
+        if let Some(port) = env::var("SMTP_PORT").ok().and_then(|value| value.parse().ok()) {
+            builder = builder.port(port);
+        }
+
+        if let (Ok(username), Ok(password)) = (env::var("SMTP_USERNAME"), env::var("SMTP_PASSWORD")) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(SmtpMailer { transport: builder.build(), from })
+    }
+}
+
+#[cfg(feature = "smtp")]
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> Result<(), String> {
+        use lettre::AsyncTransport;
+
+        let message = lettre::Message::builder()
+            .from(self.from.clone())
+            .to(to.parse().map_err(|err| format!("Alamat email tidak valid: {}", err))?)
+            .subject(subject)
+            .body(body.to_string())
+            .map_err(|err| err.to_string())?;
+
+        self.transport
+            .send(message)
+            .await
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+}
+
+// Pilih mailer: SMTP jika fitur `smtp` aktif dan SMTP_HOST diset, selain itu LogMailer
+pub fn from_env() -> Arc<dyn Mailer> {
+    #[cfg(feature = "smtp")]
+    if let Ok(host) = env::var("SMTP_HOST") {
+        match SmtpMailer::from_env(&host) {
+            Ok(mailer) => return Arc::new(mailer),
+            Err(err) => eprintln!("Konfigurasi SMTP gagal, email hanya dicatat ke log: {}", err),
+        }
+    }
+
+    #[cfg(not(feature = "smtp"))]
+    if env::var("SMTP_HOST").is_ok() {
+        eprintln!("SMTP_HOST diset tetapi fitur `smtp` tidak aktif, email hanya dicatat ke log");
+    }
+
+    Arc::new(LogMailer)
+}
+
+// Kirim email di background agar request tidak menunggu (dan tidak gagal karena) SMTP
+pub fn send_in_background(mailer: Arc<dyn Mailer>, to: String, subject: String, body: String) {
+    tokio::spawn(async move {
+        if let Err(err) = mailer.send(&to, &subject, &body).await {
+            eprintln!("Mail error: {}", err);
+        }
+    });
+}
@@ -1,27 +1,7 @@
-use axum::{
-    routing::{get, post, put, delete},
-    Router,
-    http::StatusCode,
-};
 use dotenvy::dotenv;
 use std::env;
-use tower_http::{
-    services::{ServeDir, ServeFile},
-    cors::{Any, CorsLayer},
-};
-use sqlx::postgres::PgPoolOptions;
 
-mod database;
-mod models;
-mod routes;
-
-use routes::auth::{signup, signin, forgot_password};
-use routes::user::get_user_by_id;
-use routes::profile::{get_profile, update_profile, update_email, update_password};
-use routes::kategori::{get_all_kategori, create_kategori, update_kategori, delete_kategori, get_kategori_by_id};
-use routes::budget::{get_user_budgets, create_budget, update_budget, delete_budget, get_budget_by_id};
-use routes::transaksi::{get_user_transaksi, create_transaksi, update_transaksi, delete_transaksi, get_transaksi_by_id};
-use routes::statistik::{get_user_statistik, get_spending_ranges, get_user_monthly_spending, get_dashboard_data};
+use savior_be::{app, budget_period, database, jwt, metrics, retention};
 
 #[tokio::main]
 async fn main() {
@@ -32,93 +12,55 @@ async fn main() {
     let database_url = env::var("DATABASE_URL")
         .expect("DATABASE_URL tidak ditemukan di .env");
 
-    // 3️⃣ Inisialisasi koneksi pool PostgreSQL
-    let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&database_url)
-        .await
-        .expect("Gagal menghubungkan ke database PostgreSQL");
+    // Pastikan JWT_SECRET tersedia (dan bukan secret contoh) sebelum server menerima request
+    if let Err(message) = jwt::check_secret_config() {
+        eprintln!("❌ {}", message);
+        std::process::exit(1);
+    }
 
-    // 4️⃣ Jalankan migrations
-    sqlx::migrate!("./migrations")
-        .run(&pool)
+    // 3️⃣ Inisialisasi koneksi pool PostgreSQL (dengan retry jika database belum siap)
+    let pool = database::connect_with_retry(&database_url, 5)
         .await
-        .expect("Gagal menjalankan migrations");
-
-    // 5️⃣ Static file frontend
-    let serve_dir = ServeDir::new("../fe/dist")
-        .not_found_service(ServeFile::new("../fe/dist/index.html"));
-
-    // 6️⃣ Middleware CORS
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+        .expect("Gagal menghubungkan ke database PostgreSQL");
 
-    // 7️⃣ Handler 404
-    async fn handle_404() -> StatusCode {
-        StatusCode::NOT_FOUND
+    // 4️⃣ Jalankan migrations, kecuali dengan --skip-migrations (migrations dijalankan terpisah, mis. di pipeline deploy)
+    if env::args().any(|arg| arg == "--skip-migrations") {
+        println!("⏭️ Migrations dilewati (--skip-migrations)");
+    } else if let Err(err) = database::run_migrations(&pool).await {
+        eprintln!("❌ {}", database::migration_error_message(&pool, &err).await);
+        std::process::exit(1);
     }
 
-    // 8️⃣ Definisi routes API
-    let api_routes = Router::new()
-        // Auth
-        .route("/signup", post(signup))
-        .route("/signin", post(signin))
-        .route("/forgot-password", post(forgot_password))
-
-        // User
-        .route("/api/user/:user_id", get(get_user_by_id))
-
-        // Profile
-        .route("/api/profile/:user_id", get(get_profile))
-        .route("/api/profile/:user_id", put(update_profile))
-        .route("/api/profile/:user_id/email", put(update_email))
-        .route("/api/profile/:user_id/password", put(update_password))
+    // Reset periode budget bulanan (beserta rollover) di background
+    budget_period::spawn_reset_task(pool.clone());
 
-        // Kategori
-        .route("/api/kategori", get(get_all_kategori))
-        .route("/api/kategori", post(create_kategori))
-        .route("/api/kategori/:id", get(get_kategori_by_id))
-        .route("/api/kategori/:id", put(update_kategori))
-        .route("/api/kategori/:id", delete(delete_kategori))
+    // Purge data kedaluwarsa harian (aktif jika TRASH_RETENTION_DAYS > 0)
+    retention::spawn_purge_task(pool.clone());
 
-        // Budget
-        .route("/api/budget/:user_id", get(get_user_budgets))
-        .route("/api/budget/:user_id", post(create_budget))
-        .route("/api/budget/:user_id/:budget_id", get(get_budget_by_id))
-        .route("/api/budget/:user_id/:budget_id", put(update_budget))
-        .route("/api/budget/:user_id/:budget_id", delete(delete_budget))
+    // Metrics Prometheus; METRICS_ADDR (mis. "0.0.0.0:9100") memisahkan /metrics ke port sendiri
+    let metrics_handle = metrics::install_recorder();
+    let metrics_routes = metrics::router(metrics_handle, pool.clone());
+    let metrics_addr = env::var("METRICS_ADDR").ok();
 
-        // Transaksi
-        .route("/api/transaksi/:user_id", get(get_user_transaksi))
-        .route("/api/transaksi/:user_id", post(create_transaksi))
-        .route("/api/transaksi/:user_id/:transaksi_id", get(get_transaksi_by_id))
-        .route("/api/transaksi/:user_id/:transaksi_id", put(update_transaksi))
-        .route("/api/transaksi/:user_id/:transaksi_id", delete(delete_transaksi))
+    // 5️⃣ Routes API + middleware (lihat `app` di lib.rs)
+    let mut app = app(pool);
 
-        // Statistik
-        .route("/api/statistik/ranges", get(get_spending_ranges))
-        .route("/api/statistik/:user_id", get(get_user_statistik))
-        .route("/api/statistik/:user_id/monthly", get(get_user_monthly_spending))
-        .route("/api/dashboard/:user_id", get(get_dashboard_data))
-
-        // Test route
-        .route("/hello", get(|| async { "Hello from Axum!" }));
-
-    // 9️⃣ Gabungkan API + middleware
-    let app = Router::new()
-        .merge(api_routes)
-        .with_state(pool)
-        .layer(cors)
-        .fallback(handle_404) // Handler 404 API
-        .fallback_service(serve_dir); // Fallback ke frontend
+    match &metrics_addr {
+        Some(addr) => {
+            let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+            tokio::spawn(async move {
+                axum::serve(listener, metrics_routes).await.unwrap();
+            });
+        }
+        None => app = app.merge(metrics_routes),
+    }
 
-    // 🔟 Jalankan server
+    // 6️⃣ Jalankan server
     let addr = "0.0.0.0:3000";
     println!("🚀 Server running at http://{}", addr);
-    println!("✅ Database connected and migrations completed");
+    println!("✅ Database connected");
     println!("🔗 Endpoints available at http://{}", addr);
+    println!("📈 Metrics available at http://{}/metrics", metrics_addr.as_deref().unwrap_or(addr));
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
     axum::serve(listener, app).await.unwrap();
@@ -0,0 +1,62 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::time::Instant;
+
+use crate::database::Database;
+
+const REQUEST_DURATION: &str = "http_request_duration_seconds";
+
+// Bucket latency (detik) untuk histogram request
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+// Pasang recorder Prometheus global; dipanggil sekali saat startup
+pub fn install_recorder() -> PrometheusHandle {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Full(REQUEST_DURATION.to_string()), LATENCY_BUCKETS)
+        .expect("Bucket histogram tidak valid")
+        .install_recorder()
+        .expect("Gagal memasang recorder metrics")
+}
+
+// Middleware: hitung request per route + status dan catat latency-nya.
+// Label `path` memakai pola route (mis. "/api/transaksi/:user_id") agar kardinalitas tetap kecil
+pub async fn track_metrics(request: Request, next: Next) -> impl IntoResponse {
+    let start = Instant::now();
+    let method = request.method().to_string();
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16().to_string();
+    let labels = [("method", method), ("path", path), ("status", status)];
+    metrics::counter!("http_requests_total", &labels).increment(1);
+    metrics::histogram!(REQUEST_DURATION, &labels).record(start.elapsed().as_secs_f64());
+
+    response
+}
+
+// Router berisi GET /metrics; gauge pool diperbarui setiap kali di-scrape
+pub fn router(handle: PrometheusHandle, db: Database) -> Router {
+    Router::new().route(
+        "/metrics",
+        get(move || async move {
+            let size = db.size();
+            let idle = db.num_idle() as u32;
+            metrics::gauge!("db_pool_connections").set(size as f64);
+            metrics::gauge!("db_pool_idle_connections").set(idle as f64);
+            metrics::gauge!("db_pool_active_connections").set(size.saturating_sub(idle) as f64);
+
+            handle.render()
+        }),
+    )
+}
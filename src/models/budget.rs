@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::models::kategori::Kategori;
+use crate::models::statistik::RingkasanBudget;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Budget {
@@ -10,6 +13,10 @@ pub struct Budget {
     pub kategori_id: i32,
     pub amount: i32,
     pub spent: Option<i32>,
+    pub minor_unit: i16, // Eksponen mata uang saat ditulis (0 untuk IDR)
+    pub period_start: NaiveDate, // Awal periode bulanan yang sedang berjalan
+    pub rollover: bool,
+    pub carry_over: i32, // Sisa periode lalu yang dibawa (hanya jika rollover aktif)
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -21,18 +28,157 @@ pub struct BudgetWithCategory {
     pub kategori_id: i32,
     pub kategori_nama: String,
     pub amount: i32,
+    pub carry_over: i32,
+    pub effective_amount: i32, // amount + carry_over; dasar perhitungan percentage
     pub spent: i32,
-    pub percentage: f64,
+    pub percentage: f64, // Dibulatkan 2 desimal di query, sama seperti persentase statistik
+    pub transaction_count: i64, // Jumlah pengeluaran di periode berjalan yang dihitung ke spent
+    pub rollover: bool,
+    pub period_start: NaiveDate,
+    pub minor_unit: i16,
+    #[sqlx(skip)]
+    pub amount_formatted: String, // Diisi handler sesuai preferensi mata uang user
+    #[sqlx(skip)]
+    pub spent_formatted: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateBudgetRequest {
     pub kategori_id: i32,
     pub amount: i32,
+    pub rollover: Option<bool>, // Default false
+}
+
+// Body PUT /api/budget/:user_id/kategori/:kategori_id (create atau update)
+#[derive(Debug, Deserialize)]
+pub struct UpsertBudgetRequest {
+    pub amount: i32,
+    pub rollover: Option<bool>, // Default false saat create; saat update tidak diubah jika kosong
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkCreateBudgetRequest {
+    pub budgets: Vec<CreateBudgetRequest>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkippedBudget {
+    pub kategori_id: i32,
+    pub reason: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateBudgetRequest {
     pub amount: Option<i32>,
     pub spent: Option<i32>,
+    pub rollover: Option<bool>,
+    pub expected_updated_at: Option<DateTime<Utc>>, // Optimistic concurrency check
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BudgetListQuery {
+    pub status: Option<String>, // "over", "near", atau "under"; kosong = semua budget
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BudgetReportQuery {
+    pub month: Option<String>, // Format: "YYYY-MM"
+}
+
+#[derive(Debug, FromRow)]
+pub struct BudgetReportRow {
+    pub kategori_id: i32,
+    pub kategori_nama: String,
+    pub amount: Option<i64>,
+    pub actual: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BudgetReportItem {
+    pub kategori_id: i32,
+    pub kategori_nama: String,
+    pub amount: Option<i64>,
+    pub actual: i64,
+    pub variance: Option<i64>,
+    pub status: String, // "over", "under", atau "unbudgeted"
+}
+
+#[derive(Debug, Serialize)]
+pub struct BurndownPoint {
+    pub tanggal: NaiveDate,
+    pub aktual: Option<i64>, // Spent kumulatif; null untuk hari yang belum terjadi
+    pub ideal: f64, // Garis ideal: budget efektif dibagi rata sejak budget berlaku
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct BudgetHistory {
+    pub id: i32,
+    pub budget_id: i32,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub amount: i32,
+    pub carry_over: i32,
+    pub spent: i32,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BudgetHistoryQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// Budget yang pemakaiannya sudah mencapai salah satu threshold alert.
+// Alert dihitung saat request, jadi tidak punya created_at/updated_at; waktu yang relevan adalah acknowledged_at.
+#[derive(Debug, Serialize, FromRow)]
+pub struct BudgetAlert {
+    pub budget_id: i32,
+    pub kategori_id: i32,
+    pub kategori_nama: String,
+    pub effective_amount: i32,
+    pub spent: i32,
+    pub percentage: f64,
+    pub threshold: i32, // Threshold tertinggi yang sudah tercapai (persen)
+    pub period_start: NaiveDate,
+    pub acknowledged_at: Option<DateTime<Utc>>, // null jika belum di-acknowledge pada threshold ini
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BudgetAlertQuery {
+    pub include_acknowledged: Option<bool>, // Default false
+}
+
+// Data halaman budget dalam satu response (GET /api/budget/:user_id/page)
+#[derive(Debug, Serialize)]
+pub struct BudgetPageResponse {
+    pub budgets: Vec<BudgetWithCategory>,
+    pub summary: RingkasanBudget,
+    pub alerts: Vec<BudgetAlert>, // Hanya alert yang belum di-acknowledge
+    pub kategori_tanpa_budget: Vec<Kategori>, // Kandidat untuk budget baru
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AcknowledgeAlertsRequest {
+    pub budget_ids: Vec<i32>,
+}
+
+// Saran budget untuk kategori yang rutin dipakai tapi belum punya budget
+#[derive(Debug, Serialize, FromRow)]
+pub struct BudgetSuggestion {
+    pub kategori_id: i32,
+    pub kategori_nama: String,
+    pub rata_rata_bulanan: i64, // Rata-rata pengeluaran per bulan selama jendela pengamatan
+    pub bulan_aktif: i64, // Jumlah bulan dalam jendela yang memiliki pengeluaran
+    #[sqlx(skip)]
+    pub suggested_amount: i64, // rata_rata_bulanan dibulatkan ke atas menjadi angka "bulat"
+}
+
+// Hasil recalculate spent seluruh budget (admin)
+#[derive(Debug, Default, Serialize)]
+pub struct RecalculateSummary {
+    pub checked: i64, // Jumlah budget yang diperiksa
+    pub corrected: i64, // Jumlah budget yang spent-nya berbeda dan sudah diperbaiki
+    pub total_drift: i64, // Total selisih absolut spent lama vs hasil hitung ulang
 }
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CategorizationRule {
+    pub id: i32,
+    pub user_id: Uuid,
+    pub pattern: String, // Dicocokkan sebagai substring deskripsi, tanpa membedakan huruf besar/kecil
+    pub kategori_id: i32,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCategorizationRuleRequest {
+    pub pattern: String,
+    pub kategori_id: i32,
+}
@@ -1,11 +1,14 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use chrono::{DateTime, Utc};
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Kategori {
     pub id: i32,
     pub nama: String,
+    pub user_id: Option<Uuid>, // None = kategori sistem
+    pub sort_order: i32, // Urutan tampilan; diatur lewat reorder, default 0
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -13,9 +16,27 @@ pub struct Kategori {
 #[derive(Debug, Deserialize)]
 pub struct CreateKategoriRequest {
     pub nama: String,
+    pub user_id: Option<Uuid>, // Kosong = kategori sistem
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncKategoriRequest {
+    pub user_id: Uuid,
+    pub categories: Vec<String>, // Nama kategori; yang belum ada dibuat sebagai kategori milik user
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReorderKategoriRequest {
+    pub user_id: Uuid,
+    pub kategori_ids: Vec<i32>, // Seluruh kategori milik user sesuai urutan yang diinginkan
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateKategoriRequest {
     pub nama: String,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct KategoriQuery {
+    pub user_id: Option<Uuid>,
+}
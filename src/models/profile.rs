@@ -19,6 +19,11 @@ pub struct UpdateProfileRequest {
     pub last_name: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UpdateDisplayNameRequest {
+    pub display_name: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UpdateEmailRequest {
     pub new_email: String,
@@ -30,3 +35,63 @@ pub struct UpdatePasswordRequest {
     pub current_password: String,
     pub new_password: String,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateDailyLimitRequest {
+    pub daily_limit: Option<i32>, // null = hapus batas harian
+}
+
+// Default preferensi jika user belum pernah menyimpan preferensi
+pub const DEFAULT_CURRENCY: &str = "IDR";
+pub const DEFAULT_LOCALE: &str = "id-ID";
+pub const DEFAULT_DATE_FORMAT: &str = "DD/MM/YYYY";
+pub const DEFAULT_WEEK_START: &str = WEEK_START_MONDAY;
+
+// Nilai kolom `week_start` pada user_preferences
+pub const WEEK_START_MONDAY: &str = "monday";
+pub const WEEK_START_SUNDAY: &str = "sunday";
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct UserPreferences {
+    pub currency: String,
+    pub locale: String,
+    pub date_format: String,
+    pub week_start: String, // Hari pertama minggu kalender: "monday" (default) atau "sunday"
+    pub created_at: Option<DateTime<Utc>>, // null jika preferensi belum pernah disimpan (masih default)
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Default for UserPreferences {
+    fn default() -> Self {
+        UserPreferences {
+            currency: DEFAULT_CURRENCY.to_string(),
+            locale: DEFAULT_LOCALE.to_string(),
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            week_start: DEFAULT_WEEK_START.to_string(),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePreferencesRequest {
+    pub currency: Option<String>,    // Kode ISO-4217, mis. "IDR"
+    pub locale: Option<String>,      // Tag BCP-47, mis. "id-ID"
+    pub date_format: Option<String>, // Salah satu dari DATE_FORMATS
+    pub week_start: Option<String>,  // "monday" atau "sunday"
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct AuditEvent {
+    pub id: i64,
+    pub event_type: String, // Lihat konstanta EVENT_* di modul audit
+    pub ip_address: Option<String>,
+    pub detail: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ActivityQuery {
+    pub limit: Option<i64>, // Default 20, maks 100
+}
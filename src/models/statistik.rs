@@ -1,16 +1,28 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::models::budget::BudgetReportItem;
+
+// Sebagian besar struct di sini adalah hasil agregasi (SUM/COUNT per kategori, hari, atau bulan),
+// sehingga tidak punya created_at/updated_at. Baris transaksi (TransaksiTerakhir) tetap menyertakannya.
+
+// kategori_id untuk slice gabungan "Lainnya" (bukan kategori sungguhan; id SERIAL dimulai dari 1)
+pub const KATEGORI_LAINNYA_ID: i32 = 0;
 
 #[derive(Debug, Serialize, FromRow)]
 pub struct PengeluaranKategori {
+    pub kategori_id: i32,
     pub kategori_nama: String,
     pub total_pengeluaran: i64,
     pub persentase: f64,
+    pub percentage_of_income: Option<f64>, // Terhadap total pemasukan periode; null jika tidak ada pemasukan
 }
 
 #[derive(Debug, Serialize, FromRow)]
 pub struct RingkasanPengeluaran {
     pub total_pengeluaran: i64,
+    pub total_pemasukan: i64,
     pub rata_rata_harian: f64,
     pub total_transaksi: i64,
     pub tertinggi_hari_ini: Option<i64>,
@@ -22,6 +34,9 @@ pub struct RingkasanPengeluaran {
 #[derive(Debug, Serialize)]
 pub struct DashboardResponse {
     pub total_bulan_ini: i64,
+    pub total_bulan_lalu: i64, // Periode yang sama di bulan lalu (tanggal 1 s/d tanggal hari ini)
+    pub perubahan_persen: Option<f64>, // null jika bulan lalu tidak ada pengeluaran
+    pub total_minggu_ini: i64, // Minggu kalender berjalan, awal minggu sesuai preferensi week_start
     pub total_hari_ini: i64,
     pub tertinggi_bulan_ini: i64,
     pub tertinggi_hari_ini: i64,
@@ -44,6 +59,8 @@ pub struct TransaksiTerakhir {
     pub jumlah: i32,  // ✅ FIXED: Use i32 to match database INT4
     pub tanggal: String,
     pub kategori_nama: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -59,11 +76,224 @@ pub struct PengeluaranRange {
     pub persentase: f64,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SpendingRangesQuery {
+    pub refresh: Option<bool>, // true = hitung ulang dan abaikan cache (khusus admin)
+}
+
 #[derive(Debug, Deserialize)]
 pub struct StatistikQuery {
-    pub filter: Option<String>, // "daily", "weekly", "monthly"
+    pub filter: Option<String>, // "daily", "weekly" (7 hari terakhir), "calendar_week" (sesuai week_start), "monthly"
     pub start_date: Option<String>,
     pub end_date: Option<String>,
     pub year: Option<i32>,
     pub month: Option<u32>,
+    pub format: Option<String>, // "json" (default) atau "csv"
+    pub top: Option<usize>, // Hanya N kategori teratas, sisanya digabung jadi "Lainnya"; default semua
+}
+
+// Statistik satu kategori untuk layar detail kategori
+#[derive(Debug, Serialize)]
+pub struct KategoriStatistikResponse {
+    pub kategori_id: i32,
+    pub kategori_nama: String,
+    pub total_pengeluaran: i64,
+    pub total_transaksi: i64,
+    pub rata_rata_transaksi: f64, // 0 jika tidak ada transaksi
+    pub rata_rata_harian: f64,
+    pub harian: Vec<KategoriHarianPoint>, // Hanya hari yang memiliki pengeluaran
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct KategoriHarianPoint {
+    pub tanggal: NaiveDate,
+    pub total: i64,
+    pub jumlah_transaksi: i64,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct HeatmapPoint {
+    pub tanggal: NaiveDate,
+    pub total: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DashboardQuery {
+    pub recent_limit: Option<i64>, // Jumlah transaksi_terakhir, default 10, maks 50
+    pub end_date: Option<String>, // Hari terakhir grafik mingguan (YYYY-MM-DD), default hari ini
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HeatmapQuery {
+    pub year: Option<i32>,
+    pub month: Option<u32>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct TopDeskripsi {
+    pub deskripsi: String,
+    pub jumlah_transaksi: i64,
+    pub total: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TopDeskripsiQuery {
+    pub limit: Option<i64>,
+    pub start_date: Option<String>, // Format: "YYYY-MM-DD"
+    pub end_date: Option<String>,   // Format: "YYYY-MM-DD"
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct TransaksiTerbesar {
+    pub id: i32,
+    pub tanggal: NaiveDate,
+    pub kategori_id: i32,
+    pub kategori_nama: String,
+    pub deskripsi: String,
+    pub jumlah: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransaksiTerbesarQuery {
+    pub limit: Option<i64>,
+    pub start_date: Option<String>, // Format: "YYYY-MM-DD"
+    pub end_date: Option<String>,   // Format: "YYYY-MM-DD"
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct KategoriTidakTerpakai {
+    pub kategori_id: i32,
+    pub kategori_nama: String,
+    pub is_sistem: bool, // true untuk kategori sistem (tidak bisa dihapus user)
+    pub terakhir_dipakai: Option<NaiveDate>, // None = belum pernah dipakai sama sekali
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KategoriTidakTerpakaiQuery {
+    pub month: Option<String>, // Format: "YYYY-MM", default bulan berjalan
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct CashflowPoint {
+    pub bulan: String, // Format: "YYYY-MM"
+    #[sqlx(skip)]
+    pub bulan_label: String, // Mis. "Okt 2026"; diisi handler sesuai locale user
+    pub pemasukan: i64,
+    pub pengeluaran: i64,
+    pub net: i64, // pemasukan - pengeluaran
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CashflowQuery {
+    pub months: Option<u32>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct DayOfMonthPoint {
+    pub tanggal: i32, // Tanggal dalam bulan (1-31)
+    pub rata_rata: f64, // Rata-rata pengeluaran per hari untuk tanggal ini
+    pub total: i64,
+    pub jumlah_sampel: i64, // Jumlah hari yang dihitung (bulan tanpa tanggal ini tidak ikut)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DayOfMonthQuery {
+    pub months: Option<u32>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct RingkasanBudget {
+    pub jumlah_budget: i64,
+    pub total_budget: i64, // Jumlah budget efektif (amount + carry_over)
+    pub total_terpakai: i64,
+    pub jumlah_over: i64, // Budget yang spent-nya melebihi budget efektif
+}
+
+#[derive(Debug, Serialize)]
+pub struct OverviewResponse {
+    pub total_hari_ini: i64,
+    pub total_bulan_ini: i64,
+    pub top_kategori: Vec<PengeluaranKategori>,
+    pub budget: RingkasanBudget,
+    pub transaksi_terakhir: Vec<TransaksiTerakhir>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VelocityResponse {
+    pub rata_rata_bulan_ini: f64, // Rata-rata pengeluaran harian bulan berjalan (s/d hari ini)
+    pub rata_rata_sebelumnya: Option<f64>, // Rata-rata harian bulan-bulan pembanding; null jika belum ada riwayat
+    pub rasio: Option<f64>,
+    pub status: String, // "accelerating", "normal", atau "slowing"
+    pub bulan_pembanding: u32, // Jumlah bulan riwayat yang dipakai (maksimal 3)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareQuery {
+    pub a_start: Option<String>, // Format: "YYYY-MM-DD"
+    pub a_end: Option<String>,
+    pub b_start: Option<String>,
+    pub b_end: Option<String>,
+}
+
+// Breakdown per kategori untuk satu rentang tanggal
+#[derive(Debug, Serialize)]
+pub struct RangeStatistik {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub total_pengeluaran: i64,
+    pub pengeluaran_per_kategori: Vec<PengeluaranKategori>,
+}
+
+// Perubahan satu kategori dari rentang B (pembanding) ke rentang A
+#[derive(Debug, Serialize)]
+pub struct KategoriDelta {
+    pub kategori_id: i32,
+    pub kategori_nama: String,
+    pub total_a: i64,
+    pub total_b: i64,
+    pub selisih: i64, // total_a - total_b
+    pub persentase_perubahan: Option<f64>, // null jika total_b = 0
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompareResponse {
+    pub a: RangeStatistik,
+    pub b: RangeStatistik,
+    pub total_selisih: i64,
+    pub total_persentase_perubahan: Option<f64>,
+    pub perubahan_per_kategori: Vec<KategoriDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreakQuery {
+    pub threshold: Option<i64>, // Batas pengeluaran harian; default daily_limit user
+}
+
+// Streak dihitung dari transaksi pertama user sampai hari ini
+#[derive(Debug, Serialize)]
+pub struct StreakResponse {
+    pub threshold: Option<i64>, // null jika tidak ada parameter threshold maupun daily_limit
+    pub current_streak: Option<i64>, // Hari berturut-turut (sampai hari ini) dengan pengeluaran <= threshold
+    pub longest_streak: Option<i64>,
+    pub current_logging_streak: i64, // Hari berturut-turut dengan minimal satu transaksi
+    pub start_date: Option<NaiveDate>, // Tanggal transaksi pertama; null jika belum ada transaksi
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LaporanBulananQuery {
+    pub month: Option<String>, // Format: "YYYY-MM", default bulan berjalan
+    pub format: Option<String>, // "json" (default) atau "csv"
+}
+
+// Laporan "month in review" satu bulan penuh untuk ditampilkan atau dikirim lewat email
+#[derive(Debug, Serialize)]
+pub struct LaporanBulananResponse {
+    pub bulan: String, // Format: "YYYY-MM"
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub ringkasan: RingkasanPengeluaran,
+    pub pengeluaran_per_kategori: Vec<PengeluaranKategori>,
+    pub transaksi_terbesar: Vec<TransaksiTerbesar>,
+    pub harian: Vec<HeatmapPoint>, // Setiap tanggal dalam bulan, termasuk hari tanpa pengeluaran (total 0)
+    pub budget_vs_aktual: Vec<BudgetReportItem>,
 }
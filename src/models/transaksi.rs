@@ -1,8 +1,12 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc, NaiveDate};
 
+// Nilai kolom `tipe` pada tabel transaksi
+pub const TIPE_PENGELUARAN: &str = "pengeluaran";
+pub const TIPE_PEMASUKAN: &str = "pemasukan";
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Transaksi {
     pub id: i32,
@@ -11,6 +15,11 @@ pub struct Transaksi {
     pub jumlah: i32,
     pub deskripsi: String,
     pub tanggal: NaiveDate,
+    pub note: Option<String>,
+    pub receipt_url: Option<String>,
+    pub tipe: String,
+    pub minor_unit: i16, // Eksponen mata uang saat ditulis (0 untuk IDR)
+    pub budget_period_start: Option<NaiveDate>, // Override periode budget; null = mengikuti tanggal
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -22,18 +31,38 @@ pub struct TransaksiWithCategory {
     pub kategori_id: i32,
     pub kategori_nama: String,
     pub jumlah: i32,
+    pub minor_unit: i16,
+    #[sqlx(skip)]
+    pub jumlah_formatted: String, // Diisi handler sesuai preferensi mata uang user
     pub deskripsi: String,
     pub tanggal: NaiveDate,
+    pub note: Option<String>,
+    pub receipt_url: Option<String>,
+    pub tipe: String,
+    pub tags: Vec<String>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateTransaksiRequest {
-    pub kategori_id: i32,
+    pub kategori_id: Option<i32>, // Jika kosong, diambil dari aturan kategori yang cocok dengan deskripsi
     pub jumlah: i32,
     pub deskripsi: String,
     pub tanggal: String, // Format: "YYYY-MM-DD"
+    pub note: Option<String>,
+    pub receipt_url: Option<String>, // URL file struk yang sudah diupload client
+    pub tipe: Option<String>, // "pengeluaran" (default) atau "pemasukan"
+    pub tags: Option<Vec<String>>, // Tag bebas, mis. ["liburan", "kerja"]
+    pub auto_budget_amount: Option<i32>, // Buat budget otomatis jika belum ada
+    // Awal periode budget (YYYY-MM-DD) tempat pengeluaran ini dihitung, mis. tagihan yang terlambat tercatat.
+    // Harus periode berjalan atau periode lama budget kategori ini; jika kosong, periode mengikuti `tanggal`
+    pub budget_period_start: Option<String>,
+}
+
+// Field update yang bisa dihapus: tidak dikirim = None, `null` = Some(None), nilai = Some(Some(_))
+fn clearable<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Option<String>>, D::Error> {
+    Option::<String>::deserialize(deserializer).map(Some)
 }
 
 #[derive(Debug, Deserialize)]
@@ -42,4 +71,90 @@ pub struct UpdateTransaksiRequest {
     pub jumlah: Option<i32>,
     pub deskripsi: Option<String>,
     pub tanggal: Option<String>, // Format: "YYYY-MM-DD"
+    #[serde(default, deserialize_with = "clearable")]
+    pub note: Option<Option<String>>, // null atau string kosong menghapus note
+    #[serde(default, deserialize_with = "clearable")]
+    pub receipt_url: Option<Option<String>>, // null atau string kosong menghapus receipt_url
+    pub tags: Option<Vec<String>>, // Jika dikirim, menggantikan seluruh tag transaksi
+    #[serde(default, deserialize_with = "clearable")]
+    pub budget_period_start: Option<Option<String>>, // Sama dengan create; null atau string kosong menghapus override
+    pub expected_updated_at: Option<DateTime<Utc>>, // Optimistic concurrency check
+}
+
+// Input cepat berbasis teks, mis. { "text": "kopi 25000 makanan" }
+#[derive(Debug, Deserialize)]
+pub struct QuickTransaksiRequest {
+    pub text: String,
+    pub create_kategori: Option<bool>, // Buat kategori pribadi jika belum ada (default false)
+    pub auto_budget_amount: Option<i32>, // Diteruskan ke create_transaksi
+}
+
+// Proyeksi budget jika transaksi jadi dibuat (POST /api/transaksi/:user_id/preview)
+#[derive(Debug, Serialize)]
+pub struct BudgetPreview {
+    pub kategori_id: i32,
+    pub budget_baru: bool, // true jika budget akan dibuat otomatis dari auto_budget_amount
+    pub amount: i32,
+    pub effective_amount: i32, // amount + carry_over; dasar perhitungan percentage
+    pub spent_sebelum: i32,
+    pub percentage_sebelum: f64,
+    pub spent: i32,
+    pub percentage: f64,
+    pub sisa: i32, // Sisa budget setelah transaksi (negatif jika terlampaui)
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransaksiPreview {
+    pub tipe: String,
+    pub jumlah: i32,
+    pub budget: Option<BudgetPreview>, // None untuk pemasukan
+    pub would_exceed: bool, // true jika create_transaksi akan menolak karena melebihi sisa budget
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SplitItem {
+    pub kategori_id: i32,
+    pub jumlah: i32,
+    pub deskripsi: Option<String>, // Default: deskripsi transaksi asal
+}
+
+// Pecah satu transaksi (mis. satu struk belanja) ke beberapa kategori
+#[derive(Debug, Deserialize)]
+pub struct SplitTransaksiRequest {
+    pub splits: Vec<SplitItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClearTransaksiRequest {
+    pub password: String, // Konfirmasi password karena aksi ini tidak bisa dibatalkan
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReassignKategoriRequest {
+    pub from_kategori_id: i32,
+    pub to_kategori_id: i32,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct KategoriUsage {
+    pub kategori_id: i32,
+    pub kategori_nama: String,
+    pub jumlah_transaksi: i64,
+    pub total: i64,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct RecentKategori {
+    pub kategori_id: i32,
+    pub kategori_nama: String,
+    pub terakhir_digunakan: NaiveDate,
+}
+
+// Transaksi satu hari yang dikelompokkan per kategori (detail hari di kalender)
+#[derive(Debug, Serialize)]
+pub struct TransaksiPerKategori {
+    pub kategori_id: i32,
+    pub kategori_nama: String,
+    pub subtotal: i64,
+    pub transaksi: Vec<TransaksiWithCategory>,
 }
@@ -6,9 +6,15 @@ use uuid::Uuid;
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct User {
     pub id: Uuid,
-    pub username: String,
+    pub username: String, // Display name, diubah lewat PUT /api/profile/:user_id/display-name
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
     pub email: String,
     pub password_hash: String,
+    pub role: String,
+    pub daily_limit: Option<i32>,
+    #[serde(skip_serializing)]
+    pub token_version: i32, // Harus sama dengan claim `ver` di access token
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -33,6 +39,11 @@ pub struct SignupRequest {
     pub password: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UserByEmailQuery {
+    pub email: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
     pub status: String,
@@ -40,9 +51,13 @@ pub struct AuthResponse {
     pub user: Option<UserResponse>,
 }
 
-#[derive(Debug, Serialize)]
+// User tanpa password_hash, aman untuk dikirim ke client
+#[derive(Debug, Serialize, FromRow)]
 pub struct UserResponse {
     pub id: Uuid,
+    pub username: String,
     pub email: String,
+    pub role: String,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
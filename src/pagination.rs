@@ -0,0 +1,118 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Query},
+    http::{request::Parts, StatusCode, Uri},
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::env;
+
+// Ukuran halaman default dan maksimum; bisa diubah lewat DEFAULT_PAGE_SIZE / MAX_PAGE_SIZE
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 100;
+
+fn page_size_from_env(name: &str, default: i64) -> i64 {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .filter(|size| *size > 0)
+        .unwrap_or(default)
+}
+
+// Parameter `limit`/`offset` yang sudah dinormalisasi untuk endpoint list:
+// limit di [1, MAX_PAGE_SIZE] (default DEFAULT_PAGE_SIZE) dan offset >= 0
+#[derive(Debug, Clone, Copy)]
+pub struct Pagination {
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl Pagination {
+    pub fn new(limit: Option<i64>, offset: Option<i64>) -> Self {
+        let max_page_size = page_size_from_env("MAX_PAGE_SIZE", MAX_PAGE_SIZE);
+        let default_page_size = page_size_from_env("DEFAULT_PAGE_SIZE", DEFAULT_PAGE_SIZE).min(max_page_size);
+
+        Pagination {
+            limit: limit.unwrap_or(default_page_size).clamp(1, max_page_size),
+            offset: offset.unwrap_or(0).max(0),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PaginationParams {
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Pagination
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, Json<Value>);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(params) = Query::<PaginationParams>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Parameter limit dan offset harus berupa bilangan bulat."
+                    }))
+                )
+            })?;
+
+        Ok(Pagination::new(params.limit, params.offset))
+    }
+}
+
+// URL (path + query) dari request saat ini dengan parameter tertentu diganti atau dihapus.
+// Parameter lain dipertahankan apa adanya (masih ter-encode); nilai baru harus sudah aman untuk URL
+pub fn page_url(uri: &Uri, set: &[(&str, String)], remove: &[&str]) -> String {
+    let mut pairs: Vec<String> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter(|pair| {
+            let key = pair.split_once('=').map_or(*pair, |(key, _)| key);
+            !remove.contains(&key) && !set.iter().any(|(name, _)| *name == key)
+        })
+        .map(str::to_string)
+        .collect();
+    pairs.extend(set.iter().map(|(name, value)| format!("{}={}", name, value)));
+
+    if pairs.is_empty() {
+        uri.path().to_string()
+    } else {
+        format!("{}?{}", uri.path(), pairs.join("&"))
+    }
+}
+
+// Object `links` {self, next?, prev?}; next/prev dihilangkan jika tidak ada halaman tersebut
+pub fn links(self_url: String, next: Option<String>, prev: Option<String>) -> Value {
+    let mut links = Map::new();
+    links.insert("self".to_string(), Value::String(self_url));
+    if let Some(next) = next {
+        links.insert("next".to_string(), Value::String(next));
+    }
+    if let Some(prev) = prev {
+        links.insert("prev".to_string(), Value::String(prev));
+    }
+    Value::Object(links)
+}
+
+// `links` untuk pagination berbasis limit/offset
+pub fn offset_links(uri: &Uri, limit: i64, offset: i64, total: i64) -> Value {
+    let link = |offset: i64| page_url(uri, &[("limit", limit.to_string()), ("offset", offset.to_string())], &[]);
+
+    links(
+        link(offset),
+        (offset + limit < total).then(|| link(offset + limit)),
+        (offset > 0).then(|| link((offset - limit).max(0))),
+    )
+}
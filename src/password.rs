@@ -0,0 +1,133 @@
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use rand_core::OsRng;
+use std::env;
+
+// Default kebijakan password, bisa diubah lewat PASSWORD_MIN_LENGTH,
+// PASSWORD_REQUIRE_DIGIT, dan PASSWORD_REQUIRE_LETTER
+const DEFAULT_MIN_LENGTH: usize = 8;
+
+// Hasil verifikasi password terhadap hash yang tersimpan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordCheck {
+    pub valid: bool,
+    // true jika hash tersimpan masih format lama (plaintext/bcrypt) dan perlu di-hash ulang
+    pub needs_rehash: bool,
+}
+
+// Hash password baru menggunakan Argon2
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+}
+
+// Verifikasi password; format hash dideteksi dari prefix:
+// "$argon2" = Argon2, "$2a$"/"$2b$"/"$2y$" = bcrypt, selain itu plaintext (data lama)
+pub fn verify_password(password: &str, stored_hash: &str) -> PasswordCheck {
+    if stored_hash.starts_with("$argon2") {
+        let valid = PasswordHash::new(stored_hash)
+            .map(|parsed| Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+            .unwrap_or(false);
+        return PasswordCheck { valid, needs_rehash: false };
+    }
+
+    let valid = if ["$2a$", "$2b$", "$2y$"].iter().any(|prefix| stored_hash.starts_with(prefix)) {
+        bcrypt::verify(password, stored_hash).unwrap_or(false)
+    } else {
+        stored_hash == password
+    };
+
+    PasswordCheck { valid, needs_rehash: valid }
+}
+
+// Kebijakan password untuk password baru (signup, reset, dan ganti password)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_digit: bool,
+    pub require_letter: bool,
+}
+
+impl PasswordPolicy {
+    pub fn from_env() -> Self {
+        PasswordPolicy {
+            min_length: env::var("PASSWORD_MIN_LENGTH")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(DEFAULT_MIN_LENGTH),
+            require_digit: env_flag("PASSWORD_REQUIRE_DIGIT", true),
+            require_letter: env_flag("PASSWORD_REQUIRE_LETTER", true),
+        }
+    }
+
+    // Kembalikan pesan error untuk aturan pertama yang tidak terpenuhi
+    pub fn check(&self, password: &str) -> Result<(), String> {
+        if password.chars().count() < self.min_length {
+            return Err(format!("Password minimal {} karakter.", self.min_length));
+        }
+
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            return Err("Password harus mengandung minimal satu angka.".to_string());
+        }
+
+        if self.require_letter && !password.chars().any(char::is_alphabetic) {
+            return Err("Password harus mengandung minimal satu huruf.".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+// Validasi password baru terhadap kebijakan dari env
+pub fn validate_password(password: &str) -> Result<(), String> {
+    PasswordPolicy::from_env().check(password)
+}
+
+fn env_flag(key: &str, default: bool) -> bool {
+    match env::var(key) {
+        Ok(value) => matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes"),
+        Err(_) => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PasswordPolicy;
+
+    const POLICY: PasswordPolicy = PasswordPolicy { min_length: 8, require_digit: true, require_letter: true };
+
+    #[test]
+    fn accepts_password_meeting_every_rule() {
+        assert_eq!(POLICY.check("rahasia123"), Ok(()));
+    }
+
+    #[test]
+    fn rejects_password_shorter_than_min_length() {
+        assert_eq!(POLICY.check("rhs1234"), Err("Password minimal 8 karakter.".to_string()));
+        // Panjang dihitung per karakter, bukan byte
+        assert!(POLICY.check("kuñç1234").is_ok());
+        assert!(POLICY.check("ñç1234").is_err());
+    }
+
+    #[test]
+    fn rejects_password_without_digit() {
+        assert_eq!(POLICY.check("rahasiaku"), Err("Password harus mengandung minimal satu angka.".to_string()));
+    }
+
+    #[test]
+    fn rejects_password_without_letter() {
+        assert_eq!(POLICY.check("12345678"), Err("Password harus mengandung minimal satu huruf.".to_string()));
+    }
+
+    #[test]
+    fn disabled_rules_are_skipped() {
+        let policy = PasswordPolicy { min_length: 4, require_digit: false, require_letter: false };
+        assert_eq!(policy.check("abcd"), Ok(()));
+        assert_eq!(policy.check("1234"), Ok(()));
+        assert!(policy.check("abc").is_err());
+    }
+}
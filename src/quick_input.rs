@@ -0,0 +1,80 @@
+// Hasil parse input cepat: "[deskripsi] <jumlah> <kategori>", mis. "kopi 25000 makanan"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuickInput {
+    pub deskripsi: Option<String>,
+    pub jumlah: i32,
+    pub kategori_nama: String,
+}
+
+// Parse satu token jumlah: "25000", "25.000", "Rp25.000", "25rb", "25k", "1,5jt".
+// None jika token bukan angka (berarti bagian dari deskripsi/kategori).
+fn parse_jumlah(token: &str) -> Option<i64> {
+    let token = token.to_lowercase();
+    let token = token.strip_prefix("rp").unwrap_or(&token);
+
+    let (number, multiplier) = [("ribu", 1_000), ("rb", 1_000), ("k", 1_000), ("juta", 1_000_000), ("jt", 1_000_000)]
+        .iter()
+        .find_map(|(suffix, multiplier)| token.strip_suffix(suffix).map(|number| (number, *multiplier)))
+        .unwrap_or((token, 1));
+
+    if !number.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    if multiplier > 1 {
+        // Dengan satuan, koma/titik dianggap desimal ("1,5jt" = 1.500.000)
+        let value: f64 = number.replace(',', ".").parse().ok()?;
+        return Some((value * multiplier as f64).round() as i64);
+    }
+
+    // Tanpa satuan, titik/koma hanya boleh sebagai pemisah ribuan ("25.000")
+    let groups: Vec<&str> = number.split(['.', ',']).collect();
+    let valid_groups = groups.iter().all(|group| !group.is_empty() && group.chars().all(|c| c.is_ascii_digit()))
+        && groups.iter().skip(1).all(|group| group.len() == 3)
+        && (groups.len() == 1 || groups[0].len() <= 3);
+    if !valid_groups {
+        return None;
+    }
+    groups.concat().parse().ok()
+}
+
+// Parse teks input cepat. Error berisi penjelasan yang bisa langsung ditampilkan ke user.
+pub fn parse_quick_input(text: &str) -> Result<QuickInput, String> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+
+    let amounts: Vec<(usize, i64)> = tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(index, token)| parse_jumlah(token).map(|jumlah| (index, jumlah)))
+        .collect();
+
+    let (index, jumlah) = match amounts.as_slice() {
+        [] => return Err("Jumlah tidak ditemukan. Contoh: \"kopi 25000 makanan\".".to_string()),
+        [amount] => *amount,
+        _ => {
+            let found: Vec<&str> = amounts.iter().map(|(index, _)| tokens[*index]).collect();
+            return Err(format!(
+                "Input ambigu: ditemukan lebih dari satu jumlah ({}). Tulis satu jumlah saja.",
+                found.join(", ")
+            ));
+        }
+    };
+
+    let jumlah = i32::try_from(jumlah)
+        .ok()
+        .filter(|jumlah| *jumlah > 0)
+        .ok_or_else(|| "Jumlah harus lebih dari 0 dan tidak terlalu besar.".to_string())?;
+
+    let kategori_nama = tokens[index + 1..].join(" ");
+    if kategori_nama.is_empty() {
+        return Err("Nama kategori tidak ditemukan. Tulis kategori setelah jumlah, mis. \"kopi 25000 makanan\".".to_string());
+    }
+
+    let deskripsi = tokens[..index].join(" ");
+
+    Ok(QuickInput {
+        deskripsi: (!deskripsi.is_empty()).then_some(deskripsi),
+        jumlah,
+        kategori_nama,
+    })
+}
@@ -0,0 +1,91 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+// Batas ukuran body error yang dibaca ulang untuk disisipi `request_id`
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+// Request id yang sedang diproses; tersedia di extensions request untuk handler yang membutuhkannya
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+// Pakai X-Request-Id dari client jika wajar (ASCII terlihat, maks 128 karakter), selain itu buat UUID v4 baru
+fn request_id_from(request: &Request) -> String {
+    request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= 128 && id.chars().all(|c| c.is_ascii_graphic()))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+// Middleware: beri setiap request sebuah id, kembalikan di header X-Request-Id,
+// dan sisipkan sebagai `request_id` di body JSON response error (4xx/5xx).
+// Response 5xx juga dicatat ke log beserta id-nya agar laporan user bisa dicocokkan dengan log server
+pub async fn propagate_request_id(mut request: Request, next: Next) -> Response {
+    let request_id = request_id_from(&request);
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    request.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let response = next.run(request).await;
+    let status = response.status();
+
+    if status.is_server_error() {
+        eprintln!("Request {} {} {} -> {}", request_id, method, path, status.as_u16());
+    }
+
+    let mut response = if status.is_client_error() || status.is_server_error() {
+        with_request_id_in_body(response, &request_id).await
+    } else {
+        response
+    };
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+// Tambahkan `request_id` ke body error berbentuk object JSON; body lain dikembalikan apa adanya
+async fn with_request_id_in_body(response: Response, request_id: &str) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            eprintln!("Request {}: gagal membaca body error: {:?}", request_id, err);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    let body = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(Value::Object(mut object)) => {
+            object.insert("request_id".to_string(), Value::String(request_id.to_string()));
+            Body::from(Value::Object(object).to_string())
+        }
+        _ => Body::from(bytes),
+    };
+
+    // Panjang body berubah; biarkan hyper menghitung ulang
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, body)
+}
@@ -0,0 +1,40 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+// Envelope response sukses yang seragam: {"status": "success", "message": ..., "data": ...}
+//
+// Modul kategori dan statistik sudah memakai envelope ini. Perubahan untuk client:
+// - GET /api/kategori sekarang mengembalikan daftar kategori di `data` (sebelumnya array langsung)
+// - `message` selalu ada (null jika tidak ada pesan)
+// - Field tambahan seperti `filter_applied`, `summary`, dan `debug` tetap di level atas
+//   selama masa transisi, lewat `meta`
+#[derive(Debug, Serialize)]
+pub struct ApiResponse<T: Serialize> {
+    pub status: &'static str,
+    pub message: Option<String>,
+    pub data: T,
+    #[serde(flatten)]
+    pub meta: Map<String, Value>,
+}
+
+impl<T: Serialize> ApiResponse<T> {
+    pub fn success(data: T) -> Self {
+        ApiResponse {
+            status: "success",
+            message: None,
+            data,
+            meta: Map::new(),
+        }
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    // Tambahkan field di level atas (mis. "filter_applied") di samping `data`
+    pub fn with_meta(mut self, key: &str, value: Value) -> Self {
+        self.meta.insert(key.to_string(), value);
+        self
+    }
+}
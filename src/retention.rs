@@ -0,0 +1,49 @@
+use std::{env, time::Duration};
+
+use crate::database::Database;
+
+// Purge dijalankan sekali sehari
+const PURGE_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+// Retensi (hari) dari TRASH_RETENTION_DAYS; None (nonaktif) jika tidak diset, 0, atau tidak valid
+pub fn retention_days_from_env() -> Option<i32> {
+    env::var("TRASH_RETENTION_DAYS")
+        .ok()
+        .and_then(|value| value.trim().parse::<i32>().ok())
+        .filter(|days| *days > 0)
+}
+
+// Hapus permanen refresh token yang sudah kedaluwarsa atau dicabut lebih dari `retention_days` hari lalu.
+// Transaksi dihapus secara langsung (tidak ada soft delete), jadi belum ada baris "trash" yang perlu dibersihkan
+pub async fn purge_expired(db: &Database, retention_days: i32) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM refresh_tokens
+        -- LEAST mengabaikan NULL, jadi token yang belum dicabut memakai expires_at
+        WHERE LEAST(revoked_at, expires_at) < NOW() - make_interval(days => $1)
+        "#
+    )
+    .bind(retention_days)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// Jalankan purge harian di background; tidak melakukan apa pun jika retensi nonaktif
+pub fn spawn_purge_task(db: Database) {
+    let Some(retention_days) = retention_days_from_env() else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(PURGE_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            match purge_expired(&db, retention_days).await {
+                Ok(count) => println!("🧹 Retensi {} hari: {} refresh token dihapus", retention_days, count),
+                Err(err) => eprintln!("Retention purge error: {:?}", err),
+            }
+        }
+    });
+}
@@ -1,12 +1,22 @@
 use axum::{
     extract::State,
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Json,
 };
+use chrono::{DateTime, Duration, Utc};
 use serde_json::{json, Value};
+use sqlx::{Postgres, Transaction};
 use uuid::Uuid;
+use std::sync::Arc;
 
+use crate::audit::{self, client_ip, EVENT_LOGIN, EVENT_PASSWORD_CHANGE};
 use crate::database::Database;
+use crate::extract::JsonBody;
+use crate::mail::{send_in_background, Mailer};
+use crate::jwt::{AuthUser, create_token, expires_in_minutes, generate_refresh_token, hash_refresh_token, refresh_expires_in_days};
+use crate::password::{hash_password, validate_password, verify_password};
+use crate::validate::normalize_email;
+use crate::routes::kategori::seed_default_kategori;
 use crate::models::user::{User, SignupRequest};
 
 #[derive(Debug, serde::Deserialize)]
@@ -22,12 +32,49 @@ pub struct ForgotPasswordRequest {
     pub confirm_password: String,
 }
 
+#[derive(Debug, serde::Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+// Simpan refresh token baru (hash) untuk user dan kembalikan token mentahnya
+async fn issue_refresh_token(tx: &mut Transaction<'_, Postgres>, user_id: Uuid) -> Result<String, sqlx::Error> {
+    let refresh_token = generate_refresh_token();
+    let expires_at = Utc::now() + Duration::days(refresh_expires_in_days());
+
+    sqlx::query("INSERT INTO refresh_tokens (user_id, token_hash, expires_at) VALUES ($1, $2, $3)")
+        .bind(user_id)
+        .bind(hash_refresh_token(&refresh_token))
+        .bind(expires_at)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(refresh_token)
+}
+
+// Naikkan token_version dan cabut semua refresh token aktif user, sehingga semua sesi lama
+// (access maupun refresh token) tidak bisa dipakai lagi. Dipakai saat password diganti/direset.
+pub(crate) async fn invalidate_sessions(tx: &mut Transaction<'_, Postgres>, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE users SET token_version = token_version + 1 WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL")
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
 pub async fn signup(
     State(db): State<Database>,
-    Json(payload): Json<SignupRequest>,
+    JsonBody(payload): JsonBody<SignupRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // Validasi input
-    if payload.email.is_empty() || payload.password.is_empty() {
+    // Validasi input (email dinormalisasi lebih dulu)
+    let email = normalize_email(&payload.email);
+    if email.is_empty() || payload.password.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(json!({
@@ -37,9 +84,20 @@ pub async fn signup(
         ));
     }
 
+    // Validasi kebijakan password
+    if let Err(message) = validate_password(&payload.password) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": message
+            }))
+        ));
+    }
+
     // Cek apakah email sudah terdaftar
     let existing_user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
-        .bind(&payload.email)
+        .bind(&email)
         .fetch_optional(&db)
         .await
         .map_err(|_| {
@@ -65,19 +123,38 @@ pub async fn signup(
     // Generate user ID
     let user_id = Uuid::new_v4();
 
-    // Note: Dalam production, Anda harus hash password menggunakan bcrypt atau argon2
-    // Untuk sementara, kita simpan password mentah (TIDAK AMAN untuk production!)
-    let password_hash = payload.password; // TODO: Hash password properly
+    // Hash password menggunakan Argon2
+    let password_hash = hash_password(&payload.password).map_err(|err| {
+        eprintln!("Password hash error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal membuat akun."
+            }))
+        )
+    })?;
+
+    // Insert user baru + kategori default dalam satu DB transaction
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
 
-    // Insert user baru
     let new_user = sqlx::query_as::<_, User>(
         "INSERT INTO users (id, username, email, password_hash) VALUES ($1, $2, $3, $4) RETURNING *"
     )
     .bind(user_id)
-    .bind(&payload.email) // Menggunakan email sebagai username sementara
-    .bind(&payload.email)
+    .bind(&email) // Menggunakan email sebagai username sementara
+    .bind(&email)
     .bind(&password_hash)
-    .fetch_one(&db)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|_| {
         (
@@ -89,6 +166,28 @@ pub async fn signup(
         )
     })?;
 
+    let categories = seed_default_kategori(&mut tx, new_user.id).await.map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal membuat kategori default."
+            }))
+        )
+    })?;
+
+    tx.commit().await.map_err(|err| {
+        eprintln!("Transaction commit error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal membuat akun."
+            }))
+        )
+    })?;
+
     // Response sukses
     Ok(Json(json!({
         "status": "success",
@@ -97,16 +196,19 @@ pub async fn signup(
             "id": new_user.id,
             "email": new_user.email,
             "created_at": new_user.created_at
-        }
+        },
+        "categories": categories
     })))
 }
 
 pub async fn signin(
     State(db): State<Database>,
-    Json(payload): Json<SigninRequest>,
+    headers: HeaderMap,
+    JsonBody(payload): JsonBody<SigninRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // Validasi input
-    if payload.email.is_empty() || payload.password.is_empty() {
+    // Validasi input (email dinormalisasi lebih dulu)
+    let email = normalize_email(&payload.email);
+    if email.is_empty() || payload.password.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(json!({
@@ -118,7 +220,7 @@ pub async fn signin(
 
     // Cari user berdasarkan email
     let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
-        .bind(&payload.email)
+        .bind(&email)
         .fetch_optional(&db)
         .await
         .map_err(|_| {
@@ -146,8 +248,8 @@ pub async fn signin(
     };
 
     // Verifikasi password
-    // Note: Dalam production, gunakan bcrypt::verify untuk hash password
-    if user.password_hash != payload.password {
+    let password_check = verify_password(&payload.password, &user.password_hash);
+    if !password_check.valid {
         return Err((
             StatusCode::UNAUTHORIZED,
             Json(json!({
@@ -157,15 +259,85 @@ pub async fn signin(
         ));
     }
 
+    // Upgrade hash lama (plaintext/bcrypt) ke Argon2 secara transparan
+    if password_check.needs_rehash {
+        match hash_password(&payload.password) {
+            Ok(new_hash) => {
+                if let Err(err) = sqlx::query("UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2")
+                    .bind(&new_hash)
+                    .bind(user.id)
+                    .execute(&db)
+                    .await
+                {
+                    eprintln!("Gagal upgrade password hash user {}: {:?}", user.id, err);
+                }
+            }
+            Err(err) => eprintln!("Password hash error: {:?}", err),
+        }
+    }
+
+    // Buat access token
+    let token = create_token(user.id, &user.role, user.token_version).map_err(|err| {
+        eprintln!("Token error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal membuat token."
+            }))
+        )
+    })?;
+
+    // Buat refresh token
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    let refresh_token = issue_refresh_token(&mut tx, user.id).await.map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal membuat token."
+            }))
+        )
+    })?;
+
+    tx.commit().await.map_err(|err| {
+        eprintln!("Transaction commit error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal membuat token."
+            }))
+        )
+    })?;
+
+    audit::record(&db, user.id, EVENT_LOGIN, client_ip(&headers).as_deref(), None).await;
+
     // Response sukses login
     Ok(Json(json!({
         "status": "success",
         "message": "Login berhasil!",
+        "token": token,
+        "token_type": "Bearer",
+        "expires_in": expires_in_minutes() * 60,
+        "refresh_token": refresh_token,
         "user_id": user.id,
         "user": {
             "id": user.id,
             "email": user.email,
             "username": user.username,
+            "role": user.role,
             "created_at": user.created_at
         }
     })))
@@ -173,10 +345,13 @@ pub async fn signin(
 
 pub async fn forgot_password(
     State(db): State<Database>,
-    Json(payload): Json<ForgotPasswordRequest>,
+    State(mailer): State<Arc<dyn Mailer>>,
+    headers: HeaderMap,
+    JsonBody(payload): JsonBody<ForgotPasswordRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // Validasi input
-    if payload.email.is_empty() || payload.new_password.is_empty() || payload.confirm_password.is_empty() {
+    // Validasi input (email dinormalisasi lebih dulu)
+    let email = normalize_email(&payload.email);
+    if email.is_empty() || payload.new_password.is_empty() || payload.confirm_password.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(json!({
@@ -197,20 +372,20 @@ pub async fn forgot_password(
         ));
     }
 
-    // Validasi panjang password
-    if payload.new_password.len() < 6 {
+    // Validasi kebijakan password
+    if let Err(message) = validate_password(&payload.new_password) {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(json!({
                 "success": false,
-                "message": "Password minimal 6 karakter."
+                "message": message
             }))
         ));
     }
 
     // Cari user berdasarkan email
     let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
-        .bind(&payload.email)
+        .bind(&email)
         .fetch_optional(&db)
         .await
         .map_err(|_| {
@@ -238,17 +413,8 @@ pub async fn forgot_password(
     };
 
     // Update password
-    // Note: Dalam production, hash password menggunakan bcrypt atau argon2
-    let password_hash = payload.new_password; // TODO: Hash password properly
-
-    let updated_user = sqlx::query_as::<_, User>(
-        "UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
-    )
-    .bind(&password_hash)
-    .bind(user.id)
-    .fetch_one(&db)
-    .await
-    .map_err(|_| {
+    let password_hash = hash_password(&payload.new_password).map_err(|err| {
+        eprintln!("Password hash error: {:?}", err);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({
@@ -258,6 +424,41 @@ pub async fn forgot_password(
         )
     })?;
 
+    let password_error = |_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "success": false,
+                "message": "Gagal mengupdate password."
+            }))
+        )
+    };
+
+    // Password baru dan pencabutan semua sesi lama disimpan bersamaan
+    let mut tx = db.begin().await.map_err(password_error)?;
+
+    let updated_user = sqlx::query_as::<_, User>(
+        "UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+    )
+    .bind(&password_hash)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(password_error)?;
+
+    invalidate_sessions(&mut tx, user.id).await.map_err(password_error)?;
+    tx.commit().await.map_err(password_error)?;
+
+    audit::record(&db, user.id, EVENT_PASSWORD_CHANGE, client_ip(&headers).as_deref(), Some(json!({ "via": "forgot_password" }))).await;
+
+    // Beri tahu pemilik akun bahwa password telah direset
+    send_in_background(
+        mailer,
+        updated_user.email.clone(),
+        "Password akun Savior Anda telah direset".to_string(),
+        "Password akun Savior Anda baru saja direset. Jika ini bukan Anda, segera hubungi admin.".to_string(),
+    );
+
     // Response sukses
     Ok(Json(json!({
         "success": true,
@@ -269,3 +470,160 @@ pub async fn forgot_password(
         }
     })))
 }
+
+// Tukar refresh token dengan pasangan access + refresh token baru (token lama langsung dicabut)
+pub async fn refresh(
+    State(db): State<Database>,
+    JsonBody(payload): JsonBody<RefreshTokenRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if payload.refresh_token.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "Refresh token wajib diisi."
+            }))
+        ));
+    }
+
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    // Cabut token lama; hanya berhasil jika token masih aktif dan belum kedaluwarsa
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        WITH revoked AS (
+            UPDATE refresh_tokens SET revoked_at = NOW()
+            WHERE token_hash = $1 AND revoked_at IS NULL AND expires_at > NOW()
+            RETURNING user_id
+        )
+        SELECT u.* FROM users u JOIN revoked r ON r.user_id = u.id
+        "#
+    )
+    .bind(hash_refresh_token(payload.refresh_token.trim()))
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    let user = match user {
+        Some(user) => user,
+        None => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(json!({
+                    "status": "error",
+                    "message": "Refresh token tidak valid atau sudah kedaluwarsa."
+                }))
+            ));
+        }
+    };
+
+    let refresh_token = issue_refresh_token(&mut tx, user.id).await.map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal membuat token."
+            }))
+        )
+    })?;
+
+    let token = create_token(user.id, &user.role, user.token_version).map_err(|err| {
+        eprintln!("Token error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal membuat token."
+            }))
+        )
+    })?;
+
+    tx.commit().await.map_err(|err| {
+        eprintln!("Transaction commit error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal membuat token."
+            }))
+        )
+    })?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Token berhasil diperbarui.",
+        "token": token,
+        "token_type": "Bearer",
+        "expires_in": expires_in_minutes() * 60,
+        "refresh_token": refresh_token
+    })))
+}
+
+// Cabut refresh token (logout)
+pub async fn logout(
+    State(db): State<Database>,
+    JsonBody(payload): JsonBody<RefreshTokenRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if payload.refresh_token.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "Refresh token wajib diisi."
+            }))
+        ));
+    }
+
+    // Idempotent: token yang sudah dicabut/tidak dikenal tetap dianggap logout berhasil
+    sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE token_hash = $1 AND revoked_at IS NULL")
+        .bind(hash_refresh_token(payload.refresh_token.trim()))
+        .execute(&db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Logout berhasil."
+    })))
+}
+
+// Cek token yang dikirim tanpa melakukan aksi apa pun (mis. saat frontend dimuat).
+// Token tidak valid, kedaluwarsa, atau sudah dicabut ditolak 401 oleh extractor AuthUser
+pub async fn validate_token(auth: AuthUser) -> Json<Value> {
+    let expires_at = DateTime::<Utc>::from_timestamp(auth.expires_at, 0);
+
+    Json(json!({
+        "status": "success",
+        "valid": true,
+        "user_id": auth.user_id,
+        "expires_at": expires_at
+    }))
+}
@@ -1,18 +1,932 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, State, Query},
+    http::{StatusCode, Uri},
     response::Json,
 };
 use serde_json::{json, Value};
+use sqlx::{FromRow, Postgres, Row, Transaction};
+use std::env;
 use uuid::Uuid;
+use chrono::{NaiveDate, Local, Datelike};
 
-use crate::database::Database;
-use crate::models::budget::{Budget, BudgetWithCategory, CreateBudgetRequest, UpdateBudgetRequest};
+use crate::budget_period::{effective_period_start, period_elapsed_fraction, period_end_for, period_start_for, recalculate_all_spent};
+use crate::database::{lock_user, Database};
+use crate::extract::JsonBody;
+use crate::jwt::AdminUser;
+use crate::pagination::offset_links;
+use crate::routes::kategori::{forbidden_kategori_response, kategori_access, KategoriAccess};
+use crate::format::{currency_minor_unit, format_amount};
+use crate::validate::FieldErrors;
+use crate::models::budget::{Budget, BudgetWithCategory, BudgetPageResponse, CreateBudgetRequest, UpsertBudgetRequest, BulkCreateBudgetRequest, SkippedBudget, UpdateBudgetRequest, BudgetListQuery, BudgetReportQuery, BudgetReportRow, BudgetReportItem, BurndownPoint, BudgetHistory, BudgetHistoryQuery, BudgetAlert, BudgetAlertQuery, AcknowledgeAlertsRequest, BudgetSuggestion};
+use crate::models::kategori::Kategori;
+use crate::models::profile::UserPreferences;
+use crate::models::statistik::RingkasanBudget;
+use crate::routes::profile::fetch_preferences;
 
-// Get all budgets for a user
+// Threshold alert (persen dari budget efektif), urut naik
+const ALERT_THRESHOLDS: [i32; 3] = [50, 80, 100];
+
+// Batas default budget per user, bisa diubah lewat MAX_BUDGETS
+const DEFAULT_MAX_BUDGETS: i64 = 200;
+
+pub fn max_budgets() -> i64 {
+    env::var("MAX_BUDGETS")
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .filter(|max| *max > 0)
+        .unwrap_or(DEFAULT_MAX_BUDGETS)
+}
+
+// Tolak jika budget user melebihi max_budgets(). Dipanggil di DB transaction yang sama setelah insert:
+// baris user dikunci dulu, jadi request bersamaan milik user yang sama dihitung bergantian
+// dan hitungan sudah memuat budget yang di-commit request lain
+pub async fn check_budget_limit(tx: &mut Transaction<'_, Postgres>, user_id: Uuid) -> Result<(), (StatusCode, Json<Value>)> {
+    lock_user(tx, user_id).await.map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM budgets WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    let max = max_budgets();
+    if count > max {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "status": "error",
+                "message": format!("Batas maksimal {} budget per user sudah tercapai.", max)
+            }))
+        ));
+    }
+
+    Ok(())
+}
+
+// Budget berstatus "near" mulai dari persentase ini (sama dengan threshold alert 80%) sampai 100%
+const NEAR_THRESHOLD: i64 = ALERT_THRESHOLDS[1] as i64;
+
+// Saran budget: rata-rata dari N bulan penuh terakhir, kategori harus punya pengeluaran minimal di M bulan
+const SUGGESTION_MONTHS: u32 = 3;
+const SUGGESTION_MIN_ACTIVE_MONTHS: i64 = 2;
+
+// Bulatkan ke atas ke 2 angka penting, mis. 123.456 -> 130.000 dan 4.321 -> 4.400
+fn round_up_nice(amount: i64) -> i64 {
+    if amount <= 0 {
+        return 0;
+    }
+    let digits = amount.ilog10();
+    let step = 10_i64.pow(digits.saturating_sub(1));
+    (amount + step - 1) / step * step
+}
+
+// Isi string jumlah terformat sesuai preferensi mata uang/locale user
+fn fill_formatted(budget: &mut BudgetWithCategory, preferences: &UserPreferences) {
+    budget.amount_formatted = format_amount(budget.amount as i64, budget.minor_unit, preferences);
+    budget.spent_formatted = format_amount(budget.spent as i64, budget.minor_unit, preferences);
+}
+
+// Semua budget user beserta nama kategori (jumlah terformat belum diisi)
+async fn fetch_user_budgets(db: &Database, user_id: Uuid) -> Result<Vec<BudgetWithCategory>, sqlx::Error> {
+    sqlx::query_as::<_, BudgetWithCategory>(
+        r#"
+        SELECT 
+            b.id,
+            b.user_id::text as user_id,
+            b.kategori_id,
+            c.nama as kategori_nama,
+            b.amount,
+            b.carry_over,
+            b.amount + b.carry_over as effective_amount,
+            COALESCE(b.spent, 0) as spent,
+            b.rollover,
+            b.period_start,
+            b.minor_unit,
+            CASE 
+                WHEN b.amount + b.carry_over > 0 THEN CAST(ROUND(COALESCE(b.spent, 0) * 100.0 / (b.amount + b.carry_over), 2) AS FLOAT8)
+                ELSE 0.0
+            END as percentage,
+            (
+                -- Transaksi yang membentuk spent: pengeluaran kategori ini di periode berjalan
+                SELECT COUNT(*) FROM transaksi t
+                WHERE t.user_id = b.user_id AND t.kategori_id = b.kategori_id
+                    AND t.tipe = 'pengeluaran'
+                    AND COALESCE(t.budget_period_start, t.tanggal) >= b.period_start
+                    AND COALESCE(t.budget_period_start, t.tanggal) < b.period_start + INTERVAL '1 month'
+            ) as transaction_count,
+            b.created_at,
+            b.updated_at
+        FROM budgets b
+        JOIN categories c ON b.kategori_id = c.id
+        WHERE b.user_id = $1
+        ORDER BY b.created_at DESC, b.id DESC
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await
+}
+
+// Status pemakaian budget dari spent terhadap budget efektif:
+// over = melebihi budget, near = NEAR_THRESHOLD% sampai 100%, under = di bawahnya
+fn budget_status(budget: &BudgetWithCategory) -> &'static str {
+    let spent = budget.spent as i64;
+    let effective = budget.effective_amount as i64;
+    if spent > effective {
+        "over"
+    } else if spent * 100 >= effective * NEAR_THRESHOLD && spent > 0 {
+        "near"
+    } else {
+        "under"
+    }
+}
+
+// Kategori yang bisa dipakai user (sistem + pribadi) tapi belum memiliki budget
+async fn fetch_kategori_tanpa_budget(db: &Database, user_id: Uuid) -> Result<Vec<Kategori>, sqlx::Error> {
+    sqlx::query_as::<_, Kategori>(
+        r#"
+        SELECT c.* FROM categories c
+        WHERE (c.user_id IS NULL OR c.user_id = $1)
+            AND NOT EXISTS (SELECT 1 FROM budgets b WHERE b.user_id = $1 AND b.kategori_id = c.id)
+        ORDER BY c.nama ASC, c.id ASC
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(db)
+    .await
+}
+
+// Get all budgets for a user, opsional difilter ?status=over|near|under (urutan tetap sama)
 pub async fn get_user_budgets(
     State(db): State<Database>,
-    Path(user_id): Path<String>,
+    Path(user_id): Path<String>,
+    Query(query): Query<BudgetListQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let status_filter = match query.status.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(status @ ("over" | "near" | "under")) => Some(status),
+        Some(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Parameter status harus 'over', 'near', atau 'under'."
+                }))
+            ));
+        }
+    };
+
+    let mut budgets = fetch_user_budgets(&db, user_uuid).await.map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    if let Some(status) = status_filter {
+        budgets.retain(|budget| budget_status(budget) == status);
+    }
+
+    let preferences = fetch_preferences(&db, user_uuid).await.unwrap_or_default();
+    for budget in budgets.iter_mut() {
+        fill_formatted(budget, &preferences);
+    }
+
+    Ok(Json(json!({
+        "status": "success",
+        "budgets": budgets
+    })))
+}
+
+// Aggregate khusus halaman budget di frontend: daftar budget, ringkasan total, alert aktif, dan
+// kategori yang belum memiliki budget dalam satu request. Bentuk response mengikuti kebutuhan
+// tampilan tersebut; integrasi lain sebaiknya memakai endpoint per fitur
+pub async fn get_budget_page(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let db_error = |err: sqlx::Error| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    };
+
+    let mut budgets = fetch_user_budgets(&db, user_uuid).await.map_err(db_error)?;
+    let alerts = fetch_budget_alerts(&db, user_uuid, false).await.map_err(db_error)?;
+    let kategori_tanpa_budget = fetch_kategori_tanpa_budget(&db, user_uuid).await.map_err(db_error)?;
+
+    let preferences = fetch_preferences(&db, user_uuid).await.unwrap_or_default();
+    for budget in budgets.iter_mut() {
+        fill_formatted(budget, &preferences);
+    }
+
+    // Ringkasan dihitung dari daftar yang sama supaya angkanya selalu cocok dengan yang ditampilkan
+    let summary = RingkasanBudget {
+        jumlah_budget: budgets.len() as i64,
+        total_budget: budgets.iter().map(|budget| budget.effective_amount as i64).sum(),
+        total_terpakai: budgets.iter().map(|budget| budget.spent as i64).sum(),
+        jumlah_over: budgets.iter().filter(|budget| budget.spent > budget.effective_amount).count() as i64,
+    };
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": BudgetPageResponse {
+            budgets,
+            summary,
+            alerts,
+            kategori_tanpa_budget,
+        }
+    })))
+}
+
+// Create new budget for a user
+pub async fn create_budget(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    JsonBody(payload): JsonBody<CreateBudgetRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    // Validasi semua field sekaligus
+    let mut errors = FieldErrors::new();
+
+    if payload.amount <= 0 {
+        errors.push("amount", "Amount harus lebih dari 0.");
+    }
+
+    // Cek apakah kategori exists
+    let kategori = kategori_access(&db, payload.kategori_id, user_uuid)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    match kategori {
+        KategoriAccess::Allowed => {}
+        KategoriAccess::NotFound => errors.push("kategori_id", "Kategori tidak ditemukan."),
+        KategoriAccess::Forbidden => return Err(forbidden_kategori_response()),
+    }
+
+    errors.into_result()?;
+
+    // Amount disimpan dalam minor unit mata uang user; eksponennya dicatat per baris
+    let minor_unit = fetch_preferences(&db, user_uuid)
+        .await
+        .map(|preferences| currency_minor_unit(&preferences.currency))
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    // Cek apakah user sudah punya budget untuk kategori ini
+    let existing_budget = sqlx::query_as::<_, Budget>(
+        "SELECT * FROM budgets WHERE user_id = $1 AND kategori_id = $2"
+    )
+    .bind(user_uuid)
+    .bind(payload.kategori_id)
+    .fetch_optional(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    if existing_budget.is_some() {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "status": "error",
+                "message": "Budget untuk kategori ini sudah ada."
+            }))
+        ));
+    }
+
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    // Insert budget baru
+    let new_budget = sqlx::query_as::<_, Budget>(
+        "INSERT INTO budgets (user_id, kategori_id, amount, minor_unit, rollover) VALUES ($1, $2, $3, $4, $5) RETURNING *"
+    )
+    .bind(user_uuid)
+    .bind(payload.kategori_id)
+    .bind(payload.amount)
+    .bind(minor_unit)
+    .bind(payload.rollover.unwrap_or(false))
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal membuat budget."
+            }))
+        )
+    })?;
+
+    check_budget_limit(&mut tx, user_uuid).await?;
+
+    tx.commit().await.map_err(|err| {
+        eprintln!("Transaction commit error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal menyimpan budget."
+            }))
+        )
+    })?;
+
+    // Response sukses
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Budget berhasil dibuat!",
+        "data": new_budget
+    })))
+}
+
+// Upsert budget per kategori: buat jika belum ada, update amount jika sudah ada.
+// Idempotent, jadi client tidak perlu tahu apakah budget kategori ini sudah dibuat.
+pub async fn upsert_budget(
+    State(db): State<Database>,
+    Path((user_id, kategori_id)): Path<(String, i32)>,
+    Json(payload): Json<UpsertBudgetRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    // Validasi semua field sekaligus
+    let mut errors = FieldErrors::new();
+
+    if payload.amount <= 0 {
+        errors.push("amount", "Amount harus lebih dari 0.");
+    }
+
+    // Cek apakah kategori exists
+    let kategori = kategori_access(&db, kategori_id, user_uuid)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    match kategori {
+        KategoriAccess::Allowed => {}
+        KategoriAccess::NotFound => errors.push("kategori_id", "Kategori tidak ditemukan."),
+        KategoriAccess::Forbidden => return Err(forbidden_kategori_response()),
+    }
+
+    errors.into_result()?;
+
+    // Minor unit hanya dipakai saat budget baru dibuat
+    let minor_unit = fetch_preferences(&db, user_uuid)
+        .await
+        .map(|preferences| currency_minor_unit(&preferences.currency))
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    // xmax = 0 hanya untuk baris yang baru di-insert, sehingga bisa membedakan create vs update
+    let row = sqlx::query(
+        r#"
+        INSERT INTO budgets (user_id, kategori_id, amount, minor_unit, rollover)
+        VALUES ($1, $2, $3, $4, COALESCE($5, FALSE))
+        ON CONFLICT (user_id, kategori_id) DO UPDATE SET
+            amount = EXCLUDED.amount,
+            rollover = COALESCE($5, budgets.rollover),
+            updated_at = NOW()
+        RETURNING *, (xmax = 0) as created
+        "#
+    )
+    .bind(user_uuid)
+    .bind(kategori_id)
+    .bind(payload.amount)
+    .bind(minor_unit)
+    .bind(payload.rollover)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal menyimpan budget."
+            }))
+        )
+    })?;
+
+    let created: bool = row.try_get("created").unwrap_or(false);
+    let budget = Budget::from_row(&row).map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal menyimpan budget."
+            }))
+        )
+    })?;
+
+    // Update budget yang sudah ada tidak menambah jumlah budget
+    if created {
+        check_budget_limit(&mut tx, user_uuid).await?;
+    }
+
+    tx.commit().await.map_err(|err| {
+        eprintln!("Transaction commit error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal menyimpan budget."
+            }))
+        )
+    })?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": if created { "Budget berhasil dibuat!" } else { "Budget berhasil diupdate!" },
+        "created": created,
+        "data": budget
+    })))
+}
+
+// Create multiple budgets at once (onboarding).
+// Kategori yang sudah punya budget atau tidak ditemukan dilewati dan dilaporkan di `skipped`.
+pub async fn bulk_create_budget(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    JsonBody(payload): JsonBody<BulkCreateBudgetRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    // Validasi input
+    if payload.budgets.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "Daftar budget tidak boleh kosong."
+            }))
+        ));
+    }
+
+    if let Some(invalid) = payload.budgets.iter().find(|budget| budget.amount <= 0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": format!("Amount untuk kategori {} harus lebih dari 0.", invalid.kategori_id)
+            }))
+        ));
+    }
+
+    // Amount disimpan dalam minor unit mata uang user; eksponennya dicatat per baris
+    let minor_unit = fetch_preferences(&db, user_uuid)
+        .await
+        .map(|preferences| currency_minor_unit(&preferences.currency))
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    let mut created: Vec<Budget> = Vec::new();
+    let mut skipped: Vec<SkippedBudget> = Vec::new();
+
+    for item in &payload.budgets {
+        // Kategori harus kategori sistem atau milik user ini
+        let category_exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1 AND (user_id IS NULL OR user_id = $2))"
+        )
+        .bind(item.kategori_id)
+        .bind(user_uuid)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+        if !category_exists {
+            skipped.push(SkippedBudget {
+                kategori_id: item.kategori_id,
+                reason: "Kategori tidak ditemukan.".to_string(),
+            });
+            continue;
+        }
+
+        let new_budget = sqlx::query_as::<_, Budget>(
+            "INSERT INTO budgets (user_id, kategori_id, amount, minor_unit, rollover) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (user_id, kategori_id) DO NOTHING RETURNING *"
+        )
+        .bind(user_uuid)
+        .bind(item.kategori_id)
+        .bind(item.amount)
+        .bind(minor_unit)
+        .bind(item.rollover.unwrap_or(false))
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Gagal membuat budget."
+                }))
+            )
+        })?;
+
+        match new_budget {
+            Some(budget) => created.push(budget),
+            None => skipped.push(SkippedBudget {
+                kategori_id: item.kategori_id,
+                reason: "Budget untuk kategori ini sudah ada.".to_string(),
+            }),
+        }
+    }
+
+    if !created.is_empty() {
+        check_budget_limit(&mut tx, user_uuid).await?;
+    }
+
+    tx.commit().await.map_err(|err| {
+        eprintln!("Transaction commit error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal menyimpan budget."
+            }))
+        )
+    })?;
+
+    // Response sukses
+    Ok(Json(json!({
+        "status": "success",
+        "message": format!("{} budget berhasil dibuat, {} dilewati.", created.len(), skipped.len()),
+        "created": created,
+        "skipped": skipped
+    })))
+}
+
+// Update budget
+pub async fn update_budget(
+    State(db): State<Database>,
+    Path((user_id, budget_id)): Path<(String, i32)>,
+    Json(payload): Json<UpdateBudgetRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    // Validasi input (amount dipakai sebagai pembagi untuk perhitungan persentase)
+    if payload.amount.is_some_and(|amount| amount <= 0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "Amount harus lebih dari 0."
+            }))
+        ));
+    }
+
+    if payload.spent.is_some_and(|spent| spent < 0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "Spent tidak boleh negatif."
+            }))
+        ));
+    }
+
+    // Cek apakah budget exists dan belongs to user
+    let existing_budget = sqlx::query_as::<_, Budget>(
+        "SELECT * FROM budgets WHERE id = $1 AND user_id = $2"
+    )
+    .bind(budget_id)
+    .bind(user_uuid)
+    .fetch_optional(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    if existing_budget.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Budget tidak ditemukan."
+            }))
+        ));
+    }
+
+    let _budget = existing_budget.unwrap();
+
+    // Update budget (hanya jika updated_at masih sama dengan yang diharapkan client)
+    let updated_budget = sqlx::query_as::<_, Budget>(
+        "UPDATE budgets SET amount = COALESCE($1, amount), spent = COALESCE($2, spent), rollover = COALESCE($3, rollover), updated_at = NOW() WHERE id = $4 AND ($5::timestamptz IS NULL OR updated_at = $5) RETURNING *"
+    )
+    .bind(payload.amount)
+    .bind(payload.spent)
+    .bind(payload.rollover)
+    .bind(budget_id)
+    .bind(payload.expected_updated_at)
+    .fetch_optional(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal mengupdate budget."
+            }))
+        )
+    })?;
+
+    // Tidak ada baris yang terupdate berarti budget sudah diubah oleh request lain
+    let updated_budget = match updated_budget {
+        Some(budget) => budget,
+        None => {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(json!({
+                    "status": "error",
+                    "message": "Budget telah diubah oleh request lain. Muat ulang data dan coba lagi."
+                }))
+            ));
+        }
+    };
+
+    // Response sukses
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Budget berhasil diupdate!",
+        "data": updated_budget
+    })))
+}
+
+// Delete budget
+pub async fn delete_budget(
+    State(db): State<Database>,
+    Path((user_id, budget_id)): Path<(String, i32)>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    // Cek apakah budget exists dan belongs to user
+    let existing_budget = sqlx::query_as::<_, Budget>(
+        "SELECT * FROM budgets WHERE id = $1 AND user_id = $2"
+    )
+    .bind(budget_id)
+    .bind(user_uuid)
+    .fetch_optional(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    if existing_budget.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Budget tidak ditemukan."
+            }))
+        ));
+    }
+
+    // Delete budget
+    sqlx::query("DELETE FROM budgets WHERE id = $1")
+        .bind(budget_id)
+        .execute(&db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Gagal menghapus budget."
+                }))
+            )
+        })?;
+
+    // Response sukses
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Budget berhasil dihapus!"
+    })))
+}
+
+// Get budget by ID
+pub async fn get_budget_by_id(
+    State(db): State<Database>,
+    Path((user_id, budget_id)): Path<(String, i32)>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Parse user_id as UUID
     let user_uuid = match Uuid::parse_str(&user_id) {
@@ -28,7 +942,7 @@ pub async fn get_user_budgets(
         }
     };
 
-    let budgets = sqlx::query_as::<_, BudgetWithCategory>(
+    let budget = sqlx::query_as::<_, BudgetWithCategory>(
         r#"
         SELECT 
             b.id,
@@ -36,18 +950,250 @@ pub async fn get_user_budgets(
             b.kategori_id,
             c.nama as kategori_nama,
             b.amount,
+            b.carry_over,
+            b.amount + b.carry_over as effective_amount,
             COALESCE(b.spent, 0) as spent,
+            b.rollover,
+            b.period_start,
+            b.minor_unit,
             CASE 
-                WHEN b.amount > 0 THEN (COALESCE(b.spent, 0)::float / b.amount::float * 100.0)
+                WHEN b.amount + b.carry_over > 0 THEN CAST(ROUND(COALESCE(b.spent, 0) * 100.0 / (b.amount + b.carry_over), 2) AS FLOAT8)
                 ELSE 0.0
-            END as percentage
+            END as percentage,
+            (
+                -- Transaksi yang membentuk spent: pengeluaran kategori ini di periode berjalan
+                SELECT COUNT(*) FROM transaksi t
+                WHERE t.user_id = b.user_id AND t.kategori_id = b.kategori_id
+                    AND t.tipe = 'pengeluaran'
+                    AND COALESCE(t.budget_period_start, t.tanggal) >= b.period_start
+                    AND COALESCE(t.budget_period_start, t.tanggal) < b.period_start + INTERVAL '1 month'
+            ) as transaction_count,
+            b.created_at,
+            b.updated_at
         FROM budgets b
         JOIN categories c ON b.kategori_id = c.id
-        WHERE b.user_id = $1
-        ORDER BY b.created_at DESC
+        WHERE b.id = $1 AND b.user_id = $2
+        "#
+    )
+    .bind(budget_id)
+    .bind(user_uuid)
+    .fetch_optional(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    match budget {
+        Some(mut budget) => {
+            let preferences = fetch_preferences(&db, user_uuid).await.unwrap_or_default();
+            fill_formatted(&mut budget, &preferences);
+            Ok(Json(json!({
+                "status": "success",
+                "data": budget
+            })))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Budget tidak ditemukan."
+            }))
+        ))
+    }
+}
+
+// Get monthly budget vs actual report.
+// Actual dihitung ulang dari transaksi bulan tersebut, bukan dari kolom `spent`.
+pub async fn get_budget_report(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    Query(query): Query<BudgetReportQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    // Parse bulan (default: bulan berjalan)
+    let start_of_month = match &query.month {
+        Some(month) => match NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Format bulan tidak valid. Gunakan format YYYY-MM."
+                    }))
+                ));
+            }
+        },
+        None => period_start_for(Local::now().naive_local().date()),
+    };
+    // Bulan dari query bisa berada di ujung rentang tanggal chrono, jadi akhir bulan dihitung tanpa unwrap
+    let end_of_month = start_of_month
+        .checked_add_months(chrono::Months::new(1))
+        .and_then(|next_month| next_month.pred_opt())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Bulan di luar rentang yang didukung."
+                }))
+            )
+        })?;
+
+    let report = fetch_budget_report(&db, user_uuid, start_of_month, end_of_month)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    let total_budget: i64 = report.iter().filter_map(|item| item.amount).sum();
+    let total_actual: i64 = report.iter().map(|item| item.actual).sum();
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": report,
+        "summary": {
+            "total_budget": total_budget,
+            "total_actual": total_actual,
+            "month": start_of_month.format("%Y-%m").to_string(),
+            "start_date": start_of_month.format("%Y-%m-%d").to_string(),
+            "end_date": end_of_month.format("%Y-%m-%d").to_string()
+        }
+    })))
+}
+
+// Budget vs aktual per kategori untuk rentang tanggal: kategori dengan budget atau dengan pengeluaran.
+// Dipakai juga oleh laporan bulanan statistik
+pub(crate) async fn fetch_budget_report(
+    db: &Database,
+    user_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<BudgetReportItem>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, BudgetReportRow>(
+        r#"
+        WITH actual AS (
+            SELECT kategori_id, SUM(jumlah)::BIGINT as total
+            FROM transaksi
+            WHERE user_id = $1 AND tipe = 'pengeluaran' AND tanggal >= $2 AND tanggal <= $3
+            GROUP BY kategori_id
+        )
+        SELECT
+            c.id as kategori_id,
+            c.nama as kategori_nama,
+            b.amount::BIGINT as amount,
+            COALESCE(a.total, 0) as actual
+        FROM categories c
+        LEFT JOIN budgets b ON b.kategori_id = c.id AND b.user_id = $1
+        LEFT JOIN actual a ON a.kategori_id = c.id
+        WHERE b.id IS NOT NULL OR a.total IS NOT NULL
+        ORDER BY b.id IS NULL, c.nama ASC
+        "#
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(db)
+    .await?;
+
+    let report = rows
+        .into_iter()
+        .map(|row| {
+            let (variance, status) = match row.amount {
+                Some(amount) => {
+                    let status = if row.actual > amount { "over" } else { "under" };
+                    (Some(amount - row.actual), status)
+                }
+                None => (None, "unbudgeted"),
+            };
+
+            BudgetReportItem {
+                kategori_id: row.kategori_id,
+                kategori_nama: row.kategori_nama,
+                amount: row.amount,
+                actual: row.actual,
+                variance,
+                status: status.to_string(),
+            }
+        })
+        .collect();
+
+    Ok(report)
+}
+
+// Get saran budget untuk kategori yang dipakai user tapi belum memiliki budget
+pub async fn get_budget_suggestions(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    // Jendela pengamatan: bulan-bulan penuh sebelum bulan berjalan
+    let today = Local::now().naive_local().date();
+    let start_of_month = today.with_day(1).unwrap_or(today);
+    let window_start = start_of_month - chrono::Months::new(SUGGESTION_MONTHS);
+    let window_end = start_of_month - chrono::Duration::days(1);
+
+    let mut suggestions = sqlx::query_as::<_, BudgetSuggestion>(
+        r#"
+        SELECT
+            c.id as kategori_id,
+            c.nama as kategori_nama,
+            CEIL(SUM(t.jumlah)::NUMERIC / $4)::BIGINT as rata_rata_bulanan,
+            COUNT(DISTINCT DATE_TRUNC('month', t.tanggal)) as bulan_aktif
+        FROM transaksi t
+        JOIN categories c ON t.kategori_id = c.id
+        WHERE t.user_id = $1 AND t.tipe = 'pengeluaran' AND t.tanggal >= $2 AND t.tanggal <= $3
+            AND NOT EXISTS (SELECT 1 FROM budgets b WHERE b.user_id = $1 AND b.kategori_id = t.kategori_id)
+        GROUP BY c.id, c.nama
+        HAVING COUNT(DISTINCT DATE_TRUNC('month', t.tanggal)) >= $5
+        ORDER BY rata_rata_bulanan DESC, c.nama ASC
         "#
     )
     .bind(user_uuid)
+    .bind(window_start)
+    .bind(window_end)
+    .bind(SUGGESTION_MONTHS as i32)
+    .bind(SUGGESTION_MIN_ACTIVE_MONTHS)
     .fetch_all(&db)
     .await
     .map_err(|err| {
@@ -61,17 +1207,26 @@ pub async fn get_user_budgets(
         )
     })?;
 
+    for suggestion in suggestions.iter_mut() {
+        suggestion.suggested_amount = round_up_nice(suggestion.rata_rata_bulanan);
+    }
+
     Ok(Json(json!({
         "status": "success",
-        "budgets": budgets
+        "data": suggestions,
+        "window": {
+            "months": SUGGESTION_MONTHS,
+            "min_active_months": SUGGESTION_MIN_ACTIVE_MONTHS,
+            "start_date": window_start,
+            "end_date": window_end
+        }
     })))
 }
 
-// Create new budget for a user
-pub async fn create_budget(
+// Get day-by-day cumulative spend for the current budget period plus the linear "ideal" line
+pub async fn get_budget_burndown(
     State(db): State<Database>,
-    Path(user_id): Path<String>,
-    Json(payload): Json<CreateBudgetRequest>,
+    Path((user_id, budget_id)): Path<(String, i32)>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Parse user_id as UUID
     let user_uuid = match Uuid::parse_str(&user_id) {
@@ -87,49 +1242,11 @@ pub async fn create_budget(
         }
     };
 
-    // Validasi input
-    if payload.amount <= 0 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "status": "error",
-                "message": "Amount harus lebih dari 0."
-            }))
-        ));
-    }
-
-    // Cek apakah kategori exists
-    let category_exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1)")
-        .bind(payload.kategori_id)
-        .fetch_one(&db)
-        .await
-        .map_err(|err| {
-            eprintln!("Database error: {:?}", err);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "status": "error",
-                    "message": "Terjadi kesalahan pada server."
-                }))
-            )
-        })?;
-
-    if !category_exists {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "status": "error",
-                "message": "Kategori tidak ditemukan."
-            }))
-        ));
-    }
-
-    // Cek apakah user sudah punya budget untuk kategori ini
-    let existing_budget = sqlx::query_as::<_, Budget>(
-        "SELECT * FROM budgets WHERE user_id = $1 AND kategori_id = $2"
+    let budget = sqlx::query_as::<_, Budget>(
+        "SELECT * FROM budgets WHERE id = $1 AND user_id = $2"
     )
+    .bind(budget_id)
     .bind(user_uuid)
-    .bind(payload.kategori_id)
     .fetch_optional(&db)
     .await
     .map_err(|err| {
@@ -143,24 +1260,41 @@ pub async fn create_budget(
         )
     })?;
 
-    if existing_budget.is_some() {
-        return Err((
-            StatusCode::CONFLICT,
-            Json(json!({
-                "status": "error",
-                "message": "Budget untuk kategori ini sudah ada."
-            }))
-        ));
-    }
+    let budget = match budget {
+        Some(budget) => budget,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "error",
+                    "message": "Budget tidak ditemukan."
+                }))
+            ));
+        }
+    };
 
-    // Insert budget baru
-    let new_budget = sqlx::query_as::<_, Budget>(
-        "INSERT INTO budgets (user_id, kategori_id, amount) VALUES ($1, $2, $3) RETURNING *"
+    let period_start = budget.period_start;
+    let period_end = period_end_for(period_start);
+    let today = Local::now().naive_local().date();
+
+    // Budget yang dibuat di tengah periode: garis ideal dimulai dari tanggal pembuatan
+    let ideal_start = effective_period_start(&budget);
+
+    let daily = sqlx::query_as::<_, (NaiveDate, i64)>(
+        r#"
+        SELECT tanggal, SUM(jumlah)::BIGINT
+        FROM transaksi
+        WHERE user_id = $1 AND kategori_id = $2 AND tipe = 'pengeluaran'
+            AND tanggal BETWEEN $3 AND $4
+        GROUP BY tanggal
+        ORDER BY tanggal
+        "#
     )
     .bind(user_uuid)
-    .bind(payload.kategori_id)
-    .bind(payload.amount)
-    .fetch_one(&db)
+    .bind(budget.kategori_id)
+    .bind(period_start)
+    .bind(period_end)
+    .fetch_all(&db)
     .await
     .map_err(|err| {
         eprintln!("Database error: {:?}", err);
@@ -168,24 +1302,68 @@ pub async fn create_budget(
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({
                 "status": "error",
-                "message": "Gagal membuat budget."
+                "message": "Terjadi kesalahan pada server."
             }))
         )
     })?;
 
-    // Response sukses
+    let effective_amount = (budget.amount + budget.carry_over) as f64;
+
+    let mut points = Vec::new();
+    let mut cumulative: i64 = 0;
+    let mut daily = daily.into_iter().peekable();
+    let mut tanggal = period_start;
+    while tanggal <= period_end {
+        while let Some((_, total)) = daily.next_if(|(day, _)| *day <= tanggal) {
+            cumulative += total;
+        }
+
+        let ideal = (effective_amount * period_elapsed_fraction(ideal_start, period_end, tanggal) * 100.0).round() / 100.0;
+
+        points.push(BurndownPoint {
+            tanggal,
+            aktual: (tanggal <= today).then_some(cumulative),
+            ideal,
+        });
+
+        tanggal = match tanggal.succ_opt() {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    // Proyeksi akhir periode dengan laju sejak awal efektif (prorata untuk budget yang dibuat di tengah periode)
+    let elapsed_fraction = period_elapsed_fraction(ideal_start, period_end, today);
+    let spent_to_date = points.iter().rev().find_map(|point| point.aktual).unwrap_or(0);
+    let projected_spent = (elapsed_fraction > 0.0).then(|| (spent_to_date as f64 / elapsed_fraction).round() as i64);
+
     Ok(Json(json!({
         "status": "success",
-        "message": "Budget berhasil dibuat!",
-        "data": new_budget
+        "data": points,
+        "forecast": {
+            "period_elapsed_fraction": (elapsed_fraction * 10000.0).round() / 10000.0,
+            "spent_to_date": spent_to_date,
+            "projected_spent": projected_spent,
+            "on_track": projected_spent.map(|projected| projected <= budget.amount as i64 + budget.carry_over as i64)
+        },
+        "budget": {
+            "id": budget.id,
+            "kategori_id": budget.kategori_id,
+            "amount": budget.amount,
+            "effective_amount": budget.amount + budget.carry_over,
+            "period_start": period_start,
+            "period_end": period_end,
+            "ideal_start": ideal_start
+        }
     })))
 }
 
-// Update budget
-pub async fn update_budget(
+// Get past budget periods (snapshot dari task reset periode), terbaru lebih dulu
+pub async fn get_budget_history(
     State(db): State<Database>,
     Path((user_id, budget_id)): Path<(String, i32)>,
-    Json(payload): Json<UpdateBudgetRequest>,
+    uri: Uri,
+    Query(query): Query<BudgetHistoryQuery>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Parse user_id as UUID
     let user_uuid = match Uuid::parse_str(&user_id) {
@@ -201,26 +1379,26 @@ pub async fn update_budget(
         }
     };
 
-    // Cek apakah budget exists dan belongs to user
-    let existing_budget = sqlx::query_as::<_, Budget>(
-        "SELECT * FROM budgets WHERE id = $1 AND user_id = $2"
-    )
-    .bind(budget_id)
-    .bind(user_uuid)
-    .fetch_optional(&db)
-    .await
-    .map_err(|err| {
-        eprintln!("Database error: {:?}", err);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({
-                "status": "error",
-                "message": "Terjadi kesalahan pada server."
-            }))
-        )
-    })?;
+    let limit = query.limit.unwrap_or(12).clamp(1, 100);
+    let offset = query.offset.unwrap_or(0).max(0);
 
-    if existing_budget.is_none() {
+    let budget_exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM budgets WHERE id = $1 AND user_id = $2)")
+        .bind(budget_id)
+        .bind(user_uuid)
+        .fetch_one(&db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    if !budget_exists {
         return Err((
             StatusCode::NOT_FOUND,
             Json(json!({
@@ -230,16 +1408,28 @@ pub async fn update_budget(
         ));
     }
 
-    let _budget = existing_budget.unwrap();
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM budget_history WHERE budget_id = $1")
+        .bind(budget_id)
+        .fetch_one(&db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
 
-    // Update budget
-    let updated_budget = sqlx::query_as::<_, Budget>(
-        "UPDATE budgets SET amount = COALESCE($1, amount), spent = COALESCE($2, spent), updated_at = NOW() WHERE id = $3 RETURNING *"
+    let history = sqlx::query_as::<_, BudgetHistory>(
+        "SELECT * FROM budget_history WHERE budget_id = $1 ORDER BY period_start DESC LIMIT $2 OFFSET $3"
     )
-    .bind(payload.amount)
-    .bind(payload.spent)
     .bind(budget_id)
-    .fetch_one(&db)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&db)
     .await
     .map_err(|err| {
         eprintln!("Database error: {:?}", err);
@@ -247,23 +1437,74 @@ pub async fn update_budget(
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({
                 "status": "error",
-                "message": "Gagal mengupdate budget."
+                "message": "Terjadi kesalahan pada server."
             }))
         )
     })?;
 
-    // Response sukses
     Ok(Json(json!({
         "status": "success",
-        "message": "Budget berhasil diupdate!",
-        "data": updated_budget
+        "data": history,
+        "pagination": {
+            "total": total,
+            "limit": limit,
+            "offset": offset
+        },
+        "links": offset_links(&uri, limit, offset, total)
     })))
 }
 
-// Delete budget
-pub async fn delete_budget(
+// Alert aktif user: budget yang sudah mencapai threshold dan belum di-acknowledge pada
+// threshold tersebut di periode berjalan. `include_acknowledged` ikut menampilkan yang sudah.
+async fn fetch_budget_alerts(db: &Database, user_id: Uuid, include_acknowledged: bool) -> Result<Vec<BudgetAlert>, sqlx::Error> {
+    sqlx::query_as::<_, BudgetAlert>(
+        r#"
+        WITH levels AS (
+            SELECT
+                b.id as budget_id,
+                b.kategori_id,
+                c.nama as kategori_nama,
+                b.amount + b.carry_over as effective_amount,
+                COALESCE(b.spent, 0) as spent,
+                b.period_start,
+                (
+                    SELECT MAX(t) FROM UNNEST($2::int[]) t
+                    WHERE COALESCE(b.spent, 0)::BIGINT * 100 >= t::BIGINT * (b.amount + b.carry_over)
+                ) as threshold
+            FROM budgets b
+            JOIN categories c ON c.id = b.kategori_id
+            WHERE b.user_id = $1 AND b.amount + b.carry_over > 0
+        )
+        SELECT
+            l.budget_id,
+            l.kategori_id,
+            l.kategori_nama,
+            l.effective_amount,
+            l.spent,
+            CAST(ROUND(l.spent * 100.0 / l.effective_amount, 2) AS FLOAT8) as percentage,
+            l.threshold,
+            l.period_start,
+            a.acknowledged_at
+        FROM levels l
+        LEFT JOIN budget_alerts a ON a.budget_id = l.budget_id
+            AND a.period_start = l.period_start
+            AND a.threshold >= l.threshold
+        WHERE l.threshold IS NOT NULL AND ($3 OR a.budget_id IS NULL)
+        ORDER BY l.threshold DESC, percentage DESC, l.kategori_nama ASC
+        "#
+    )
+    .bind(user_id)
+    .bind(&ALERT_THRESHOLDS[..])
+    .bind(include_acknowledged)
+    .fetch_all(db)
+    .await
+}
+
+// Get budget alerts for a user
+pub async fn get_budget_alerts(
     State(db): State<Database>,
-    Path((user_id, budget_id)): Path<(String, i32)>,
+    Path(user_id): Path<String>,
+    Query(query): Query<BudgetAlertQuery>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Parse user_id as UUID
     let user_uuid = match Uuid::parse_str(&user_id) {
@@ -279,39 +1520,7 @@ pub async fn delete_budget(
         }
     };
 
-    // Cek apakah budget exists dan belongs to user
-    let existing_budget = sqlx::query_as::<_, Budget>(
-        "SELECT * FROM budgets WHERE id = $1 AND user_id = $2"
-    )
-    .bind(budget_id)
-    .bind(user_uuid)
-    .fetch_optional(&db)
-    .await
-    .map_err(|err| {
-        eprintln!("Database error: {:?}", err);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({
-                "status": "error",
-                "message": "Terjadi kesalahan pada server."
-            }))
-        )
-    })?;
-
-    if existing_budget.is_none() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(json!({
-                "status": "error",
-                "message": "Budget tidak ditemukan."
-            }))
-        ));
-    }
-
-    // Delete budget
-    sqlx::query("DELETE FROM budgets WHERE id = $1")
-        .bind(budget_id)
-        .execute(&db)
+    let alerts = fetch_budget_alerts(&db, user_uuid, query.include_acknowledged.unwrap_or(false))
         .await
         .map_err(|err| {
             eprintln!("Database error: {:?}", err);
@@ -319,22 +1528,23 @@ pub async fn delete_budget(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(json!({
                     "status": "error",
-                    "message": "Gagal menghapus budget."
+                    "message": "Terjadi kesalahan pada server."
                 }))
             )
         })?;
 
-    // Response sukses
     Ok(Json(json!({
         "status": "success",
-        "message": "Budget berhasil dihapus!"
+        "data": alerts
     })))
 }
 
-// Get budget by ID
-pub async fn get_budget_by_id(
+// Acknowledge alerts: simpan threshold yang sedang aktif agar alert tidak muncul lagi
+// sampai spent mencapai threshold berikutnya atau periode budget berganti
+pub async fn acknowledge_budget_alerts(
     State(db): State<Database>,
-    Path((user_id, budget_id)): Path<(String, i32)>,
+    Path(user_id): Path<String>,
+    Json(payload): Json<AcknowledgeAlertsRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Parse user_id as UUID
     let user_uuid = match Uuid::parse_str(&user_id) {
@@ -350,27 +1560,53 @@ pub async fn get_budget_by_id(
         }
     };
 
-    let budget = sqlx::query_as::<_, BudgetWithCategory>(
+    if payload.budget_ids.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "budget_ids tidak boleh kosong."
+            }))
+        ));
+    }
+
+    // Hanya alert milik user yang sedang aktif yang bisa di-acknowledge
+    let alerts: Vec<BudgetAlert> = fetch_budget_alerts(&db, user_uuid, false)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?
+        .into_iter()
+        .filter(|alert| payload.budget_ids.contains(&alert.budget_id))
+        .collect();
+
+    let budget_ids: Vec<i32> = alerts.iter().map(|alert| alert.budget_id).collect();
+    let period_starts: Vec<NaiveDate> = alerts.iter().map(|alert| alert.period_start).collect();
+    let thresholds: Vec<i32> = alerts.iter().map(|alert| alert.threshold).collect();
+
+    sqlx::query(
         r#"
-        SELECT 
-            b.id,
-            b.user_id::text as user_id,
-            b.kategori_id,
-            c.nama as kategori_nama,
-            b.amount,
-            COALESCE(b.spent, 0) as spent,
-            CASE 
-                WHEN b.amount > 0 THEN (COALESCE(b.spent, 0)::float / b.amount::float * 100.0)
-                ELSE 0.0
-            END as percentage
-        FROM budgets b
-        JOIN categories c ON b.kategori_id = c.id
-        WHERE b.id = $1 AND b.user_id = $2
+        INSERT INTO budget_alerts (budget_id, user_id, period_start, threshold, acknowledged_at)
+        SELECT budget_id, $2, period_start, threshold, NOW()
+        FROM UNNEST($1::int[], $3::date[], $4::int[]) AS ack(budget_id, period_start, threshold)
+        ON CONFLICT (budget_id) DO UPDATE SET
+            period_start = EXCLUDED.period_start,
+            threshold = EXCLUDED.threshold,
+            acknowledged_at = EXCLUDED.acknowledged_at
         "#
     )
-    .bind(budget_id)
+    .bind(&budget_ids)
     .bind(user_uuid)
-    .fetch_optional(&db)
+    .bind(&period_starts)
+    .bind(&thresholds)
+    .execute(&db)
     .await
     .map_err(|err| {
         eprintln!("Database error: {:?}", err);
@@ -378,22 +1614,52 @@ pub async fn get_budget_by_id(
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({
                 "status": "error",
-                "message": "Terjadi kesalahan pada server."
+                "message": "Gagal menyimpan status alert."
             }))
         )
     })?;
 
-    match budget {
-        Some(budget) => Ok(Json(json!({
-            "status": "success",
-            "data": budget
-        }))),
-        None => Err((
-            StatusCode::NOT_FOUND,
+    // Budget id yang bukan milik user atau tidak punya alert aktif dilewati
+    let skipped: Vec<i32> = payload.budget_ids
+        .iter()
+        .filter(|budget_id| !budget_ids.contains(budget_id))
+        .copied()
+        .collect();
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": format!("{} alert berhasil ditandai.", budget_ids.len()),
+        "data": {
+            "acknowledged": budget_ids,
+            "skipped": skipped
+        }
+    })))
+}
+
+// Recalculate spent semua budget di sistem (admin only), untuk pemulihan setelah bug/edit data massal
+pub async fn recalculate_all_budgets(
+    State(db): State<Database>,
+    admin: AdminUser,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let summary = recalculate_all_spent(&db).await.map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({
                 "status": "error",
-                "message": "Budget tidak ditemukan."
+                "message": "Gagal menghitung ulang budget."
             }))
-        ))
-    }
+        )
+    })?;
+
+    println!(
+        "🔧 Admin {} menghitung ulang budget: {} diperiksa, {} diperbaiki, total selisih {}",
+        admin.0.user_id, summary.checked, summary.corrected, summary.total_drift
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": format!("{} budget diperbaiki.", summary.corrected),
+        "data": summary
+    })))
 }
@@ -0,0 +1,209 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::database::Database;
+use crate::extract::JsonBody;
+use crate::models::categorization_rule::{CategorizationRule, CreateCategorizationRuleRequest};
+use crate::routes::kategori::{forbidden_kategori_response, kategori_access, KategoriAccess};
+use crate::validate::{required_text, FieldError, MAX_DESKRIPSI_LEN};
+
+// Kategori dari aturan yang cocok dengan deskripsi. Jika beberapa aturan cocok,
+// pattern terpanjang (paling spesifik) menang, lalu aturan yang dibuat lebih dulu
+pub async fn matching_kategori(db: &Database, user_id: Uuid, deskripsi: &str) -> Result<Option<i32>, sqlx::Error> {
+    sqlx::query_scalar::<_, i32>(
+        r#"
+        SELECT kategori_id FROM categorization_rules
+        WHERE user_id = $1 AND POSITION(LOWER(pattern) IN LOWER($2)) > 0
+        ORDER BY LENGTH(pattern) DESC, id ASC
+        LIMIT 1
+        "#
+    )
+    .bind(user_id)
+    .bind(deskripsi)
+    .fetch_optional(db)
+    .await
+}
+
+// Get all categorization rules for a user
+pub async fn get_user_categorization_rules(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let rules = sqlx::query_as::<_, CategorizationRule>(
+        "SELECT * FROM categorization_rules WHERE user_id = $1 ORDER BY LENGTH(pattern) DESC, id ASC"
+    )
+    .bind(user_uuid)
+    .fetch_all(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": rules
+    })))
+}
+
+// Create categorization rule for a user
+pub async fn create_categorization_rule(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    JsonBody(payload): JsonBody<CreateCategorizationRuleRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let pattern = required_text("pattern", &payload.pattern, MAX_DESKRIPSI_LEN).map_err(FieldError::into_response)?;
+
+    let kategori = kategori_access(&db, payload.kategori_id, user_uuid)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    match kategori {
+        KategoriAccess::Allowed => {}
+        KategoriAccess::NotFound => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Kategori tidak ditemukan.",
+                    "field": "kategori_id"
+                }))
+            ));
+        }
+        KategoriAccess::Forbidden => return Err(forbidden_kategori_response()),
+    }
+
+    // Pattern yang sama (tanpa membedakan huruf besar/kecil) hanya boleh punya satu aturan
+    let rule = sqlx::query_as::<_, CategorizationRule>(
+        "INSERT INTO categorization_rules (user_id, pattern, kategori_id) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING RETURNING *"
+    )
+    .bind(user_uuid)
+    .bind(pattern)
+    .bind(payload.kategori_id)
+    .fetch_optional(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal membuat aturan kategori."
+            }))
+        )
+    })?;
+
+    match rule {
+        Some(rule) => Ok(Json(json!({
+            "status": "success",
+            "message": "Aturan kategori berhasil dibuat!",
+            "data": rule
+        }))),
+        None => Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "status": "error",
+                "message": "Aturan untuk pattern ini sudah ada."
+            }))
+        ))
+    }
+}
+
+// Delete categorization rule
+pub async fn delete_categorization_rule(
+    State(db): State<Database>,
+    Path((user_id, rule_id)): Path<(String, i32)>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let result = sqlx::query("DELETE FROM categorization_rules WHERE id = $1 AND user_id = $2")
+        .bind(rule_id)
+        .bind(user_uuid)
+        .execute(&db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Gagal menghapus aturan kategori."
+                }))
+            )
+        })?;
+
+    if result.rows_affected() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Aturan kategori tidak ditemukan."
+            }))
+        ));
+    }
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Aturan kategori berhasil dihapus!"
+    })))
+}
@@ -1,18 +1,267 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
+    extract::{Path, State, Query},
+    http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+    response::{IntoResponse, Json, Response},
 };
+use chrono::{DateTime, Utc};
 use serde_json::{json, Value};
+use sqlx::{Postgres, Transaction};
+use std::env;
+use uuid::Uuid;
 
-use crate::database::Database;
-use crate::models::kategori::{Kategori, CreateKategoriRequest, UpdateKategoriRequest};
+use crate::database::{lock_user, Database};
+use crate::extract::JsonBody;
+use crate::pagination::{offset_links, Pagination};
+use crate::response::ApiResponse;
+use crate::validate::{required_text, FieldError, MAX_NAMA_LEN};
+use crate::models::kategori::{Kategori, CreateKategoriRequest, SyncKategoriRequest, ReorderKategoriRequest, UpdateKategoriRequest, KategoriQuery};
+
+// Kategori default untuk user baru, bisa diganti lewat env DEFAULT_CATEGORIES (JSON array)
+const DEFAULT_KATEGORI: [&str; 4] = ["Makanan", "Transport", "Hiburan", "Tagihan"];
+
+// Batas jumlah nama kategori dalam satu request sync
+const MAX_SYNC_KATEGORI: usize = 100;
+
+// Batas default kategori pribadi per user, bisa diubah lewat MAX_CATEGORIES
+const DEFAULT_MAX_CATEGORIES: i64 = 200;
+
+pub fn max_categories() -> i64 {
+    env::var("MAX_CATEGORIES")
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .filter(|max| *max > 0)
+        .unwrap_or(DEFAULT_MAX_CATEGORIES)
+}
+
+pub fn default_kategori_names() -> Vec<String> {
+    env::var("DEFAULT_CATEGORIES")
+        .ok()
+        .and_then(|value| serde_json::from_str::<Vec<String>>(&value).ok())
+        .unwrap_or_else(|| DEFAULT_KATEGORI.iter().map(|nama| nama.to_string()).collect())
+}
+
+// Tolak jika kategori pribadi user melebihi max_categories(); kategori sistem tidak dibatasi.
+// Dipanggil di DB transaction yang sama setelah insert: baris user dikunci dulu, jadi request bersamaan
+// milik user yang sama dihitung bergantian dan hitungan sudah memuat kategori yang di-commit request lain
+pub async fn check_kategori_limit(tx: &mut Transaction<'_, Postgres>, user_id: Uuid) -> Result<(), (StatusCode, Json<Value>)> {
+    lock_user(tx, user_id).await.map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM categories WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    let max = max_categories();
+    if count > max {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "status": "error",
+                "message": format!("Batas maksimal {} kategori per user sudah tercapai. Hapus kategori yang tidak dipakai terlebih dahulu.", max)
+            }))
+        ));
+    }
+
+    Ok(())
+}
+
+// Error insert/update kategori. Pelanggaran unique index (nama tanpa membedakan huruf besar/kecil
+// per pemilik) dari request yang berjalan bersamaan dijawab 409 seperti pengecekan di handler
+fn kategori_write_error(err: sqlx::Error, message: &str) -> (StatusCode, Json<Value>) {
+    if err.as_database_error().is_some_and(|db_err| db_err.is_unique_violation()) {
+        return (
+            StatusCode::CONFLICT,
+            Json(json!({
+                "status": "error",
+                "message": "Kategori dengan nama tersebut sudah ada."
+            }))
+        );
+    }
+
+    eprintln!("Database error: {:?}", err);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({
+            "status": "error",
+            "message": message
+        }))
+    )
+}
+
+// Hak akses user terhadap sebuah kategori
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KategoriAccess {
+    Allowed, // Kategori sistem (user_id NULL) atau milik user sendiri
+    NotFound,
+    Forbidden, // Kategori pribadi milik user lain
+}
+
+// Cek apakah user boleh memakai kategori untuk budget/transaksi
+pub async fn kategori_access(db: &Database, kategori_id: i32, user_id: Uuid) -> Result<KategoriAccess, sqlx::Error> {
+    let owner = sqlx::query_scalar::<_, Option<Uuid>>("SELECT user_id FROM categories WHERE id = $1")
+        .bind(kategori_id)
+        .fetch_optional(db)
+        .await?;
+
+    Ok(match owner {
+        None => KategoriAccess::NotFound,
+        Some(None) => KategoriAccess::Allowed,
+        Some(Some(owner)) if owner == user_id => KategoriAccess::Allowed,
+        Some(Some(_)) => KategoriAccess::Forbidden,
+    })
+}
+
+// Response 403 untuk kategori pribadi milik user lain
+pub fn forbidden_kategori_response() -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::FORBIDDEN,
+        Json(json!({
+            "status": "error",
+            "message": "Kategori ini milik user lain dan tidak dapat digunakan."
+        }))
+    )
+}
+
+// Seed kategori default milik user di dalam DB transaction yang sudah berjalan.
+// Dilewati jika user sudah punya kategori; nama yang sudah ada sebagai kategori sistem tidak diduplikasi.
+pub async fn seed_default_kategori(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+) -> Result<Vec<Kategori>, sqlx::Error> {
+    let existing_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM categories WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    if existing_count > 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut created = Vec::new();
+    for nama in default_kategori_names() {
+        let nama = nama.trim();
+        if nama.is_empty() {
+            continue;
+        }
+
+        let kategori = sqlx::query_as::<_, Kategori>(
+            r#"
+            INSERT INTO categories (nama, user_id)
+            SELECT $1, $2
+            WHERE NOT EXISTS (
+                SELECT 1 FROM categories
+                WHERE LOWER(nama) = LOWER($1) AND (user_id IS NULL OR user_id = $2)
+            )
+            RETURNING *
+            "#
+        )
+        .bind(nama)
+        .bind(user_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        created.extend(kategori);
+    }
+
+    Ok(created)
+}
+
+// Bentuk kanonik nama kategori: spasi berlebih dirapikan, huruf besar/kecil dipertahankan
+fn canonical_nama(nama: &str) -> String {
+    nama.split_whitespace().collect::<Vec<_>>().join(" ")
+}
 
 // Get all categories
+// Tanpa user_id hanya kategori sistem yang dikembalikan. Mendukung pagination limit/offset.
+// Mendukung conditional request: ETag (berubah saat kategori dibuat/diubah/dihapus) dan
+// Last-Modified (max updated_at). If-None-Match diprioritaskan; Last-Modified tidak ikut
+// berubah saat kategori dihapus, jadi client sebaiknya memakai ETag.
+// Diurutkan menurut sort_order (hasil reorder), lalu nama.
 pub async fn get_all_kategori(
     State(db): State<Database>,
-) -> Result<Json<Vec<Kategori>>, (StatusCode, Json<Value>)> {
-    let categories = sqlx::query_as::<_, Kategori>("SELECT * FROM categories ORDER BY created_at DESC")
+    Query(query): Query<KategoriQuery>,
+    pagination: Pagination,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    let (count, max_id, last_modified) = sqlx::query_as::<_, (i64, Option<i32>, Option<DateTime<Utc>>)>(
+        "SELECT COUNT(*), MAX(id), MAX(updated_at) FROM categories WHERE user_id IS NULL OR user_id = $1"
+    )
+    .bind(query.user_id)
+    .fetch_one(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    // Halaman ikut menentukan ETag karena isi response berbeda per limit/offset
+    let etag = format!(
+        "W/\"kategori-{}-{}-{}-{}-{}\"",
+        count,
+        max_id.unwrap_or(0),
+        last_modified.map(|ts| ts.timestamp_micros()).unwrap_or(0),
+        pagination.limit,
+        pagination.offset
+    );
+    let last_modified_header = last_modified.map(|ts| ts.format("%a, %d %b %Y %H:%M:%S GMT").to_string());
+
+    let not_modified = match headers.get(header::IF_NONE_MATCH).and_then(|value| value.to_str().ok()) {
+        Some(if_none_match) => if_none_match.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*"),
+        None => match (
+            headers.get(header::IF_MODIFIED_SINCE).and_then(|value| value.to_str().ok()),
+            last_modified,
+        ) {
+            (Some(since), Some(last_modified)) => DateTime::parse_from_rfc2822(since)
+                .map(|since| last_modified.timestamp() <= since.timestamp())
+                .unwrap_or(false),
+            _ => false,
+        },
+    };
+
+    let mut response_headers = HeaderMap::new();
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, value);
+    }
+    if let Some(value) = last_modified_header.and_then(|value| HeaderValue::from_str(&value).ok()) {
+        response_headers.insert(header::LAST_MODIFIED, value);
+    }
+
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, response_headers).into_response());
+    }
+
+    let categories = sqlx::query_as::<_, Kategori>(
+        "SELECT * FROM categories WHERE user_id IS NULL OR user_id = $1 ORDER BY sort_order ASC, nama ASC, id ASC LIMIT $2 OFFSET $3"
+    )
+        .bind(query.user_id)
+        .bind(pagination.limit)
+        .bind(pagination.offset)
         .fetch_all(&db)
         .await
         .map_err(|err| {
@@ -26,28 +275,33 @@ pub async fn get_all_kategori(
             )
         })?;
 
-    Ok(Json(categories))
+    let response = ApiResponse::success(categories)
+        .with_meta("pagination", json!({
+            "total": count,
+            "limit": pagination.limit,
+            "offset": pagination.offset
+        }))
+        .with_meta("links", offset_links(&uri, pagination.limit, pagination.offset, count));
+
+    Ok((response_headers, Json(response)).into_response())
 }
 
 // Create new category
 pub async fn create_kategori(
     State(db): State<Database>,
-    Json(payload): Json<CreateKategoriRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    JsonBody(payload): JsonBody<CreateKategoriRequest>,
+) -> Result<Json<ApiResponse<Kategori>>, (StatusCode, Json<Value>)> {
     // Validasi input
-    if payload.nama.trim().is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "status": "error",
-                "message": "Nama kategori wajib diisi."
-            }))
-        ));
-    }
+    let nama = canonical_nama(
+        required_text("nama", &payload.nama, MAX_NAMA_LEN).map_err(FieldError::into_response)?
+    );
 
-    // Cek apakah kategori dengan nama yang sama sudah ada
-    let existing_category = sqlx::query_as::<_, Kategori>("SELECT * FROM categories WHERE nama = $1")
-        .bind(&payload.nama.trim())
+    // Cek apakah kategori dengan nama yang sama (tanpa membedakan huruf besar/kecil) sudah ada (kategori sistem atau milik user yang sama)
+    let existing_category = sqlx::query_as::<_, Kategori>(
+        "SELECT * FROM categories WHERE LOWER(nama) = LOWER($1) AND (user_id IS NULL OR user_id IS NOT DISTINCT FROM $2)"
+    )
+        .bind(&nama)
+        .bind(payload.user_id)
         .fetch_optional(&db)
         .await
         .map_err(|err| {
@@ -71,53 +325,87 @@ pub async fn create_kategori(
         ));
     }
 
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
     // Insert kategori baru
     let new_category = sqlx::query_as::<_, Kategori>(
-        "INSERT INTO categories (nama) VALUES ($1) RETURNING *"
+        "INSERT INTO categories (nama, user_id) VALUES ($1, $2) RETURNING *"
     )
-    .bind(&payload.nama.trim())
-    .fetch_one(&db)
+    .bind(&nama)
+    .bind(payload.user_id)
+    .fetch_one(&mut *tx)
     .await
-    .map_err(|err| {
-        eprintln!("Database error: {:?}", err);
+    .map_err(|err| kategori_write_error(err, "Gagal membuat kategori."))?;
+
+    if let Some(user_id) = payload.user_id {
+        check_kategori_limit(&mut tx, user_id).await?;
+    }
+
+    tx.commit().await.map_err(|err| {
+        eprintln!("Transaction commit error: {:?}", err);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({
                 "status": "error",
-                "message": "Gagal membuat kategori."
+                "message": "Gagal menyimpan kategori."
             }))
         )
     })?;
 
     // Response sukses
-    Ok(Json(json!({
-        "status": "success",
-        "message": "Kategori berhasil dibuat!",
-        "data": new_category
-    })))
+    Ok(Json(ApiResponse::success(new_category).with_message("Kategori berhasil dibuat!")))
 }
 
-// Update category
-pub async fn update_kategori(
+// Sinkronisasi daftar kategori milik user (mis. saat onboarding): nama yang belum ada dibuat,
+// nama yang sudah ada (kategori sistem atau milik user, tanpa membedakan huruf besar/kecil) dipakai apa adanya.
+// Response berisi kategori hasil resolve sesuai urutan request, duplikat di request hanya muncul sekali.
+pub async fn sync_kategori(
     State(db): State<Database>,
-    Path(kategori_id): Path<i32>,
-    Json(payload): Json<UpdateKategoriRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    JsonBody(payload): JsonBody<SyncKategoriRequest>,
+) -> Result<Json<ApiResponse<Vec<Kategori>>>, (StatusCode, Json<Value>)> {
     // Validasi input
-    if payload.nama.trim().is_empty() {
+    if payload.categories.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(json!({
                 "status": "error",
-                "message": "Nama kategori wajib diisi."
+                "message": "Daftar kategori tidak boleh kosong."
             }))
         ));
     }
 
-    // Cek apakah kategori dengan ID tersebut ada
-    let existing_category = sqlx::query_as::<_, Kategori>("SELECT * FROM categories WHERE id = $1")
-        .bind(kategori_id)
-        .fetch_optional(&db)
+    if payload.categories.len() > MAX_SYNC_KATEGORI {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": format!("Maksimal {} kategori per sinkronisasi.", MAX_SYNC_KATEGORI)
+            }))
+        ));
+    }
+
+    let mut names: Vec<String> = Vec::new();
+    for nama in &payload.categories {
+        let nama = canonical_nama(
+            required_text("categories", nama, MAX_NAMA_LEN).map_err(FieldError::into_response)?
+        );
+        if !names.iter().any(|existing| existing.to_lowercase() == nama.to_lowercase()) {
+            names.push(nama);
+        }
+    }
+
+    let user_exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+        .bind(payload.user_id)
+        .fetch_one(&db)
         .await
         .map_err(|err| {
             eprintln!("Database error: {:?}", err);
@@ -130,20 +418,251 @@ pub async fn update_kategori(
             )
         })?;
 
-    if existing_category.is_none() {
+    if !user_exists {
         return Err((
             StatusCode::NOT_FOUND,
             Json(json!({
                 "status": "error",
-                "message": "Kategori tidak ditemukan."
+                "message": "User tidak ditemukan."
             }))
         ));
     }
 
-    // Cek apakah ada kategori lain dengan nama yang sama
-    let duplicate_category = sqlx::query_as::<_, Kategori>("SELECT * FROM categories WHERE nama = $1 AND id != $2")
-        .bind(&payload.nama.trim())
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    let mut resolved: Vec<Kategori> = Vec::new();
+    let mut created_count = 0;
+
+    for nama in &names {
+        let new_category = sqlx::query_as::<_, Kategori>(
+            r#"
+            INSERT INTO categories (nama, user_id)
+            SELECT $1, $2
+            WHERE NOT EXISTS (
+                SELECT 1 FROM categories
+                WHERE LOWER(nama) = LOWER($1) AND (user_id IS NULL OR user_id = $2)
+            )
+            RETURNING *
+            "#
+        )
+        .bind(nama)
+        .bind(payload.user_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|err| kategori_write_error(err, "Gagal membuat kategori."))?;
+
+        let kategori = match new_category {
+            Some(kategori) => {
+                created_count += 1;
+                kategori
+            }
+            // Sudah ada: kategori milik user lebih diutamakan daripada kategori sistem bernama sama
+            None => sqlx::query_as::<_, Kategori>(
+                "SELECT * FROM categories WHERE LOWER(nama) = LOWER($1) AND (user_id IS NULL OR user_id = $2) ORDER BY user_id NULLS LAST, id LIMIT 1"
+            )
+            .bind(nama)
+            .bind(payload.user_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|err| {
+                eprintln!("Database error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Terjadi kesalahan pada server."
+                    }))
+                )
+            })?,
+        };
+
+        resolved.push(kategori);
+    }
+
+    if created_count > 0 {
+        check_kategori_limit(&mut tx, payload.user_id).await?;
+    }
+
+    tx.commit().await.map_err(|err| {
+        eprintln!("Transaction commit error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal menyimpan kategori."
+            }))
+        )
+    })?;
+
+    Ok(Json(ApiResponse::success(resolved)
+        .with_message(format!("{} kategori baru dibuat.", created_count))
+        .with_meta("created_count", json!(created_count))))
+}
+
+// Atur ulang urutan kategori milik user. Daftar harus berisi tepat semua kategori pribadi user,
+// masing-masing sekali; kategori sistem dipakai bersama semua user sehingga urutannya tidak bisa diubah.
+// sort_order diisi sesuai posisi (mulai 1) dalam satu DB transaction
+pub async fn reorder_kategori(
+    State(db): State<Database>,
+    JsonBody(payload): JsonBody<ReorderKategoriRequest>,
+) -> Result<Json<ApiResponse<Vec<Kategori>>>, (StatusCode, Json<Value>)> {
+    let mut ids = payload.kategori_ids.clone();
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.len() != payload.kategori_ids.len() {
+        return Err(FieldError {
+            field: "kategori_ids",
+            message: "kategori_ids tidak boleh berisi id yang sama lebih dari sekali.".to_string(),
+        }
+        .into_response());
+    }
+
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    // Kunci kategori milik user agar kategori baru/terhapus di tengah reorder tidak terlewat
+    let owned_ids = sqlx::query_scalar::<_, i32>(
+        "SELECT id FROM categories WHERE user_id = $1 ORDER BY id FOR UPDATE"
+    )
+    .bind(payload.user_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    if ids.is_empty() || ids != owned_ids {
+        return Err(FieldError {
+            field: "kategori_ids",
+            message: "kategori_ids harus berisi tepat semua kategori milik user.".to_string(),
+        }
+        .into_response());
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE categories c SET sort_order = o.position::INT, updated_at = NOW()
+        FROM UNNEST($2::int[]) WITH ORDINALITY AS o(id, position)
+        WHERE c.id = o.id AND c.user_id = $1
+        "#
+    )
+    .bind(payload.user_id)
+    .bind(&payload.kategori_ids)
+    .execute(&mut *tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal menyimpan urutan kategori."
+            }))
+        )
+    })?;
+
+    let categories = sqlx::query_as::<_, Kategori>(
+        "SELECT * FROM categories WHERE user_id = $1 ORDER BY sort_order ASC, nama ASC, id ASC"
+    )
+    .bind(payload.user_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    tx.commit().await.map_err(|err| {
+        eprintln!("Transaction commit error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal menyimpan urutan kategori."
+            }))
+        )
+    })?;
+
+    Ok(Json(ApiResponse::success(categories).with_message("Urutan kategori berhasil disimpan.")))
+}
+
+// Update category
+pub async fn update_kategori(
+    State(db): State<Database>,
+    Path(kategori_id): Path<i32>,
+    Json(payload): Json<UpdateKategoriRequest>,
+) -> Result<Json<ApiResponse<Kategori>>, (StatusCode, Json<Value>)> {
+    // Validasi input
+    let nama = canonical_nama(
+        required_text("nama", &payload.nama, MAX_NAMA_LEN).map_err(FieldError::into_response)?
+    );
+
+    // Cek apakah kategori dengan ID tersebut ada
+    let existing_category = sqlx::query_as::<_, Kategori>("SELECT * FROM categories WHERE id = $1")
+        .bind(kategori_id)
+        .fetch_optional(&db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    let existing_category = match existing_category {
+        Some(category) => category,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "error",
+                    "message": "Kategori tidak ditemukan."
+                }))
+            ));
+        }
+    };
+
+    // Cek apakah ada kategori lain dengan nama yang sama (dalam lingkup pemilik yang sama)
+    let duplicate_category = sqlx::query_as::<_, Kategori>(
+        "SELECT * FROM categories WHERE LOWER(nama) = LOWER($1) AND id != $2 AND (user_id IS NULL OR user_id IS NOT DISTINCT FROM $3)"
+    )
+        .bind(&nama)
         .bind(kategori_id)
+        .bind(existing_category.user_id)
         .fetch_optional(&db)
         .await
         .map_err(|err| {
@@ -171,34 +690,21 @@ pub async fn update_kategori(
     let updated_category = sqlx::query_as::<_, Kategori>(
         "UPDATE categories SET nama = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
     )
-    .bind(&payload.nama.trim())
+    .bind(&nama)
     .bind(kategori_id)
     .fetch_one(&db)
     .await
-    .map_err(|err| {
-        eprintln!("Database error: {:?}", err);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({
-                "status": "error",
-                "message": "Gagal mengupdate kategori."
-            }))
-        )
-    })?;
+    .map_err(|err| kategori_write_error(err, "Gagal mengupdate kategori."))?;
 
     // Response sukses
-    Ok(Json(json!({
-        "status": "success",
-        "message": "Kategori berhasil diupdate!",
-        "data": updated_category
-    })))
+    Ok(Json(ApiResponse::success(updated_category).with_message("Kategori berhasil diupdate!")))
 }
 
 // Delete category
 pub async fn delete_kategori(
     State(db): State<Database>,
     Path(kategori_id): Path<i32>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<ApiResponse<()>>, (StatusCode, Json<Value>)> {
     // Cek apakah kategori dengan ID tersebut ada
     let existing_category = sqlx::query_as::<_, Kategori>("SELECT * FROM categories WHERE id = $1")
         .bind(kategori_id)
@@ -242,17 +748,14 @@ pub async fn delete_kategori(
         })?;
 
     // Response sukses
-    Ok(Json(json!({
-        "status": "success",
-        "message": "Kategori berhasil dihapus!"
-    })))
+    Ok(Json(ApiResponse::success(()).with_message("Kategori berhasil dihapus!")))
 }
 
 // Get category by ID
 pub async fn get_kategori_by_id(
     State(db): State<Database>,
     Path(kategori_id): Path<i32>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<ApiResponse<Kategori>>, (StatusCode, Json<Value>)> {
     let category = sqlx::query_as::<_, Kategori>("SELECT * FROM categories WHERE id = $1")
         .bind(kategori_id)
         .fetch_optional(&db)
@@ -269,10 +772,7 @@ pub async fn get_kategori_by_id(
         })?;
 
     match category {
-        Some(category) => Ok(Json(json!({
-            "status": "success",
-            "data": category
-        }))),
+        Some(category) => Ok(Json(ApiResponse::success(category))),
         None => Err((
             StatusCode::NOT_FOUND,
             Json(json!({
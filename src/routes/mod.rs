@@ -5,3 +5,5 @@ pub mod kategori;
 pub mod budget;
 pub mod transaksi;
 pub mod statistik;
+pub mod webhook;
+pub mod categorization_rule;
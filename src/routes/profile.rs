@@ -1,14 +1,33 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::Json,
 };
 use serde_json::{json, Value};
 use uuid::Uuid;
+use std::sync::Arc;
 
+use crate::audit::{self, client_ip, EVENT_PASSWORD_CHANGE};
 use crate::database::Database;
+use crate::jwt::AuthUser;
+use crate::routes::auth::invalidate_sessions;
+use crate::mail::{send_in_background, Mailer};
+use crate::validate::{currency_code, date_format, locale_tag, week_start, normalize_email, optional_text, required_text, FieldError, MAX_NAMA_LEN};
+use crate::password::{hash_password, validate_password, verify_password};
 use crate::models::user::User;
-use crate::models::profile::{Profile, UpdateProfileRequest, UpdateEmailRequest, UpdatePasswordRequest};
+use crate::models::profile::{UpdateProfileRequest, UpdateDisplayNameRequest, UpdateEmailRequest, UpdatePasswordRequest, UpdateDailyLimitRequest, UserPreferences, UpdatePreferencesRequest, AuditEvent, ActivityQuery};
+
+// Ambil preferensi user, atau default (IDR/id-ID) jika belum pernah disimpan
+pub(crate) async fn fetch_preferences(db: &Database, user_id: Uuid) -> Result<UserPreferences, sqlx::Error> {
+    let preferences = sqlx::query_as::<_, UserPreferences>(
+        "SELECT currency, locale, date_format, week_start, created_at, updated_at FROM user_preferences WHERE user_id = $1"
+    )
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(preferences.unwrap_or_default())
+}
 
 pub async fn get_profile(
     State(db): State<Database>,
@@ -42,21 +61,41 @@ pub async fn get_profile(
         }
     };
 
+    let preferences = fetch_preferences(&db, user.id).await.map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "success": false,
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
     // Response sukses dengan data profile
     Ok(Json(json!({
         "success": true,
         "message": "Profile berhasil dimuat.",
         "profile": {
             "id": user.id,
-            "first_name": user.username.split_whitespace().next().unwrap_or(""),
-            "last_name": user.username.split_whitespace().skip(1).collect::<Vec<&str>>().join(" "),
+            "first_name": user.first_name.unwrap_or_default(),
+            "last_name": user.last_name.unwrap_or_default(),
+            "display_name": user.username,
             "email": user.email,
+            "preferences": preferences,
             "created_at": user.created_at,
             "updated_at": user.updated_at
         }
     })))
 }
 
+// Profile user yang sedang login, diambil dari subject access token (tanpa :user_id)
+pub async fn get_me(
+    State(db): State<Database>,
+    auth: AuthUser,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    get_profile(State(db), Path(auth.user_id)).await
+}
+
 pub async fn update_profile(
     State(db): State<Database>,
     Path(user_id): Path<Uuid>,
@@ -73,18 +112,29 @@ pub async fn update_profile(
         ));
     }
 
-    // Gabungkan first_name dan last_name menjadi username
-    let full_name = format!(
-        "{} {}",
-        payload.first_name.as_deref().unwrap_or(""),
-        payload.last_name.as_deref().unwrap_or("")
-    ).trim().to_string();
+    // Validasi dan trim nama; first_name tidak boleh kosong jika dikirim
+    let first_name = payload.first_name.as_deref()
+        .map(|first_name| required_text("first_name", first_name, MAX_NAMA_LEN))
+        .transpose()
+        .map_err(FieldError::into_success_response)?;
+    let last_name = optional_text("last_name", payload.last_name.as_deref(), MAX_NAMA_LEN)
+        .map_err(FieldError::into_success_response)?;
 
-    // Update username di database
+    // Hanya field yang dikirim yang diubah; last_name kosong menghapus nama belakang.
+    // username (display name) tidak disentuh, lihat update_display_name
     let updated_user = sqlx::query_as::<_, User>(
-        "UPDATE users SET username = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+        r#"
+        UPDATE users SET
+            first_name = COALESCE($1, first_name),
+            last_name = CASE WHEN $2 THEN $3 ELSE last_name END,
+            updated_at = NOW()
+        WHERE id = $4
+        RETURNING *
+        "#
     )
-    .bind(&full_name)
+    .bind(first_name)
+    .bind(payload.last_name.is_some())
+    .bind(last_name)
     .bind(user_id)
     .fetch_optional(&db)
     .await
@@ -104,8 +154,9 @@ pub async fn update_profile(
             "message": "Profile berhasil diupdate!",
             "profile": {
                 "id": user.id,
-                "first_name": payload.first_name,
-                "last_name": payload.last_name,
+                "first_name": user.first_name.unwrap_or_default(),
+                "last_name": user.last_name.unwrap_or_default(),
+                "display_name": user.username,
                 "email": user.email,
                 "updated_at": user.updated_at
             }
@@ -120,13 +171,81 @@ pub async fn update_profile(
     }
 }
 
+// Ubah display name (kolom username) tanpa mengubah nama depan/belakang
+pub async fn update_display_name(
+    State(db): State<Database>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<UpdateDisplayNameRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let display_name = required_text("display_name", &payload.display_name, MAX_NAMA_LEN)
+        .map_err(FieldError::into_success_response)?;
+
+    // username unik di tabel users
+    let taken = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE username = $1 AND id <> $2)")
+        .bind(display_name)
+        .bind(user_id)
+        .fetch_one(&db)
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "success": false,
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    if taken {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "success": false,
+                "message": "Display name sudah dipakai user lain."
+            }))
+        ));
+    }
+
+    let result = sqlx::query("UPDATE users SET username = $1, updated_at = NOW() WHERE id = $2")
+        .bind(display_name)
+        .bind(user_id)
+        .execute(&db)
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "success": false,
+                    "message": "Gagal mengupdate display name."
+                }))
+            )
+        })?;
+
+    if result.rows_affected() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "success": false,
+                "message": "User tidak ditemukan."
+            }))
+        ));
+    }
+
+    // Response berisi profile lengkap terbaru
+    let Json(mut body) = get_profile(State(db), Path(user_id)).await?;
+    body["message"] = json!("Display name berhasil diupdate!");
+    Ok(Json(body))
+}
+
 pub async fn update_email(
     State(db): State<Database>,
+    State(mailer): State<Arc<dyn Mailer>>,
     Path(user_id): Path<Uuid>,
     Json(payload): Json<UpdateEmailRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // Validasi input
-    if payload.new_email.is_empty() || payload.password.is_empty() {
+    // Validasi input (email dinormalisasi lebih dulu)
+    let new_email = normalize_email(&payload.new_email);
+    if new_email.is_empty() || payload.password.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(json!({
@@ -165,7 +284,7 @@ pub async fn update_email(
     };
 
     // Verifikasi password
-    if user.password_hash != payload.password {
+    if !verify_password(&payload.password, &user.password_hash).valid {
         return Err((
             StatusCode::UNAUTHORIZED,
             Json(json!({
@@ -177,7 +296,7 @@ pub async fn update_email(
 
     // Cek apakah email sudah digunakan user lain
     let existing_user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1 AND id != $2")
-        .bind(&payload.new_email)
+        .bind(&new_email)
         .bind(user_id)
         .fetch_optional(&db)
         .await
@@ -205,7 +324,7 @@ pub async fn update_email(
     let updated_user = sqlx::query_as::<_, User>(
         "UPDATE users SET email = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
     )
-    .bind(&payload.new_email)
+    .bind(&new_email)
     .bind(user_id)
     .fetch_one(&db)
     .await
@@ -219,6 +338,17 @@ pub async fn update_email(
         )
     })?;
 
+    // Kirim pemberitahuan ke alamat lama, agar pemilik akun tahu jika perubahan ini bukan dari dirinya
+    send_in_background(
+        mailer,
+        user.email.clone(),
+        "Email akun Savior Anda telah diubah".to_string(),
+        format!(
+            "Email akun Savior Anda telah diubah menjadi {}. Jika ini bukan Anda, segera hubungi admin.",
+            updated_user.email
+        ),
+    );
+
     // Response sukses
     Ok(Json(json!({
         "success": true,
@@ -234,6 +364,7 @@ pub async fn update_email(
 pub async fn update_password(
     State(db): State<Database>,
     Path(user_id): Path<Uuid>,
+    headers: HeaderMap,
     Json(payload): Json<UpdatePasswordRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Validasi input
@@ -247,13 +378,13 @@ pub async fn update_password(
         ));
     }
 
-    // Validasi panjang password baru
-    if payload.new_password.len() < 6 {
+    // Validasi kebijakan password baru
+    if let Err(message) = validate_password(&payload.new_password) {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(json!({
                 "success": false,
-                "message": "Password baru minimal 6 karakter."
+                "message": message
             }))
         ));
     }
@@ -287,7 +418,7 @@ pub async fn update_password(
     };
 
     // Verifikasi password lama
-    if user.password_hash != payload.current_password {
+    if !verify_password(&payload.current_password, &user.password_hash).valid {
         return Err((
             StatusCode::UNAUTHORIZED,
             Json(json!({
@@ -298,33 +429,324 @@ pub async fn update_password(
     }
 
     // Update password
-    // Note: Dalam production, hash password menggunakan bcrypt atau argon2
-    let new_password_hash = payload.new_password; // TODO: Hash password properly
+    let new_password_hash = hash_password(&payload.new_password).map_err(|err| {
+        eprintln!("Password hash error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "success": false,
+                "message": "Gagal mengupdate password."
+            }))
+        )
+    })?;
+
+    let password_error = |_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "success": false,
+                "message": "Gagal mengupdate password."
+            }))
+        )
+    };
+
+    // Password baru dan pencabutan semua sesi lama disimpan bersamaan
+    let mut tx = db.begin().await.map_err(password_error)?;
 
     let updated_user = sqlx::query_as::<_, User>(
         "UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
     )
     .bind(&new_password_hash)
     .bind(user_id)
-    .fetch_one(&db)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(password_error)?;
+
+    invalidate_sessions(&mut tx, user_id).await.map_err(password_error)?;
+    tx.commit().await.map_err(password_error)?;
+
+    audit::record(&db, user_id, EVENT_PASSWORD_CHANGE, client_ip(&headers).as_deref(), None).await;
+
+    // Response sukses
+    Ok(Json(json!({
+        "success": true,
+        "message": "Password berhasil diupdate!",
+        "profile": {
+            "id": updated_user.id,
+            "updated_at": updated_user.updated_at
+        }
+    })))
+}
+
+pub async fn update_daily_limit(
+    State(db): State<Database>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<UpdateDailyLimitRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Validasi input
+    if matches!(payload.daily_limit, Some(limit) if limit <= 0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "success": false,
+                "message": "Batas harian harus lebih dari 0."
+            }))
+        ));
+    }
+
+    let updated_user = sqlx::query_as::<_, User>(
+        "UPDATE users SET daily_limit = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+    )
+    .bind(payload.daily_limit)
+    .bind(user_id)
+    .fetch_optional(&db)
     .await
     .map_err(|_| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({
                 "success": false,
-                "message": "Gagal mengupdate password."
+                "message": "Gagal mengupdate batas harian."
+            }))
+        )
+    })?;
+
+    match updated_user {
+        Some(user) => Ok(Json(json!({
+            "success": true,
+            "message": "Batas harian berhasil diupdate!",
+            "profile": {
+                "id": user.id,
+                "daily_limit": user.daily_limit,
+                "updated_at": user.updated_at
+            }
+        }))),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "success": false,
+                "message": "User tidak ditemukan."
+            }))
+        ))
+    }
+}
+
+pub async fn get_preferences(
+    State(db): State<Database>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let user_exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+        .bind(user_id)
+        .fetch_one(&db)
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "success": false,
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    if !user_exists {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "success": false,
+                "message": "User tidak ditemukan."
+            }))
+        ));
+    }
+
+    let preferences = fetch_preferences(&db, user_id).await.map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "success": false,
+                "message": "Terjadi kesalahan pada server."
             }))
         )
     })?;
 
-    // Response sukses
     Ok(Json(json!({
         "success": true,
-        "message": "Password berhasil diupdate!",
-        "profile": {
-            "id": updated_user.id,
-            "updated_at": updated_user.updated_at
+        "message": "Preferensi berhasil dimuat.",
+        "preferences": preferences
+    })))
+}
+
+pub async fn update_preferences(
+    State(db): State<Database>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<UpdatePreferencesRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Validasi input (field yang tidak dikirim tidak diubah)
+    let currency = payload.currency.as_deref()
+        .map(currency_code)
+        .transpose()
+        .map_err(FieldError::into_success_response)?;
+    let locale = payload.locale.as_deref()
+        .map(locale_tag)
+        .transpose()
+        .map_err(FieldError::into_success_response)?;
+    let date_format = payload.date_format.as_deref()
+        .map(date_format)
+        .transpose()
+        .map_err(FieldError::into_success_response)?;
+    let week_start = payload.week_start.as_deref()
+        .map(week_start)
+        .transpose()
+        .map_err(FieldError::into_success_response)?;
+
+    let user_exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+        .bind(user_id)
+        .fetch_one(&db)
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "success": false,
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    if !user_exists {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "success": false,
+                "message": "User tidak ditemukan."
+            }))
+        ));
+    }
+
+    let defaults = UserPreferences::default();
+
+    // Jumlah tersimpan memakai minor unit mata uang saat ditulis dan ditampilkan dengan simbol mata uang
+    // preferensi, jadi mata uang tidak boleh diganti selama masih ada transaksi atau budget
+    if let Some(currency) = &currency {
+        let blocked = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT COALESCE((SELECT currency FROM user_preferences WHERE user_id = $1), $3) <> $2
+                AND (
+                    EXISTS(SELECT 1 FROM transaksi WHERE user_id = $1)
+                    OR EXISTS(SELECT 1 FROM budgets WHERE user_id = $1)
+                )
+            "#
+        )
+        .bind(user_id)
+        .bind(currency)
+        .bind(&defaults.currency)
+        .fetch_one(&db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "success": false,
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+        if blocked {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(json!({
+                    "success": false,
+                    "message": "Mata uang tidak dapat diubah selama masih ada transaksi atau budget.",
+                    "field": "currency"
+                }))
+            ));
         }
+    }
+
+    let preferences = sqlx::query_as::<_, UserPreferences>(
+        r#"
+        INSERT INTO user_preferences (user_id, currency, locale, date_format, week_start)
+        VALUES ($1, COALESCE($2, $6), COALESCE($3, $7), COALESCE($4, $8), COALESCE($5, $9))
+        ON CONFLICT (user_id) DO UPDATE SET
+            currency = COALESCE($2, user_preferences.currency),
+            locale = COALESCE($3, user_preferences.locale),
+            date_format = COALESCE($4, user_preferences.date_format),
+            week_start = COALESCE($5, user_preferences.week_start),
+            updated_at = NOW()
+        RETURNING currency, locale, date_format, week_start, created_at, updated_at
+        "#
+    )
+    .bind(user_id)
+    .bind(currency)
+    .bind(locale)
+    .bind(date_format)
+    .bind(week_start)
+    .bind(&defaults.currency)
+    .bind(&defaults.locale)
+    .bind(&defaults.date_format)
+    .bind(&defaults.week_start)
+    .fetch_one(&db)
+    .await
+    .map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "success": false,
+                "message": "Gagal mengupdate preferensi."
+            }))
+        )
+    })?;
+
+    Ok(Json(json!({
+        "success": true,
+        "message": "Preferensi berhasil diupdate!",
+        "preferences": preferences
+    })))
+}
+
+// Aktivitas terbaru user (login, ganti password, transaksi besar) agar akses mencurigakan mudah dikenali
+pub async fn get_activity(
+    State(db): State<Database>,
+    auth: AuthUser,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<ActivityQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Aktivitas berisi event login dan IP, jadi hanya bisa dilihat oleh pemilik akun
+    if auth.user_id != user_id {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({
+                "success": false,
+                "message": "Anda tidak memiliki akses ke aktivitas user ini."
+            }))
+        ));
+    }
+
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+
+    let events = sqlx::query_as::<_, AuditEvent>(
+        "SELECT id, event_type, ip_address, detail, created_at FROM audit_log WHERE user_id = $1 ORDER BY created_at DESC, id DESC LIMIT $2"
+    )
+    .bind(user_id)
+    .bind(limit)
+    .fetch_all(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "success": false,
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    Ok(Json(json!({
+        "success": true,
+        "message": "Aktivitas berhasil dimuat.",
+        "activity": events
     })))
 }
@@ -1,35 +1,377 @@
 use axum::{
     extract::{Path, State, Query},
-    http::StatusCode,
-    response::Json,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
 use serde_json::{json, Value};
 use uuid::Uuid;
 use chrono::{NaiveDate, Local, Datelike};
+use std::collections::HashMap;
+use std::env;
+use std::time::{Duration, Instant};
 
+use crate::budget_period::period_end_for;
 use crate::database::Database;
-use crate::models::statistik::{StatistikResponse, PengeluaranKategori, RingkasanPengeluaran, PengeluaranRange, StatistikQuery, DashboardResponse, ChartDataPoint, TransaksiTerakhir};
+use crate::jwt::AdminUser;
+use crate::models::profile::WEEK_START_SUNDAY;
+use crate::state::SpendingRangesCache;
+use crate::format::{label_language, month_label, weekday_label};
+use crate::routes::budget::fetch_budget_report;
+use crate::routes::profile::fetch_preferences;
+use crate::routes::kategori::{forbidden_kategori_response, kategori_access, KategoriAccess};
+use crate::response::ApiResponse;
+use crate::models::statistik::{KATEGORI_LAINNYA_ID, StatistikResponse, KategoriStatistikResponse, KategoriHarianPoint, PengeluaranKategori, RingkasanPengeluaran, PengeluaranRange, SpendingRangesQuery, StatistikQuery, DashboardQuery, DashboardResponse, ChartDataPoint, TransaksiTerakhir, HeatmapPoint, HeatmapQuery, TopDeskripsi, TopDeskripsiQuery, CashflowPoint, CashflowQuery, DayOfMonthPoint, DayOfMonthQuery, OverviewResponse, RingkasanBudget, VelocityResponse, CompareQuery, CompareResponse, RangeStatistik, KategoriDelta, TransaksiTerbesar, TransaksiTerbesarQuery, KategoriTidakTerpakai, KategoriTidakTerpakaiQuery, StreakQuery, StreakResponse, LaporanBulananQuery, LaporanBulananResponse};
+use crate::routes::transaksi::parse_optional_date;
+use crate::validate::FieldErrors;
 
-// Get user statistics
-pub async fn get_user_statistik(
-    State(db): State<Database>,
-    Path(user_id): Path<String>,
-    Query(query): Query<StatistikQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // Parse user_id as UUID
-    let user_uuid = match Uuid::parse_str(&user_id) {
-        Ok(uuid) => uuid,
-        Err(_) => {
-            return Err((
+// Batas rasio velocity: di atas ACCELERATING dianggap makin boros, di bawah SLOWING makin hemat
+const VELOCITY_ACCELERATING: f64 = 1.2;
+const VELOCITY_SLOWING: f64 = 0.8;
+const VELOCITY_MONTHS: u32 = 3;
+
+// Rentang pengeluaran bulanan (label, batas atas inklusif); rentang terakhir tanpa batas atas
+const SPENDING_RANGES: [(&str, Option<i64>); 4] = [
+    ("$ 0 - $ 20,000", Some(20000)),
+    ("$ 20,000 - $ 30,000", Some(30000)),
+    ("$ 30,000 - $ 60,000", Some(60000)),
+    ("more than $ 60,000", None),
+];
+
+// TTL default cache spending ranges, bisa diubah lewat SPENDING_RANGES_CACHE_SECS
+const DEFAULT_SPENDING_RANGES_CACHE_SECS: u64 = 300;
+
+// Rentang tahun yang diterima parameter `year`
+const MIN_YEAR: i32 = 1970;
+const MAX_YEAR: i32 = 2100;
+
+// Tanggal pertama dan terakhir bulan dari parameter year/month; 400 jika di luar rentang
+fn month_bounds(year: i32, month: u32) -> Result<(NaiveDate, NaiveDate), (StatusCode, Json<Value>)> {
+    let invalid = |message: String| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": message
+            }))
+        )
+    };
+
+    if !(MIN_YEAR..=MAX_YEAR).contains(&year) {
+        return Err(invalid(format!("Parameter year harus antara {} dan {}.", MIN_YEAR, MAX_YEAR)));
+    }
+    if !(1..=12).contains(&month) {
+        return Err(invalid("Parameter month harus antara 1 dan 12.".to_string()));
+    }
+
+    let start = NaiveDate::from_ymd_opt(year, month, 1);
+    let end = start
+        .and_then(|start| start.checked_add_months(chrono::Months::new(1)))
+        .map(|next_month| next_month - chrono::Duration::days(1));
+    start.zip(end).ok_or_else(|| invalid("Parameter year/month tidak valid.".to_string()))
+}
+
+// Pertahankan `top` kategori pertama (baris sudah terurut dari pengeluaran terbesar) dan
+// gabungkan sisanya menjadi satu slice "Lainnya" agar pie chart tidak dipenuhi slice kecil
+fn collapse_kategori_lainnya(mut rows: Vec<PengeluaranKategori>, top: usize, total_pengeluaran: i64, total_pemasukan: i64) -> Vec<PengeluaranKategori> {
+    if rows.len() <= top {
+        return rows;
+    }
+
+    let lainnya_total: i64 = rows.split_off(top).iter().map(|row| row.total_pengeluaran).sum();
+    let persentase = if total_pengeluaran > 0 {
+        (lainnya_total as f64 * 100.0 / total_pengeluaran as f64 * 100.0).round() / 100.0
+    } else {
+        0.0
+    };
+    let percentage_of_income = (total_pemasukan > 0)
+        .then(|| (lainnya_total as f64 * 100.0 / total_pemasukan as f64 * 100.0).round() / 100.0);
+    rows.push(PengeluaranKategori {
+        kategori_id: KATEGORI_LAINNYA_ID,
+        kategori_nama: "Lainnya".to_string(),
+        total_pengeluaran: lainnya_total,
+        persentase,
+        percentage_of_income,
+    });
+    rows
+}
+
+// Label rentang untuk total pengeluaran bulanan
+fn spending_range_label(amount: i64) -> &'static str {
+    SPENDING_RANGES
+        .iter()
+        .find(|(_, upper)| upper.is_none_or(|upper| amount <= upper))
+        .map(|(label, _)| *label)
+        .unwrap_or_default()
+}
+
+// true jika client meminta CSV lewat `?format=csv` atau header `Accept: text/csv`;
+// `?format=` eksplisit selalu menang atas header Accept
+fn wants_csv(format: Option<&str>, headers: &HeaderMap) -> bool {
+    match format {
+        Some(format) => format.eq_ignore_ascii_case("csv"),
+        None => headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .map(|accept| accept.split(',').any(|media| media.trim().starts_with("text/csv")))
+            .unwrap_or(false),
+    }
+}
+
+// Jumlah transaksi terbesar yang dimuat di laporan bulanan
+const LAPORAN_TRANSAKSI_TERBESAR: i64 = 5;
+
+// Kutip field CSV jika berisi koma, kutip, atau baris baru
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Breakdown per kategori sebagai CSV: kategori_nama,total_pengeluaran,persentase
+fn kategori_csv(rows: &[PengeluaranKategori]) -> String {
+    let mut csv = String::from("kategori_nama,total_pengeluaran,persentase\n");
+    for row in rows {
+        csv.push_str(&format!("{},{},{:.2}\n", csv_field(&row.kategori_nama), row.total_pengeluaran, row.persentase));
+    }
+    csv
+}
+
+// Laporan bulanan sebagai CSV: satu bagian per isi laporan (judul, header, baris), dipisah baris kosong
+fn laporan_csv(laporan: &LaporanBulananResponse) -> String {
+    let ringkasan = &laporan.ringkasan;
+    let mut csv = format!(
+        "Ringkasan\nbulan,total_pengeluaran,total_pemasukan,total_transaksi,rata_rata_harian\n{},{},{},{},{:.2}\n",
+        laporan.bulan, ringkasan.total_pengeluaran, ringkasan.total_pemasukan, ringkasan.total_transaksi, ringkasan.rata_rata_harian
+    );
+
+    csv.push_str("\nPengeluaran per kategori\n");
+    csv.push_str(&kategori_csv(&laporan.pengeluaran_per_kategori));
+
+    csv.push_str("\nTransaksi terbesar\ntanggal,kategori_nama,deskripsi,jumlah\n");
+    for transaksi in &laporan.transaksi_terbesar {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            transaksi.tanggal.format("%Y-%m-%d"),
+            csv_field(&transaksi.kategori_nama),
+            csv_field(&transaksi.deskripsi),
+            transaksi.jumlah
+        ));
+    }
+
+    csv.push_str("\nPengeluaran harian\ntanggal,total\n");
+    for point in &laporan.harian {
+        csv.push_str(&format!("{},{}\n", point.tanggal.format("%Y-%m-%d"), point.total));
+    }
+
+    csv.push_str("\nBudget vs aktual\nkategori_nama,amount,actual,variance,status\n");
+    for item in &laporan.budget_vs_aktual {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            csv_field(&item.kategori_nama),
+            item.amount.map(|amount| amount.to_string()).unwrap_or_default(),
+            item.actual,
+            item.variance.map(|variance| variance.to_string()).unwrap_or_default(),
+            item.status
+        ));
+    }
+
+    csv
+}
+
+// Total pengeluaran user dalam rentang tanggal (inklusif)
+async fn sum_pengeluaran(db: &Database, user_id: Uuid, start: NaiveDate, end: NaiveDate) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COALESCE(SUM(jumlah), 0) FROM transaksi WHERE user_id = $1 AND tipe = 'pengeluaran' AND tanggal >= $2 AND tanggal <= $3"
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .fetch_one(db)
+    .await
+}
+
+// Total pemasukan user dalam rentang tanggal (inklusif)
+async fn sum_pemasukan(db: &Database, user_id: Uuid, start: NaiveDate, end: NaiveDate) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COALESCE(SUM(jumlah), 0) FROM transaksi WHERE user_id = $1 AND tipe = 'pemasukan' AND tanggal >= $2 AND tanggal <= $3"
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .fetch_one(db)
+    .await
+}
+
+// Jumlah transaksi user (semua tipe) dalam rentang tanggal (inklusif)
+async fn count_transaksi(db: &Database, user_id: Uuid, start: NaiveDate, end: NaiveDate) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM transaksi WHERE user_id = $1 AND tanggal >= $2 AND tanggal <= $3"
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .fetch_one(db)
+    .await
+}
+
+// Total pengeluaran per hari dalam rentang; hari tanpa pengeluaran tidak ada di hasil
+async fn fetch_pengeluaran_harian(db: &Database, user_id: Uuid, start: NaiveDate, end: NaiveDate) -> Result<Vec<HeatmapPoint>, sqlx::Error> {
+    sqlx::query_as::<_, HeatmapPoint>(
+        r#"
+        SELECT
+            tanggal,
+            SUM(jumlah)::BIGINT as total
+        FROM transaksi
+        WHERE user_id = $1 AND tipe = 'pengeluaran' AND tanggal >= $2 AND tanggal <= $3
+        GROUP BY tanggal
+        ORDER BY tanggal ASC
+        "#
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(db)
+    .await
+}
+
+// Pengeluaran terbesar dalam rentang, urut jumlah menurun
+async fn fetch_transaksi_terbesar(
+    db: &Database,
+    user_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+    limit: i64,
+) -> Result<Vec<TransaksiTerbesar>, sqlx::Error> {
+    sqlx::query_as::<_, TransaksiTerbesar>(
+        r#"
+        SELECT
+            t.id,
+            t.tanggal,
+            t.kategori_id,
+            c.nama as kategori_nama,
+            t.deskripsi,
+            t.jumlah
+        FROM transaksi t
+        JOIN categories c ON t.kategori_id = c.id
+        WHERE t.user_id = $1 AND t.tipe = 'pengeluaran' AND t.tanggal >= $2 AND t.tanggal <= $3
+        ORDER BY t.jumlah DESC, t.tanggal DESC, t.id DESC
+        LIMIT $4
+        "#
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .bind(limit)
+    .fetch_all(db)
+    .await
+}
+
+// Tanggal pertama bulan dari parameter `month` (YYYY-MM); default bulan berjalan
+fn parse_month_param(month: &Option<String>) -> Result<NaiveDate, (StatusCode, Json<Value>)> {
+    match month {
+        Some(month) => NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").map_err(|_| {
+            (
                 StatusCode::BAD_REQUEST,
                 Json(json!({
                     "status": "error",
-                    "message": "Invalid user ID format."
+                    "message": "Format bulan tidak valid. Gunakan format YYYY-MM."
                 }))
-            ));
+            )
+        }),
+        None => {
+            let today = Local::now().naive_local().date();
+            Ok(today.with_day(1).unwrap_or(today))
         }
+    }
+}
+
+// Pengeluaran per kategori (semua kategori yang terlihat user), terbesar lebih dulu.
+// Persentase dihitung terhadap total pengeluaran dan terhadap total pemasukan periode yang sama
+async fn fetch_pengeluaran_per_kategori(
+    db: &Database,
+    user_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+    total_pengeluaran: i64,
+    total_pemasukan: i64,
+) -> Result<Vec<PengeluaranKategori>, sqlx::Error> {
+    sqlx::query_as::<_, PengeluaranKategori>(
+        r#"
+        SELECT 
+            c.id as kategori_id,
+            c.nama as kategori_nama,
+            COALESCE(SUM(t.jumlah), 0) as total_pengeluaran,
+            CASE 
+                WHEN $4 > 0 THEN CAST(ROUND((COALESCE(SUM(t.jumlah), 0) * 100.0 / $4), 2) AS FLOAT8)
+                ELSE 0.0
+            END as persentase,
+            CASE
+                WHEN $5 > 0 THEN CAST(ROUND((COALESCE(SUM(t.jumlah), 0) * 100.0 / $5), 2) AS FLOAT8)
+                ELSE NULL
+            END as percentage_of_income
+        FROM categories c
+        LEFT JOIN transaksi t ON c.id = t.kategori_id 
+            AND t.user_id = $1 
+            AND t.tipe = 'pengeluaran'
+            AND t.tanggal >= $2 
+            AND t.tanggal <= $3
+        WHERE c.user_id IS NULL OR c.user_id = $1
+        GROUP BY c.id, c.nama
+        ORDER BY total_pengeluaran DESC, c.nama ASC
+        "#
+    )
+    .bind(user_id)
+    .bind(start)
+    .bind(end)
+    .bind(total_pengeluaran)
+    .bind(total_pemasukan)
+    .fetch_all(db)
+    .await
+}
+
+// Transaksi terbaru user (pengeluaran maupun pemasukan)
+async fn fetch_transaksi_terakhir(db: &Database, user_id: Uuid, limit: i64) -> Result<Vec<TransaksiTerakhir>, sqlx::Error> {
+    sqlx::query_as::<_, TransaksiTerakhir>(
+        r#"
+        SELECT 
+            t.id,
+            t.deskripsi,
+            t.jumlah,
+            t.tanggal::text as tanggal,
+            COALESCE(c.nama, 'Tanpa Kategori') as kategori_nama,
+            t.created_at,
+            t.updated_at
+        FROM transaksi t
+        LEFT JOIN categories c ON t.kategori_id = c.id
+        WHERE t.user_id = $1
+        ORDER BY t.tanggal DESC, t.created_at DESC
+        LIMIT $2
+        "#
+    )
+    .bind(user_id)
+    .bind(limit)
+    .fetch_all(db)
+    .await
+}
+
+// Awal minggu kalender yang memuat `date`, sesuai preferensi week_start user (default Senin)
+pub fn calendar_week_start(date: NaiveDate, week_start: &str) -> NaiveDate {
+    let days_since_start = if week_start == WEEK_START_SUNDAY {
+        date.weekday().num_days_from_sunday()
+    } else {
+        date.weekday().num_days_from_monday()
     };
+    date - chrono::Duration::days(days_since_start as i64)
+}
 
+// Rentang tanggal statistik dari parameter filter/year/month, lalu ditimpa start_date/end_date jika valid
+async fn resolve_statistik_range(
+    db: &Database,
+    user_uuid: Uuid,
+    query: &StatistikQuery,
+) -> Result<(NaiveDate, NaiveDate), (StatusCode, Json<Value>)> {
     // Determine date range based on filter
     let (start_date, end_date) = match query.filter.as_deref() {
         Some("daily") => {
@@ -41,45 +383,32 @@ pub async fn get_user_statistik(
             let start = today - chrono::Duration::days(7);
             (start, today)
         },
-        Some("monthly") => {
-            // Use custom year and month if provided, otherwise use current month
-            let current_date = Local::now().naive_local().date();
-            let target_year = query.year.unwrap_or(current_date.year());
-            let target_month = query.month.unwrap_or(current_date.month());
-            
-            let start = NaiveDate::from_ymd_opt(target_year, target_month, 1).unwrap();
-            let end = if target_year == current_date.year() && target_month == current_date.month() {
-                // If it's current month, use today as end date
-                current_date
-            } else {
-                // If it's past month, use last day of that month
-                let next_month = if target_month == 12 { 1 } else { target_month + 1 };
-                let next_year = if target_month == 12 { target_year + 1 } else { target_year };
-                NaiveDate::from_ymd_opt(next_year, next_month, 1).unwrap() - chrono::Duration::days(1)
-            };
-            (start, end)
+        Some("calendar_week") => {
+            // Minggu kalender berjalan (awal minggu s/d hari ini) sesuai preferensi week_start
+            let today = Local::now().naive_local().date();
+            let preferences = fetch_preferences(db, user_uuid).await.unwrap_or_default();
+            (calendar_week_start(today, &preferences.week_start), today)
         },
         _ => {
-            // Default: current month, but can be overridden by year/month params
+            // "monthly" dan default: bulan berjalan, atau bulan dari parameter year/month
             let current_date = Local::now().naive_local().date();
             let target_year = query.year.unwrap_or(current_date.year());
             let target_month = query.month.unwrap_or(current_date.month());
-            
-            let start = NaiveDate::from_ymd_opt(target_year, target_month, 1).unwrap();
+
+            let (start, end_of_month) = month_bounds(target_year, target_month)?;
             let end = if target_year == current_date.year() && target_month == current_date.month() {
+                // If it's current month, use today as end date
                 current_date
             } else {
-                let next_month = if target_month == 12 { 1 } else { target_month + 1 };
-                let next_year = if target_month == 12 { target_year + 1 } else { target_year };
-                NaiveDate::from_ymd_opt(next_year, next_month, 1).unwrap() - chrono::Duration::days(1)
+                end_of_month
             };
             (start, end)
         }
     };
 
     // Override with custom dates if provided
-    let final_start_date = if let Some(custom_start) = query.start_date {
-        match NaiveDate::parse_from_str(&custom_start, "%Y-%m-%d") {
+    let final_start_date = if let Some(custom_start) = query.start_date.as_deref() {
+        match NaiveDate::parse_from_str(custom_start, "%Y-%m-%d") {
             Ok(date) => date,
             Err(_) => start_date,
         }
@@ -87,8 +416,8 @@ pub async fn get_user_statistik(
         start_date
     };
 
-    let final_end_date = if let Some(custom_end) = query.end_date {
-        match NaiveDate::parse_from_str(&custom_end, "%Y-%m-%d") {
+    let final_end_date = if let Some(custom_end) = query.end_date.as_deref() {
+        match NaiveDate::parse_from_str(custom_end, "%Y-%m-%d") {
             Ok(date) => date,
             Err(_) => end_date,
         }
@@ -96,14 +425,44 @@ pub async fn get_user_statistik(
         end_date
     };
 
+    Ok((final_start_date, final_end_date))
+}
+
+// Get user statistics
+pub async fn get_user_statistik(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    Query(query): Query<StatistikQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let (final_start_date, final_end_date) = resolve_statistik_range(&db, user_uuid, &query).await?;
+
+    if query.top == Some(0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "Parameter top minimal 1."
+            }))
+        ));
+    }
+
     // Get total pengeluaran for percentage calculation
-    let total_pengeluaran: i64 = sqlx::query_scalar(
-        "SELECT COALESCE(SUM(jumlah), 0) FROM transaksi WHERE user_id = $1 AND tanggal >= $2 AND tanggal <= $3"
-    )
-    .bind(user_uuid)
-    .bind(final_start_date)
-    .bind(final_end_date)
-    .fetch_one(&db)
+    let total_pengeluaran = sum_pengeluaran(&db, user_uuid, final_start_date, final_end_date)
     .await
     .map_err(|err| {
         eprintln!("Database error: {:?}", err);
@@ -117,29 +476,20 @@ pub async fn get_user_statistik(
     })?;
 
     // Get pengeluaran per kategori - UPDATED: Tampilkan semua kategori yang terdaftar
-    let pengeluaran_per_kategori: Vec<PengeluaranKategori> = sqlx::query_as::<_, PengeluaranKategori>(
-        r#"
-        SELECT 
-            c.nama as kategori_nama,
-            COALESCE(SUM(t.jumlah), 0) as total_pengeluaran,
-            CASE 
-                WHEN $4 > 0 THEN CAST(ROUND((COALESCE(SUM(t.jumlah), 0) * 100.0 / $4), 2) AS FLOAT8)
-                ELSE 0.0
-            END as persentase
-        FROM categories c
-        LEFT JOIN transaksi t ON c.id = t.kategori_id 
-            AND t.user_id = $1 
-            AND t.tanggal >= $2 
-            AND t.tanggal <= $3
-        GROUP BY c.id, c.nama
-        ORDER BY total_pengeluaran DESC, c.nama ASC
-        "#
-    )
-    .bind(user_uuid)
-    .bind(final_start_date)
-    .bind(final_end_date)
-    .bind(total_pengeluaran)
-    .fetch_all(&db)
+    let total_pemasukan = sum_pemasukan(&db, user_uuid, final_start_date, final_end_date)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    let pengeluaran_per_kategori = fetch_pengeluaran_per_kategori(&db, user_uuid, final_start_date, final_end_date, total_pengeluaran, total_pemasukan)
     .await
     .map_err(|err| {
         eprintln!("Database error: {:?}", err);
@@ -152,14 +502,29 @@ pub async fn get_user_statistik(
         )
     })?;
 
+    let pengeluaran_per_kategori = match query.top {
+        Some(top) => collapse_kategori_lainnya(pengeluaran_per_kategori, top, total_pengeluaran, total_pemasukan),
+        None => pengeluaran_per_kategori,
+    };
+
+    // CSV hanya berisi breakdown per kategori, jadi ringkasan tidak perlu dihitung
+    if wants_csv(query.format.as_deref(), &headers) {
+        let filename = format!(
+            "attachment; filename=\"statistik_{}_{}.csv\"",
+            final_start_date.format("%Y-%m-%d"),
+            final_end_date.format("%Y-%m-%d")
+        );
+        return Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (header::CONTENT_DISPOSITION, filename),
+            ],
+            kategori_csv(&pengeluaran_per_kategori),
+        ).into_response());
+    }
+
     // Get total transaksi count
-    let total_transaksi: i64 = sqlx::query_scalar(
-        "SELECT COUNT(*) FROM transaksi WHERE user_id = $1 AND tanggal >= $2 AND tanggal <= $3"
-    )
-    .bind(user_uuid)
-    .bind(final_start_date)
-    .bind(final_end_date)
-    .fetch_one(&db)
+    let total_transaksi = count_transaksi(&db, user_uuid, final_start_date, final_end_date)
     .await
     .map_err(|err| {
         eprintln!("Database error: {:?}", err);
@@ -182,6 +547,7 @@ pub async fn get_user_statistik(
 
     let ringkasan = RingkasanPengeluaran {
         total_pengeluaran,
+        total_pemasukan,
         rata_rata_harian,
         total_transaksi,
         tertinggi_hari_ini: None,
@@ -195,57 +561,23 @@ pub async fn get_user_statistik(
         ringkasan,
     };
 
-    Ok(Json(json!({
-        "status": "success",
-        "data": statistik,
-        "filter_applied": {
-            "start_date": final_start_date.format("%Y-%m-%d").to_string(),
-            "end_date": final_end_date.format("%Y-%m-%d").to_string(),
-            "filter_type": query.filter.unwrap_or_else(|| "monthly".to_string()),
-            "year": query.year,
-            "month": query.month
-        }
-    })))
-}
-
-// Get global spending range statistics (for the donut chart)
-pub async fn get_spending_ranges() -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // This is demo data for the spending ranges
-    // In real implementation, you would calculate this from all users' data
-    let spending_ranges = vec![
-        PengeluaranRange {
-            range_label: "$ 0 - $ 20,000".to_string(),
-            jumlah_user: 20,
-            persentase: 20.0,
-        },
-        PengeluaranRange {
-            range_label: "$ 20,000 - $ 30,000".to_string(),
-            jumlah_user: 25,
-            persentase: 25.0,
-        },
-        PengeluaranRange {
-            range_label: "$ 30,000 - $ 60,000".to_string(),
-            jumlah_user: 40,
-            persentase: 40.0,
-        },
-        PengeluaranRange {
-            range_label: "more than $ 60,000".to_string(),
-            jumlah_user: 15,
-            persentase: 15.0,
-        },
-    ];
-
-    Ok(Json(json!({
-        "status": "success",
-        "data": spending_ranges
-    })))
+    Ok(Json(ApiResponse::success(statistik).with_meta("filter_applied", json!({
+        "start_date": final_start_date.format("%Y-%m-%d").to_string(),
+        "end_date": final_end_date.format("%Y-%m-%d").to_string(),
+        "filter_type": query.filter.unwrap_or_else(|| "monthly".to_string()),
+        "year": query.year,
+        "month": query.month,
+        "top": query.top
+    }))).into_response())
 }
 
-// Get user monthly spending for range categorization
-pub async fn get_user_monthly_spending(
+// Get statistik satu kategori (total, jumlah transaksi, rata-rata, breakdown harian).
+// Rentang tanggal mengikuti parameter yang sama dengan get_user_statistik; `format` dan `top` diabaikan
+pub async fn get_kategori_statistik(
     State(db): State<Database>,
-    Path(user_id): Path<String>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    Path((user_id, kategori_id)): Path<(String, i32)>,
+    Query(query): Query<StatistikQuery>,
+) -> Result<Json<ApiResponse<KategoriStatistikResponse>>, (StatusCode, Json<Value>)> {
     // Parse user_id as UUID
     let user_uuid = match Uuid::parse_str(&user_id) {
         Ok(uuid) => uuid,
@@ -260,17 +592,67 @@ pub async fn get_user_monthly_spending(
         }
     };
 
-    // Get current month spending
-    let today = Local::now().naive_local().date();
-    let start_of_month = NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap();
-    
-    let monthly_spending: i64 = sqlx::query_scalar(
-        "SELECT COALESCE(SUM(jumlah), 0) FROM transaksi WHERE user_id = $1 AND tanggal >= $2 AND tanggal <= $3"
+    let kategori = kategori_access(&db, kategori_id, user_uuid)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    match kategori {
+        KategoriAccess::Allowed => {}
+        KategoriAccess::NotFound => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "error",
+                    "message": "Kategori tidak ditemukan."
+                }))
+            ));
+        }
+        KategoriAccess::Forbidden => return Err(forbidden_kategori_response()),
+    }
+
+    let (start_date, end_date) = resolve_statistik_range(&db, user_uuid, &query).await?;
+
+    let kategori_nama: String = sqlx::query_scalar("SELECT nama FROM categories WHERE id = $1")
+        .bind(kategori_id)
+        .fetch_one(&db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    let harian = sqlx::query_as::<_, KategoriHarianPoint>(
+        r#"
+        SELECT
+            tanggal,
+            SUM(jumlah)::BIGINT as total,
+            COUNT(*) as jumlah_transaksi
+        FROM transaksi
+        WHERE user_id = $1 AND kategori_id = $2 AND tipe = 'pengeluaran' AND tanggal >= $3 AND tanggal <= $4
+        GROUP BY tanggal
+        ORDER BY tanggal ASC
+        "#
     )
     .bind(user_uuid)
-    .bind(start_of_month)
-    .bind(today)
-    .fetch_one(&db)
+    .bind(kategori_id)
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(&db)
     .await
     .map_err(|err| {
         eprintln!("Database error: {:?}", err);
@@ -283,32 +665,200 @@ pub async fn get_user_monthly_spending(
         )
     })?;
 
-    // Categorize spending range
-    let spending_category = if monthly_spending <= 20000 {
-        "$ 0 - $ 20,000"
-    } else if monthly_spending <= 30000 {
-        "$ 20,000 - $ 30,000"
-    } else if monthly_spending <= 60000 {
-        "$ 30,000 - $ 60,000"
+    let total_pengeluaran: i64 = harian.iter().map(|point| point.total).sum();
+    let total_transaksi: i64 = harian.iter().map(|point| point.jumlah_transaksi).sum();
+    let rata_rata_transaksi = if total_transaksi > 0 {
+        total_pengeluaran as f64 / total_transaksi as f64
     } else {
-        "more than $ 60,000"
+        0.0
     };
+    let days_diff = (end_date - start_date).num_days() + 1;
+    let rata_rata_harian = if days_diff > 0 {
+        total_pengeluaran as f64 / days_diff as f64
+    } else {
+        0.0
+    };
+
+    let statistik = KategoriStatistikResponse {
+        kategori_id,
+        kategori_nama,
+        total_pengeluaran,
+        total_transaksi,
+        rata_rata_transaksi,
+        rata_rata_harian,
+        harian,
+    };
+
+    Ok(Json(ApiResponse::success(statistik).with_meta("filter_applied", json!({
+        "start_date": start_date.format("%Y-%m-%d").to_string(),
+        "end_date": end_date.format("%Y-%m-%d").to_string(),
+        "filter_type": query.filter.unwrap_or_else(|| "monthly".to_string()),
+        "year": query.year,
+        "month": query.month
+    }))))
+}
+
+// Response spending ranges beserta info cache; `cached_at` None berarti baru saja dihitung
+fn spending_ranges_response(data: Value, cached_at: Option<Instant>) -> ApiResponse<Value> {
+    ApiResponse::success(data).with_meta("cache", json!({
+        "hit": cached_at.is_some(),
+        "age_secs": cached_at.map_or(0, |cached_at| cached_at.elapsed().as_secs())
+    }))
+}
+
+// Get distribusi pengeluaran bulan berjalan seluruh user per rentang.
+// Hasilnya di-cache selama TTL karena memerlukan scan global; admin bisa memaksa hitung ulang dengan `?refresh=true`
+pub async fn get_spending_ranges(
+    State(db): State<Database>,
+    State(cache): State<SpendingRangesCache>,
+    admin: Result<AdminUser, (StatusCode, Json<Value>)>,
+    Query(query): Query<SpendingRangesQuery>,
+) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<Value>)> {
+    let refresh = query.refresh.unwrap_or(false);
+    if refresh {
+        admin?;
+    }
+
+    let ttl = Duration::from_secs(
+        env::var("SPENDING_RANGES_CACHE_SECS")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_SPENDING_RANGES_CACHE_SECS),
+    );
 
-    Ok(Json(json!({
-        "status": "success",
-        "data": {
-            "monthly_spending": monthly_spending,
-            "spending_category": spending_category,
-            "month": today.format("%Y-%m").to_string()
+    if !refresh {
+        if let Some((computed_at, data)) = cache.0.read().await.as_ref() {
+            if computed_at.elapsed() < ttl {
+                return Ok(Json(spending_ranges_response(data.clone(), Some(*computed_at))));
+            }
         }
-    })))
+    }
+
+    // Tahan write lock selama menghitung agar request bersamaan tidak ikut memicu scan yang sama
+    let mut cached = cache.0.write().await;
+    if let Some((computed_at, data)) = cached.as_ref().filter(|(computed_at, _)| !refresh && computed_at.elapsed() < ttl) {
+        return Ok(Json(spending_ranges_response(data.clone(), Some(*computed_at))));
+    }
+
+    let today = Local::now().naive_local().date();
+    let start_of_month = today.with_day(1).unwrap_or(today);
+
+    // Jumlah user per rentang berdasarkan total pengeluaran bulan berjalan (user tanpa pengeluaran masuk rentang pertama)
+    let counts = sqlx::query_as::<_, (i64, i64, i64, i64)>(
+        r#"
+        WITH monthly AS (
+            SELECT u.id, COALESCE(SUM(t.jumlah), 0)::BIGINT as total
+            FROM users u
+            LEFT JOIN transaksi t ON t.user_id = u.id AND t.tipe = 'pengeluaran'
+                AND t.tanggal >= $1 AND t.tanggal <= $2
+            GROUP BY u.id
+        )
+        SELECT
+            COUNT(*) FILTER (WHERE total <= $3),
+            COUNT(*) FILTER (WHERE total > $3 AND total <= $4),
+            COUNT(*) FILTER (WHERE total > $4 AND total <= $5),
+            COUNT(*) FILTER (WHERE total > $5)
+        FROM monthly
+        "#
+    )
+    .bind(start_of_month)
+    .bind(today)
+    .bind(SPENDING_RANGES[0].1)
+    .bind(SPENDING_RANGES[1].1)
+    .bind(SPENDING_RANGES[2].1)
+    .fetch_one(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    let counts = [counts.0, counts.1, counts.2, counts.3];
+    let total_user: i64 = counts.iter().sum();
+    let spending_ranges: Vec<PengeluaranRange> = SPENDING_RANGES
+        .iter()
+        .zip(counts)
+        .map(|((label, _), jumlah_user)| PengeluaranRange {
+            range_label: label.to_string(),
+            jumlah_user,
+            persentase: if total_user > 0 {
+                (jumlah_user as f64 * 100.0 / total_user as f64 * 100.0).round() / 100.0
+            } else {
+                0.0
+            },
+        })
+        .collect();
+
+    let data = json!(spending_ranges);
+    *cached = Some((Instant::now(), data.clone()));
+
+    Ok(Json(spending_ranges_response(data, None)))
+}
+
+// Get user monthly spending for range categorization
+pub async fn get_user_monthly_spending(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    // Get current month spending
+    let today = Local::now().naive_local().date();
+    let start_of_month = today.with_day(1).unwrap_or(today);
+    
+    let monthly_spending: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(jumlah), 0) FROM transaksi WHERE user_id = $1 AND tipe = 'pengeluaran' AND tanggal >= $2 AND tanggal <= $3"
+    )
+    .bind(user_uuid)
+    .bind(start_of_month)
+    .bind(today)
+    .fetch_one(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    // Categorize spending range
+    let spending_category = spending_range_label(monthly_spending);
+
+    Ok(Json(ApiResponse::success(json!({
+        "monthly_spending": monthly_spending,
+        "spending_category": spending_category,
+        "month": today.format("%Y-%m").to_string()
+    }))))
 }
 
 // ✅ FIXED: Get comprehensive dashboard data dengan debugging dan fallback user
 pub async fn get_dashboard_data(
     State(db): State<Database>,
     Path(user_id): Path<String>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    Query(query): Query<DashboardQuery>,
+) -> Result<Json<ApiResponse<DashboardResponse>>, (StatusCode, Json<Value>)> {
     // Parse user_id as UUID
     let user_uuid = match Uuid::parse_str(&user_id) {
         Ok(uuid) => uuid,
@@ -326,7 +876,19 @@ pub async fn get_dashboard_data(
     println!("🔍 Dashboard API called for user: {}", user_id);
 
     let today = Local::now().naive_local().date();
-    let start_of_month = NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap();
+    let start_of_month = today.with_day(1).unwrap_or(today);
+
+    // Grafik mingguan bisa digeser ke tanggal lampau untuk melihat pola minggu sebelumnya
+    let chart_end = parse_optional_date(&query.end_date)?.unwrap_or(today);
+    if chart_end > today {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "end_date tidak boleh setelah hari ini."
+            }))
+        ));
+    }
 
     println!("📅 Date range: {} to {}", start_of_month, today);
 
@@ -354,29 +916,36 @@ pub async fn get_dashboard_data(
     };
 
     // Get daily total
-    let total_hari_ini: i64 = sqlx::query_scalar(
-        "SELECT COALESCE(SUM(jumlah), 0) FROM transaksi WHERE user_id = $1 AND tanggal = $2"
-    )
-    .bind(actual_user_uuid)
-    .bind(today)
-    .fetch_one(&db)
-    .await
-    .unwrap_or(0);
+    let total_hari_ini = sum_pengeluaran(&db, actual_user_uuid, today, today)
+        .await
+        .unwrap_or(0);
 
     // Get monthly total
-    let total_bulan_ini: i64 = sqlx::query_scalar(
-        "SELECT COALESCE(SUM(jumlah), 0) FROM transaksi WHERE user_id = $1 AND tanggal >= $2 AND tanggal <= $3"
-    )
-    .bind(actual_user_uuid)
-    .bind(start_of_month)
-    .bind(today)
-    .fetch_one(&db)
-    .await
-    .unwrap_or(0);
+    let total_bulan_ini = sum_pengeluaran(&db, actual_user_uuid, start_of_month, today)
+        .await
+        .unwrap_or(0);
+
+    // Get last month's total for the same period (tanggal 1 s/d tanggal yang sama bulan lalu;
+    // checked_sub_months membulatkan ke akhir bulan jika tanggalnya tidak ada, mis. 31 -> 30)
+    let start_of_last_month = start_of_month - chrono::Months::new(1);
+    let same_day_last_month = today
+        .checked_sub_months(chrono::Months::new(1))
+        .unwrap_or(start_of_month - chrono::Duration::days(1));
+    let total_bulan_lalu = sum_pengeluaran(&db, actual_user_uuid, start_of_last_month, same_day_last_month)
+        .await
+        .unwrap_or(0);
+
+    // Persentase perubahan vs bulan lalu; null jika bulan lalu 0 (tidak bisa dibagi)
+    let perubahan_persen = if total_bulan_lalu > 0 {
+        let persen = (total_bulan_ini - total_bulan_lalu) as f64 * 100.0 / total_bulan_lalu as f64;
+        Some((persen * 100.0).round() / 100.0)
+    } else {
+        None
+    };
 
     // ✅ FIXED: Get highest daily amount (individual transaction) dengan error handling
     let tertinggi_hari_ini: i64 = match sqlx::query_scalar::<_, Option<i32>>(
-        "SELECT MAX(jumlah) FROM transaksi WHERE user_id = $1 AND tanggal = $2"
+        "SELECT MAX(jumlah) FROM transaksi WHERE user_id = $1 AND tipe = 'pengeluaran' AND tanggal = $2"
     )
     .bind(actual_user_uuid)
     .bind(today)
@@ -392,7 +961,7 @@ pub async fn get_dashboard_data(
 
     // ✅ FIXED: Get highest monthly amount (individual transaction) dengan error handling
     let tertinggi_bulan_ini: i64 = match sqlx::query_scalar::<_, Option<i32>>(
-        "SELECT MAX(jumlah) FROM transaksi WHERE user_id = $1 AND tanggal >= $2 AND tanggal <= $3"
+        "SELECT MAX(jumlah) FROM transaksi WHERE user_id = $1 AND tipe = 'pengeluaran' AND tanggal >= $2 AND tanggal <= $3"
     )
     .bind(actual_user_uuid)
     .bind(start_of_month)
@@ -409,7 +978,7 @@ pub async fn get_dashboard_data(
 
     // ✅ FIXED: Get lowest daily amount (only non-zero values) dengan error handling
     let terendah_hari_ini: i64 = match sqlx::query_scalar::<_, Option<i32>>(
-        "SELECT MIN(jumlah) FROM transaksi WHERE user_id = $1 AND tanggal = $2 AND jumlah > 0"
+        "SELECT MIN(jumlah) FROM transaksi WHERE user_id = $1 AND tipe = 'pengeluaran' AND tanggal = $2 AND jumlah > 0"
     )
     .bind(actual_user_uuid)
     .bind(today)
@@ -425,7 +994,7 @@ pub async fn get_dashboard_data(
 
     // ✅ FIXED: Get lowest monthly spending (only non-zero values) dengan error handling
     let terendah_bulan_ini: i64 = match sqlx::query_scalar::<_, Option<i32>>(
-        "SELECT MIN(jumlah) FROM transaksi WHERE user_id = $1 AND tanggal >= $2 AND tanggal <= $3 AND jumlah > 0"
+        "SELECT MIN(jumlah) FROM transaksi WHERE user_id = $1 AND tipe = 'pengeluaran' AND tanggal >= $2 AND tanggal <= $3 AND jumlah > 0"
     )
     .bind(actual_user_uuid)
     .bind(start_of_month)
@@ -444,54 +1013,47 @@ pub async fn get_dashboard_data(
     println!("📈 Highest - Daily: {}, Monthly: {}", tertinggi_hari_ini, tertinggi_bulan_ini);
     println!("📉 Lowest - Daily: {}, Monthly: {}", terendah_hari_ini, terendah_bulan_ini);
 
-    // Get weekly chart data (last 7 days) dengan data yang lebih akurat
-    let mut pengeluaran_mingguan = Vec::new();
-    for i in 0..7 {
-        let current_day = today - chrono::Duration::days(6 - i);
-        let day_total: i64 = sqlx::query_scalar(
-            "SELECT COALESCE(SUM(jumlah), 0) FROM transaksi WHERE user_id = $1 AND tanggal = $2"
-        )
-        .bind(actual_user_uuid)
-        .bind(current_day)
-        .fetch_one(&db)
+    // Label hari dan awal minggu mengikuti preferensi user yang meminta (bukan user fallback)
+    let preferences = fetch_preferences(&db, user_uuid).await.unwrap_or_default();
+
+    // Total minggu kalender berjalan (awal minggu sesuai preferensi week_start s/d hari ini)
+    let start_of_week = calendar_week_start(today, &preferences.week_start);
+    let total_minggu_ini = sum_pengeluaran(&db, actual_user_uuid, start_of_week, today)
         .await
         .unwrap_or(0);
 
-        let day_name = match current_day.weekday() {
-            chrono::Weekday::Mon => "Sen",
-            chrono::Weekday::Tue => "Sel",
-            chrono::Weekday::Wed => "Rab",
-            chrono::Weekday::Thu => "Kam",
-            chrono::Weekday::Fri => "Jum",
-            chrono::Weekday::Sat => "Sab",
-            chrono::Weekday::Sun => "Min",
-        };
+    // Get weekly chart data (7 hari berakhir di chart_end) dalam satu query, hari tanpa transaksi diisi 0
+    let week_start = chart_end - chrono::Duration::days(6);
+    let daily_totals: HashMap<NaiveDate, i64> = sqlx::query_as::<_, (NaiveDate, i64)>(
+        "SELECT tanggal, COALESCE(SUM(jumlah), 0)::BIGINT FROM transaksi WHERE user_id = $1 AND tipe = 'pengeluaran' AND tanggal BETWEEN $2 AND $3 GROUP BY tanggal"
+    )
+    .bind(actual_user_uuid)
+    .bind(week_start)
+    .bind(chart_end)
+    .fetch_all(&db)
+    .await
+    .unwrap_or_else(|e| {
+        println!("❌ Error getting pengeluaran_mingguan: {:?}", e);
+        Vec::new()
+    })
+    .into_iter()
+    .collect();
+
+    let mut pengeluaran_mingguan = Vec::new();
+    for i in 0..7 {
+        let current_day = week_start + chrono::Duration::days(i);
+        let day_total = daily_totals.get(&current_day).copied().unwrap_or(0);
 
         pengeluaran_mingguan.push(ChartDataPoint {
-            hari: day_name.to_string(),
+            hari: weekday_label(current_day.weekday(), &preferences.locale).to_string(),
             jumlah: day_total,
         });
     }
 
-    // Get last 10 transactions (lebih sedikit untuk debugging)
-    let transaksi_terakhir: Vec<TransaksiTerakhir> = sqlx::query_as(
-        r#"
-        SELECT 
-            t.id,
-            t.deskripsi,
-            t.jumlah,
-            t.tanggal::text as tanggal,
-            COALESCE(c.nama, 'Tanpa Kategori') as kategori_nama
-        FROM transaksi t
-        LEFT JOIN categories c ON t.kategori_id = c.id
-        WHERE t.user_id = $1
-        ORDER BY t.tanggal DESC, t.created_at DESC
-        LIMIT 10
-        "#
-    )
-    .bind(actual_user_uuid)
-    .fetch_all(&db)
-    .await
+    // Get recent transactions (default 10, bisa diatur lewat recent_limit sesuai layout dashboard)
+    let recent_limit = query.recent_limit.unwrap_or(10).clamp(1, 50);
+    let transaksi_terakhir = fetch_transaksi_terakhir(&db, actual_user_uuid, recent_limit)
+        .await
     .unwrap_or_else(|err| {
         eprintln!("Error fetching transactions: {:?}", err);
         Vec::new()
@@ -501,6 +1063,9 @@ pub async fn get_dashboard_data(
 
     let dashboard_data = DashboardResponse {
         total_bulan_ini,
+        total_bulan_lalu,
+        perubahan_persen,
+        total_minggu_ini,
         total_hari_ini,
         tertinggi_bulan_ini,
         tertinggi_hari_ini,
@@ -512,21 +1077,1066 @@ pub async fn get_dashboard_data(
 
     println!("✅ Dashboard response prepared with {} transactions", dashboard_data.transaksi_terakhir.len());
 
-    Ok(Json(json!({
-        "status": "success",
-        "data": dashboard_data,
-        "debug": {
-            "requested_user": user_id,
-            "actual_user": actual_user_uuid.to_string(),
-            "user_switched": user_transaction_count == 0,
-            "date_range": format!("{} to {}", start_of_month, today),
-            "total_transactions": dashboard_data.transaksi_terakhir.len(),
-            "monthly_total": total_bulan_ini,
-            "daily_total": total_hari_ini,
-            "highest_monthly": tertinggi_bulan_ini,
-            "highest_daily": tertinggi_hari_ini,
-            "lowest_monthly": terendah_bulan_ini,
-            "lowest_daily": terendah_hari_ini
+    let debug = json!({
+        "requested_user": user_id,
+        "actual_user": actual_user_uuid.to_string(),
+        "user_switched": user_transaction_count == 0,
+        "date_range": format!("{} to {}", start_of_month, today),
+        "total_transactions": dashboard_data.transaksi_terakhir.len(),
+        "monthly_total": total_bulan_ini,
+        "daily_total": total_hari_ini,
+        "highest_monthly": tertinggi_bulan_ini,
+        "highest_daily": tertinggi_hari_ini,
+        "lowest_monthly": terendah_bulan_ini,
+        "lowest_daily": terendah_hari_ini
+    });
+
+    Ok(Json(ApiResponse::success(dashboard_data)
+        .with_meta("labels", json!({
+            "locale": preferences.locale,
+            "language": label_language(&preferences.locale)
+        }))
+        .with_meta("weekly_range", json!({
+            "start_date": week_start.format("%Y-%m-%d").to_string(),
+            "end_date": chart_end.format("%Y-%m-%d").to_string()
+        }))
+        .with_meta("debug", debug)))
+}
+
+// Get daily spending totals for a calendar heatmap.
+// Hanya hari yang memiliki pengeluaran yang dikembalikan; client harus
+// menganggap hari yang tidak ada di response sebagai 0.
+pub async fn get_spending_heatmap(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    Query(query): Query<HeatmapQuery>,
+) -> Result<Json<ApiResponse<Vec<HeatmapPoint>>>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
         }
-    })))
+    };
+
+    // Default: bulan berjalan
+    let today = Local::now().naive_local().date();
+    let target_year = query.year.unwrap_or(today.year());
+    let target_month = query.month.unwrap_or(today.month());
+
+    let (start_of_month, end_of_month) = month_bounds(target_year, target_month)?;
+
+    let heatmap = fetch_pengeluaran_harian(&db, user_uuid, start_of_month, end_of_month)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    Ok(Json(ApiResponse::success(heatmap).with_meta("filter_applied", json!({
+        "year": target_year,
+        "month": target_month,
+        "start_date": start_of_month.format("%Y-%m-%d").to_string(),
+        "end_date": end_of_month.format("%Y-%m-%d").to_string()
+    }))))
+}
+
+// Get today's spending against the user's daily limit
+pub async fn get_daily_status(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+) -> Result<Json<ApiResponse<Value>>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let daily_limit = sqlx::query_scalar::<_, Option<i32>>("SELECT daily_limit FROM users WHERE id = $1")
+        .bind(user_uuid)
+        .fetch_optional(&db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    let daily_limit = match daily_limit {
+        Some(limit) => limit,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "error",
+                    "message": "User tidak ditemukan."
+                }))
+            ));
+        }
+    };
+
+    let today = Local::now().naive_local().date();
+    let total_hari_ini: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(jumlah), 0) FROM transaksi WHERE user_id = $1 AND tipe = 'pengeluaran' AND tanggal = $2"
+    )
+    .bind(user_uuid)
+    .bind(today)
+    .fetch_one(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    // Tanpa batas harian, remaining bernilai null dan exceeded selalu false
+    let remaining = daily_limit.map(|limit| limit as i64 - total_hari_ini);
+    let exceeded = remaining.map(|remaining| remaining < 0).unwrap_or(false);
+
+    Ok(Json(ApiResponse::success(json!({
+        "tanggal": today.format("%Y-%m-%d").to_string(),
+        "total_hari_ini": total_hari_ini,
+        "daily_limit": daily_limit,
+        "remaining": remaining,
+        "exceeded": exceeded
+    }))))
+}
+
+// Panjang streak dari hari-hari berurutan (tanpa celah) yang memenuhi kondisi: (streak saat ini, terpanjang).
+// Streak saat ini adalah run yang berakhir di hari terakhir
+fn streak_lengths(days: impl Iterator<Item = bool>) -> (i64, i64) {
+    let (mut current, mut longest) = (0, 0);
+    for ok in days {
+        current = if ok { current + 1 } else { 0 };
+        longest = longest.max(current);
+    }
+    (current, longest)
+}
+
+// Get spending streaks: hari berturut-turut dengan pengeluaran tidak melebihi threshold (default daily_limit)
+// dan hari berturut-turut dengan transaksi. Dihitung dari total harian sejak transaksi pertama user;
+// hari tanpa transaksi di antaranya dianggap pengeluaran 0 (tetap hemat) tapi memutus streak pencatatan.
+// Hari ini yang belum ada transaksinya tidak memutus streak pencatatan karena harinya belum selesai
+pub async fn get_spending_streaks(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    Query(query): Query<StreakQuery>,
+) -> Result<Json<ApiResponse<StreakResponse>>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    if query.threshold.is_some_and(|threshold| threshold < 0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "Parameter threshold tidak boleh negatif."
+            }))
+        ));
+    }
+
+    let db_error = |err: sqlx::Error| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    };
+
+    let daily_limit = sqlx::query_scalar::<_, Option<i32>>("SELECT daily_limit FROM users WHERE id = $1")
+        .bind(user_uuid)
+        .fetch_optional(&db)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "error",
+                    "message": "User tidak ditemukan."
+                }))
+            )
+        })?;
+    let threshold = query.threshold.or(daily_limit.map(i64::from));
+
+    // Total pengeluaran dan jumlah transaksi (semua tipe) per hari; transaksi bertanggal masa depan diabaikan
+    let today = Local::now().naive_local().date();
+    let rows = sqlx::query_as::<_, (NaiveDate, i64, i64)>(
+        r#"
+        SELECT
+            tanggal,
+            COALESCE(SUM(jumlah) FILTER (WHERE tipe = 'pengeluaran'), 0)::BIGINT as total,
+            COUNT(*) as jumlah_transaksi
+        FROM transaksi
+        WHERE user_id = $1 AND tanggal <= $2
+        GROUP BY tanggal
+        ORDER BY tanggal ASC
+        "#
+    )
+    .bind(user_uuid)
+    .bind(today)
+    .fetch_all(&db)
+    .await
+    .map_err(db_error)?;
+
+    let start_date = rows.first().map(|(tanggal, _, _)| *tanggal);
+    let totals: HashMap<NaiveDate, i64> = rows.iter().map(|(tanggal, total, _)| (*tanggal, *total)).collect();
+    let days: Vec<NaiveDate> = start_date
+        .map(|start| start.iter_days().take_while(|day| *day <= today).collect())
+        .unwrap_or_default();
+
+    let (current_streak, longest_streak) = match threshold {
+        Some(threshold) => {
+            let (current, longest) = streak_lengths(days.iter().map(|day| totals.get(day).copied().unwrap_or(0) <= threshold));
+            (Some(current), Some(longest))
+        }
+        None => (None, None),
+    };
+
+    let logged_days = days.iter().filter(|day| **day < today || totals.contains_key(day));
+    let (current_logging_streak, _) = streak_lengths(logged_days.map(|day| totals.contains_key(day)));
+
+    Ok(Json(ApiResponse::success(StreakResponse {
+        threshold,
+        current_streak,
+        longest_streak,
+        current_logging_streak,
+        start_date,
+    })))
+}
+
+// Get descriptions (merchants) with the highest spending in a date range
+pub async fn get_top_descriptions(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    Query(query): Query<TopDeskripsiQuery>,
+) -> Result<Json<ApiResponse<Vec<TopDeskripsi>>>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let limit = query.limit.unwrap_or(10).clamp(1, 50);
+
+    // Default rentang: bulan berjalan
+    let today = Local::now().naive_local().date();
+    let start_date = parse_optional_date(&query.start_date)?
+        .unwrap_or_else(|| today.with_day(1).unwrap_or(today));
+    let end_date = match parse_optional_date(&query.end_date)? {
+        Some(date) => date,
+        None => {
+            let (next_year, next_month) = if today.month() == 12 { (today.year() + 1, 1) } else { (today.year(), today.month() + 1) };
+            NaiveDate::from_ymd_opt(next_year, next_month, 1)
+                .map(|date| date - chrono::Duration::days(1))
+                .unwrap_or(today)
+        }
+    };
+
+    if start_date > end_date {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "start_date tidak boleh setelah end_date."
+            }))
+        ));
+    }
+
+    // Deskripsi dikelompokkan setelah trim, spasi berlebih dirapikan, dan huruf kecil,
+    // sehingga "Kopi" dan "kopi " dihitung sebagai satu
+    let top_descriptions = sqlx::query_as::<_, TopDeskripsi>(
+        r#"
+        SELECT
+            MODE() WITHIN GROUP (ORDER BY REGEXP_REPLACE(TRIM(deskripsi), '\s+', ' ', 'g')) as deskripsi,
+            COUNT(*) as jumlah_transaksi,
+            SUM(jumlah)::BIGINT as total
+        FROM transaksi
+        WHERE user_id = $1 AND tipe = 'pengeluaran' AND tanggal >= $2 AND tanggal <= $3
+        GROUP BY LOWER(REGEXP_REPLACE(TRIM(deskripsi), '\s+', ' ', 'g'))
+        ORDER BY total DESC, jumlah_transaksi DESC
+        LIMIT $4
+        "#
+    )
+    .bind(user_uuid)
+    .bind(start_date)
+    .bind(end_date)
+    .bind(limit)
+    .fetch_all(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    Ok(Json(ApiResponse::success(top_descriptions).with_meta("filter_applied", json!({
+        "limit": limit,
+        "start_date": start_date.format("%Y-%m-%d").to_string(),
+        "end_date": end_date.format("%Y-%m-%d").to_string()
+    }))))
+}
+
+
+// Get the user's largest individual spending transactions (default: bulan berjalan)
+pub async fn get_largest_transaksi(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    Query(query): Query<TransaksiTerbesarQuery>,
+) -> Result<Json<ApiResponse<Vec<TransaksiTerbesar>>>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let limit = query.limit.unwrap_or(10).clamp(1, 50);
+
+    // Default rentang: bulan berjalan
+    let today = Local::now().naive_local().date();
+    let start_of_month = today.with_day(1).unwrap_or(today);
+    let start_date = parse_optional_date(&query.start_date)?.unwrap_or(start_of_month);
+    let end_date = parse_optional_date(&query.end_date)?.unwrap_or_else(|| period_end_for(start_of_month));
+
+    if start_date > end_date {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "start_date tidak boleh setelah end_date."
+            }))
+        ));
+    }
+
+    let largest = fetch_transaksi_terbesar(&db, user_uuid, start_date, end_date, limit)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    Ok(Json(ApiResponse::success(largest).with_meta("filter_applied", json!({
+        "limit": limit,
+        "start_date": start_date.format("%Y-%m-%d").to_string(),
+        "end_date": end_date.format("%Y-%m-%d").to_string()
+    }))))
+}
+
+// Get the user's categories with no transactions in a month (default: bulan berjalan)
+pub async fn get_unused_categories(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    Query(query): Query<KategoriTidakTerpakaiQuery>,
+) -> Result<Json<ApiResponse<Vec<KategoriTidakTerpakai>>>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    // Parse bulan (default: bulan berjalan)
+    let start_of_month = parse_month_param(&query.month)?;
+    let end_of_month = period_end_for(start_of_month);
+
+    // Kategori yang terlihat oleh user (sistem + milik sendiri) tanpa transaksi apa pun di bulan tersebut;
+    // terakhir_dipakai diambil dari seluruh riwayat agar "tidak pernah" bisa dibedakan dari "lama tidak dipakai"
+    let unused = sqlx::query_as::<_, KategoriTidakTerpakai>(
+        r#"
+        SELECT
+            c.id as kategori_id,
+            c.nama as kategori_nama,
+            c.user_id IS NULL as is_sistem,
+            (SELECT MAX(t.tanggal) FROM transaksi t WHERE t.user_id = $1 AND t.kategori_id = c.id) as terakhir_dipakai
+        FROM categories c
+        WHERE (c.user_id IS NULL OR c.user_id = $1)
+            AND NOT EXISTS (
+                SELECT 1 FROM transaksi t
+                WHERE t.user_id = $1 AND t.kategori_id = c.id AND t.tanggal >= $2 AND t.tanggal <= $3
+            )
+        ORDER BY terakhir_dipakai ASC NULLS FIRST, c.nama ASC
+        "#
+    )
+    .bind(user_uuid)
+    .bind(start_of_month)
+    .bind(end_of_month)
+    .fetch_all(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    Ok(Json(ApiResponse::success(unused).with_meta("filter_applied", json!({
+        "month": start_of_month.format("%Y-%m").to_string(),
+        "start_date": start_of_month.format("%Y-%m-%d").to_string(),
+        "end_date": end_of_month.format("%Y-%m-%d").to_string()
+    }))))
+}
+
+// Get monthly income vs expense totals for the last N months
+pub async fn get_cashflow(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    Query(query): Query<CashflowQuery>,
+) -> Result<Json<ApiResponse<Vec<CashflowPoint>>>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let months = query.months.unwrap_or(6).clamp(1, 24);
+
+    // Rentang: awal bulan (N-1) bulan lalu sampai bulan berjalan
+    let today = Local::now().naive_local().date();
+    let current_month = today.with_day(1).unwrap_or(today);
+    let start_month = current_month
+        .checked_sub_months(chrono::Months::new(months - 1))
+        .unwrap_or(current_month);
+
+    // generate_series memastikan bulan tanpa transaksi tetap muncul dengan nilai 0
+    let mut cashflow = sqlx::query_as::<_, CashflowPoint>(
+        r#"
+        WITH months AS (
+            SELECT generate_series($2::date, $3::date, INTERVAL '1 month')::date as bulan
+        ),
+        totals AS (
+            SELECT
+                m.bulan,
+                COALESCE(SUM(t.jumlah) FILTER (WHERE t.tipe = 'pemasukan'), 0)::BIGINT as pemasukan,
+                COALESCE(SUM(t.jumlah) FILTER (WHERE t.tipe = 'pengeluaran'), 0)::BIGINT as pengeluaran
+            FROM months m
+            LEFT JOIN transaksi t ON t.user_id = $1
+                AND t.tanggal >= m.bulan
+                AND t.tanggal < (m.bulan + INTERVAL '1 month')::date
+            GROUP BY m.bulan
+        )
+        SELECT
+            TO_CHAR(bulan, 'YYYY-MM') as bulan,
+            pemasukan,
+            pengeluaran,
+            pemasukan - pengeluaran as net
+        FROM totals
+        ORDER BY totals.bulan ASC
+        "#
+    )
+    .bind(user_uuid)
+    .bind(start_month)
+    .bind(current_month)
+    .fetch_all(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    let preferences = fetch_preferences(&db, user_uuid).await.unwrap_or_default();
+    for point in cashflow.iter_mut() {
+        // bulan selalu "YYYY-MM" dari TO_CHAR
+        if let Some((year, month)) = point.bulan.split_once('-') {
+            let month = month.parse().unwrap_or(1);
+            point.bulan_label = format!("{} {}", month_label(month, &preferences.locale), year);
+        }
+    }
+
+    let total_pemasukan: i64 = cashflow.iter().map(|point| point.pemasukan).sum();
+    let total_pengeluaran: i64 = cashflow.iter().map(|point| point.pengeluaran).sum();
+
+    Ok(Json(ApiResponse::success(cashflow)
+        .with_meta("summary", json!({
+            "total_pemasukan": total_pemasukan,
+            "total_pengeluaran": total_pengeluaran,
+            "net": total_pemasukan - total_pengeluaran
+        }))
+        .with_meta("labels", json!({
+            "locale": preferences.locale,
+            "language": label_language(&preferences.locale)
+        }))
+        .with_meta("filter_applied", json!({
+            "months": months,
+            "start_month": start_month.format("%Y-%m").to_string(),
+            "end_month": current_month.format("%Y-%m").to_string()
+        }))))
+}
+
+// Get average spending per day-of-month (1-31) over the last N months
+pub async fn get_day_of_month_spending(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    Query(query): Query<DayOfMonthQuery>,
+) -> Result<Json<ApiResponse<Vec<DayOfMonthPoint>>>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let months = query.months.unwrap_or(3).clamp(1, 24);
+
+    // Rentang: awal bulan (N-1) bulan lalu sampai hari ini
+    let today = Local::now().naive_local().date();
+    let current_month = today.with_day(1).unwrap_or(today);
+    let start_date = current_month
+        .checked_sub_months(chrono::Months::new(months - 1))
+        .unwrap_or(current_month);
+
+    // Setiap hari kalender dihitung sebagai satu sampel, jadi tanggal 29-31
+    // hanya dirata-rata dari bulan yang benar-benar memiliki tanggal tersebut
+    let rows = sqlx::query_as::<_, DayOfMonthPoint>(
+        r#"
+        WITH days AS (
+            SELECT generate_series($2::date, $3::date, INTERVAL '1 day')::date as tanggal
+        ),
+        daily AS (
+            SELECT d.tanggal, COALESCE(SUM(t.jumlah), 0)::BIGINT as total
+            FROM days d
+            LEFT JOIN transaksi t ON t.user_id = $1
+                AND t.tanggal = d.tanggal
+                AND t.tipe = 'pengeluaran'
+            GROUP BY d.tanggal
+        )
+        SELECT
+            EXTRACT(DAY FROM tanggal)::INT as tanggal,
+            AVG(total)::FLOAT8 as rata_rata,
+            SUM(total)::BIGINT as total,
+            COUNT(*) as jumlah_sampel
+        FROM daily
+        GROUP BY EXTRACT(DAY FROM tanggal)
+        ORDER BY 1
+        "#
+    )
+    .bind(user_uuid)
+    .bind(start_date)
+    .bind(today)
+    .fetch_all(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    // Selalu kembalikan 31 bucket; tanggal tanpa sampel (mis. 31 saat hanya 1 bulan
+    // pendek yang dihitung) diisi 0
+    let mut data: Vec<DayOfMonthPoint> = (1..=31)
+        .map(|tanggal| DayOfMonthPoint {
+            tanggal,
+            rata_rata: 0.0,
+            total: 0,
+            jumlah_sampel: 0,
+        })
+        .collect();
+
+    for row in rows {
+        if let Some(bucket) = data.get_mut((row.tanggal - 1) as usize) {
+            *bucket = row;
+        }
+    }
+
+    Ok(Json(ApiResponse::success(data)
+        .with_meta("filter_applied", json!({
+            "months": months,
+            "start_date": start_date.format("%Y-%m-%d").to_string(),
+            "end_date": today.format("%Y-%m-%d").to_string()
+        }))))
+}
+
+// Get home screen overview in one call: totals, top kategori, ringkasan budget, dan transaksi terbaru.
+// Ini agregat kenyamanan dari query yang sama dengan dashboard/statistik/budget; datanya boleh
+// di-cache sebentar oleh client (lihat header Cache-Control), endpoint aslinya tetap sumber kebenaran.
+pub async fn get_overview(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+) -> Result<([(header::HeaderName, &'static str); 1], Json<ApiResponse<OverviewResponse>>), (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let db_error = |err: sqlx::Error| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    };
+
+    let today = Local::now().naive_local().date();
+    let start_of_month = today.with_day(1).unwrap_or(today);
+
+    let total_hari_ini = sum_pengeluaran(&db, user_uuid, today, today).await.map_err(db_error)?;
+    let total_bulan_ini = sum_pengeluaran(&db, user_uuid, start_of_month, today).await.map_err(db_error)?;
+
+    // Top 3 kategori bulan ini; kategori tanpa pengeluaran tidak ditampilkan
+    let pemasukan_bulan_ini = sum_pemasukan(&db, user_uuid, start_of_month, today).await.map_err(db_error)?;
+    let top_kategori = fetch_pengeluaran_per_kategori(&db, user_uuid, start_of_month, today, total_bulan_ini, pemasukan_bulan_ini)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .filter(|kategori| kategori.total_pengeluaran > 0)
+        .take(3)
+        .collect();
+
+    let budget = sqlx::query_as::<_, RingkasanBudget>(
+        r#"
+        SELECT
+            COUNT(*) as jumlah_budget,
+            COALESCE(SUM(amount + carry_over), 0)::BIGINT as total_budget,
+            COALESCE(SUM(COALESCE(spent, 0)), 0)::BIGINT as total_terpakai,
+            COUNT(*) FILTER (WHERE COALESCE(spent, 0) > amount + carry_over) as jumlah_over
+        FROM budgets
+        WHERE user_id = $1
+        "#
+    )
+    .bind(user_uuid)
+    .fetch_one(&db)
+    .await
+    .map_err(db_error)?;
+
+    let transaksi_terakhir = fetch_transaksi_terakhir(&db, user_uuid, 5).await.map_err(db_error)?;
+
+    let overview = OverviewResponse {
+        total_hari_ini,
+        total_bulan_ini,
+        top_kategori,
+        budget,
+        transaksi_terakhir,
+    };
+
+    Ok((
+        [(header::CACHE_CONTROL, "private, max-age=60")],
+        Json(ApiResponse::success(overview)),
+    ))
+}
+
+// Get spending velocity: rata-rata harian bulan ini vs rata-rata harian hingga 3 bulan sebelumnya
+pub async fn get_spending_velocity(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+) -> Result<Json<ApiResponse<VelocityResponse>>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let db_error = |err: sqlx::Error| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    };
+
+    let today = Local::now().naive_local().date();
+    let start_of_month = today.with_day(1).unwrap_or(today);
+
+    // Bulan pembanding hanya dihitung sejak pengeluaran pertama user,
+    // jadi user baru dibandingkan dengan riwayat yang tersedia saja
+    let first_tanggal: Option<NaiveDate> = sqlx::query_scalar(
+        "SELECT MIN(tanggal) FROM transaksi WHERE user_id = $1 AND tipe = 'pengeluaran'"
+    )
+    .bind(user_uuid)
+    .fetch_one(&db)
+    .await
+    .map_err(db_error)?;
+
+    // Rata-rata harian diprorata: hari sebelum pengeluaran pertama user tidak ikut dihitung
+    let days_since = |start: NaiveDate, end: NaiveDate| {
+        let start = first_tanggal.map_or(start, |first| start.max(first));
+        ((end - start).num_days() + 1).max(1)
+    };
+
+    let total_bulan_ini = sum_pengeluaran(&db, user_uuid, start_of_month, today).await.map_err(db_error)?;
+    let rata_rata_bulan_ini = total_bulan_ini as f64 / days_since(start_of_month, today) as f64;
+
+    let mut bulan_pembanding = 0;
+    let mut comparison_start = start_of_month;
+    if let Some(first_month) = first_tanggal.and_then(|tanggal| tanggal.with_day(1)) {
+        while bulan_pembanding < VELOCITY_MONTHS {
+            let previous = comparison_start - chrono::Months::new(1);
+            if previous < first_month {
+                break;
+            }
+            comparison_start = previous;
+            bulan_pembanding += 1;
+        }
+    }
+
+    let rata_rata_sebelumnya = if bulan_pembanding > 0 {
+        let comparison_end = start_of_month - chrono::Duration::days(1);
+        let total = sum_pengeluaran(&db, user_uuid, comparison_start, comparison_end).await.map_err(db_error)?;
+        Some(total as f64 / days_since(comparison_start, comparison_end) as f64)
+    } else {
+        None
+    };
+
+    let rasio = rata_rata_sebelumnya
+        .filter(|previous| *previous > 0.0)
+        .map(|previous| rata_rata_bulan_ini / previous);
+
+    let status = match rasio {
+        Some(rasio) if rasio > VELOCITY_ACCELERATING => "accelerating",
+        Some(rasio) if rasio < VELOCITY_SLOWING => "slowing",
+        _ => "normal",
+    };
+
+    let round = |value: f64| (value * 100.0).round() / 100.0;
+
+    Ok(Json(ApiResponse::success(VelocityResponse {
+        rata_rata_bulan_ini: round(rata_rata_bulan_ini),
+        rata_rata_sebelumnya: rata_rata_sebelumnya.map(round),
+        rasio: rasio.map(round),
+        status: status.to_string(),
+        bulan_pembanding,
+    })
+    .with_meta("filter_applied", json!({
+        "start_date": comparison_start.format("%Y-%m-%d").to_string(),
+        "end_date": today.format("%Y-%m-%d").to_string()
+    }))))
+}
+
+// Persentase perubahan dari `before` ke `after`, dibulatkan 2 desimal; None jika `before` = 0
+fn persentase_perubahan(after: i64, before: i64) -> Option<f64> {
+    (before != 0).then(|| ((after - before) as f64 * 100.0 / before as f64 * 100.0).round() / 100.0)
+}
+
+// Breakdown per kategori untuk satu rentang, memakai query agregasi yang sama dengan get_user_statistik
+async fn fetch_range_statistik(db: &Database, user_id: Uuid, start: NaiveDate, end: NaiveDate) -> Result<RangeStatistik, sqlx::Error> {
+    let total_pengeluaran = sum_pengeluaran(db, user_id, start, end).await?;
+    let total_pemasukan = sum_pemasukan(db, user_id, start, end).await?;
+    let pengeluaran_per_kategori = fetch_pengeluaran_per_kategori(db, user_id, start, end, total_pengeluaran, total_pemasukan).await?;
+
+    Ok(RangeStatistik {
+        start_date: start,
+        end_date: end,
+        total_pengeluaran,
+        pengeluaran_per_kategori,
+    })
+}
+
+// Compare two arbitrary date ranges: rentang A dibandingkan terhadap rentang B
+pub async fn compare_statistik(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    Query(query): Query<CompareQuery>,
+) -> Result<Json<ApiResponse<CompareResponse>>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let mut errors = FieldErrors::new();
+    let mut parse_date = |field: &'static str, value: &Option<String>| match value.as_deref() {
+        Some(value) => NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+            errors.push(field, "Format tanggal tidak valid. Gunakan format YYYY-MM-DD.");
+        }).ok(),
+        None => {
+            errors.push(field, "Tanggal wajib diisi.");
+            None
+        }
+    };
+
+    let a_start = parse_date("a_start", &query.a_start);
+    let a_end = parse_date("a_end", &query.a_end);
+    let b_start = parse_date("b_start", &query.b_start);
+    let b_end = parse_date("b_end", &query.b_end);
+
+    if let (Some(start), Some(end)) = (a_start, a_end) {
+        if start > end {
+            errors.push("a_end", "Tanggal akhir tidak boleh sebelum tanggal awal.");
+        }
+    }
+    if let (Some(start), Some(end)) = (b_start, b_end) {
+        if start > end {
+            errors.push("b_end", "Tanggal akhir tidak boleh sebelum tanggal awal.");
+        }
+    }
+
+    // Setelah ini semua tanggal dipastikan ada, default tidak pernah terpakai
+    errors.into_result()?;
+    let (a_start, a_end) = (a_start.unwrap_or_default(), a_end.unwrap_or_default());
+    let (b_start, b_end) = (b_start.unwrap_or_default(), b_end.unwrap_or_default());
+
+    let db_error = |err: sqlx::Error| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    };
+
+    let a = fetch_range_statistik(&db, user_uuid, a_start, a_end).await.map_err(db_error)?;
+    let b = fetch_range_statistik(&db, user_uuid, b_start, b_end).await.map_err(db_error)?;
+
+    // Kedua rentang memuat kategori yang sama (semua kategori yang terlihat oleh user)
+    let totals_b: HashMap<i32, i64> = b.pengeluaran_per_kategori
+        .iter()
+        .map(|kategori| (kategori.kategori_id, kategori.total_pengeluaran))
+        .collect();
+
+    let perubahan_per_kategori = a.pengeluaran_per_kategori
+        .iter()
+        .map(|kategori| {
+            let total_b = totals_b.get(&kategori.kategori_id).copied().unwrap_or(0);
+            KategoriDelta {
+                kategori_id: kategori.kategori_id,
+                kategori_nama: kategori.kategori_nama.clone(),
+                total_a: kategori.total_pengeluaran,
+                total_b,
+                selisih: kategori.total_pengeluaran - total_b,
+                persentase_perubahan: persentase_perubahan(kategori.total_pengeluaran, total_b),
+            }
+        })
+        .collect();
+
+    Ok(Json(ApiResponse::success(CompareResponse {
+        total_selisih: a.total_pengeluaran - b.total_pengeluaran,
+        total_persentase_perubahan: persentase_perubahan(a.total_pengeluaran, b.total_pengeluaran),
+        perubahan_per_kategori,
+        a,
+        b,
+    })))
+}
+
+// Get laporan bulanan ("month in review"): ringkasan, breakdown per kategori, transaksi terbesar,
+// pengeluaran harian, dan budget vs aktual dalam satu payload, sebagai JSON atau CSV (`format=csv`
+// atau header Accept: text/csv). Disusun dari helper agregasi yang sama dengan endpoint per fitur
+pub async fn get_laporan_bulanan(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    Query(query): Query<LaporanBulananQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let start_of_month = parse_month_param(&query.month)?;
+    let end_of_month = period_end_for(start_of_month);
+
+    let db_error = |err: sqlx::Error| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    };
+
+    let total_pengeluaran = sum_pengeluaran(&db, user_uuid, start_of_month, end_of_month).await.map_err(db_error)?;
+    let total_pemasukan = sum_pemasukan(&db, user_uuid, start_of_month, end_of_month).await.map_err(db_error)?;
+    let total_transaksi = count_transaksi(&db, user_uuid, start_of_month, end_of_month).await.map_err(db_error)?;
+    let pengeluaran_per_kategori = fetch_pengeluaran_per_kategori(&db, user_uuid, start_of_month, end_of_month, total_pengeluaran, total_pemasukan)
+        .await
+        .map_err(db_error)?;
+    let transaksi_terbesar = fetch_transaksi_terbesar(&db, user_uuid, start_of_month, end_of_month, LAPORAN_TRANSAKSI_TERBESAR)
+        .await
+        .map_err(db_error)?;
+    let budget_vs_aktual = fetch_budget_report(&db, user_uuid, start_of_month, end_of_month).await.map_err(db_error)?;
+
+    // Lengkapi hari tanpa pengeluaran dengan total 0 agar grafik harian tidak bolong
+    let totals: HashMap<NaiveDate, i64> = fetch_pengeluaran_harian(&db, user_uuid, start_of_month, end_of_month)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(|point| (point.tanggal, point.total))
+        .collect();
+    let harian: Vec<HeatmapPoint> = start_of_month
+        .iter_days()
+        .take_while(|tanggal| *tanggal <= end_of_month)
+        .map(|tanggal| HeatmapPoint { tanggal, total: totals.get(&tanggal).copied().unwrap_or(0) })
+        .collect();
+
+    let laporan = LaporanBulananResponse {
+        bulan: start_of_month.format("%Y-%m").to_string(),
+        start_date: start_of_month,
+        end_date: end_of_month,
+        ringkasan: RingkasanPengeluaran {
+            total_pengeluaran,
+            total_pemasukan,
+            rata_rata_harian: total_pengeluaran as f64 / harian.len() as f64,
+            total_transaksi,
+            tertinggi_hari_ini: None,
+            terendah_hari_ini: None,
+            tertinggi_bulan_ini: None,
+            terendah_bulan_ini: None,
+        },
+        pengeluaran_per_kategori,
+        transaksi_terbesar,
+        harian,
+        budget_vs_aktual,
+    };
+
+    if wants_csv(query.format.as_deref(), &headers) {
+        let filename = format!("attachment; filename=\"laporan_{}.csv\"", laporan.bulan);
+        return Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (header::CONTENT_DISPOSITION, filename),
+            ],
+            laporan_csv(&laporan),
+        ).into_response());
+    }
+
+    Ok(Json(ApiResponse::success(laporan)).into_response())
 }
@@ -1,29 +1,136 @@
 use axum::{
     extract::{Path, State, Query},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, Uri},
     response::Json,
 };
 use serde_json::{json, Value};
 use uuid::Uuid;
-use chrono::NaiveDate;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{Local, NaiveDate};
 use serde::Deserialize;
+use sqlx::{Postgres, Transaction};
 
+use crate::audit::{self, client_ip, large_transaksi_threshold, EVENT_LARGE_TRANSAKSI, EVENT_TRANSAKSI_UNDO};
+use crate::budget_period::{is_budget_period, lock_budgets, period_start_for, recalculate_history_spent, recalculate_spent};
 use crate::database::Database;
-use crate::models::transaksi::{Transaksi, TransaksiWithCategory, CreateTransaksiRequest, UpdateTransaksiRequest};
+use crate::extract::JsonBody;
+use crate::pagination::{links, page_url, Pagination};
+use crate::routes::categorization_rule::matching_kategori;
+use crate::routes::budget::check_budget_limit;
+use crate::routes::kategori::{check_kategori_limit, forbidden_kategori_response, kategori_access, KategoriAccess};
+use crate::format::{currency_minor_unit, format_amount};
+use crate::models::budget::Budget;
+use crate::models::kategori::Kategori;
+use crate::quick_input::parse_quick_input;
+use crate::password::verify_password;
+use crate::webhook::{crossed_threshold, emit_budget_exceeded, WebhookClient};
+use crate::validate::{normalize_tags, optional_text, required_text, FieldError, FieldErrors, MAX_DESKRIPSI_LEN, MAX_NAMA_LEN, MAX_NOTE_LEN, MAX_URL_LEN};
+use crate::models::transaksi::{Transaksi, TransaksiWithCategory, CreateTransaksiRequest, UpdateTransaksiRequest, ReassignKategoriRequest, ClearTransaksiRequest, SplitTransaksiRequest, QuickTransaksiRequest, BudgetPreview, TransaksiPreview, KategoriUsage, RecentKategori, TransaksiPerKategori, TIPE_PENGELUARAN, TIPE_PEMASUKAN};
+use crate::routes::profile::fetch_preferences;
 
 #[derive(Debug, Deserialize)]
 pub struct TransaksiQuery {
-    pub limit: Option<i64>,
-    pub offset: Option<i64>,
+    pub cursor: Option<String>, // next_cursor dari response sebelumnya
     pub kategori_id: Option<i32>,
+    pub tag: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecentKategoriQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClearTransaksiQuery {
+    pub before: Option<String>, // Format: "YYYY-MM-DD", hapus transaksi sebelum tanggal ini
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DateRangeQuery {
     pub start_date: Option<String>,
     pub end_date: Option<String>,
 }
 
+// Cursor pagination: base64 (URL-safe) dari "tanggal:id" transaksi terakhir di halaman sebelumnya
+fn encode_cursor(tanggal: NaiveDate, id: i32) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}:{}", tanggal.format("%Y-%m-%d"), id))
+}
+
+fn decode_cursor(cursor: &str) -> Option<(NaiveDate, i32)> {
+    let decoded = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+    let (tanggal, id) = decoded.split_once(':')?;
+    Some((NaiveDate::parse_from_str(tanggal, "%Y-%m-%d").ok()?, id.parse().ok()?))
+}
+
+// Ganti seluruh tag sebuah transaksi; tag baru dibuat (upsert) per user.
+// `tags` harus sudah dinormalisasi dengan normalize_tags.
+async fn set_transaksi_tags(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    transaksi_id: i32,
+    tags: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO tags (user_id, nama) SELECT $1, UNNEST($2::text[]) ON CONFLICT (user_id, nama) DO NOTHING")
+        .bind(user_id)
+        .bind(tags)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query("DELETE FROM transaksi_tags WHERE transaksi_id = $1")
+        .bind(transaksi_id)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query(
+        "INSERT INTO transaksi_tags (transaksi_id, tag_id) SELECT $1, id FROM tags WHERE user_id = $2 AND nama = ANY($3)"
+    )
+    .bind(transaksi_id)
+    .bind(user_id)
+    .bind(tags)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+async fn get_transaksi_tags(tx: &mut Transaction<'_, Postgres>, transaksi_id: i32) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT tg.nama FROM transaksi_tags tt JOIN tags tg ON tg.id = tt.tag_id WHERE tt.transaksi_id = $1 ORDER BY tg.nama"
+    )
+    .bind(transaksi_id)
+    .fetch_all(&mut **tx)
+    .await
+}
+
+// Parse optional "YYYY-MM-DD" query param, 400 jika formatnya salah
+pub(crate) fn parse_optional_date(value: &Option<String>) -> Result<Option<NaiveDate>, (StatusCode, Json<Value>)> {
+    match value {
+        Some(value) => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Format tanggal tidak valid. Gunakan format YYYY-MM-DD."
+                    }))
+                )
+            }),
+        None => Ok(None),
+    }
+}
+
 // Get all transactions for a user
+// limit/offset di luar batas dijepit tanpa error oleh extractor Pagination (limit ke [1, MAX_PAGE_SIZE],
+// offset negatif menjadi 0); nilai yang benar-benar dipakai dikembalikan di `pagination`.
+// Nilai yang bukan bilangan bulat tetap ditolak 400
 pub async fn get_user_transaksi(
     State(db): State<Database>,
     Path(user_id): Path<String>,
+    uri: Uri,
+    Pagination { limit, offset }: Pagination, // offset masih didukung, tapi cursor lebih disarankan untuk halaman yang dalam
     Query(query): Query<TransaksiQuery>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Parse user_id as UUID
@@ -40,8 +147,24 @@ pub async fn get_user_transaksi(
         }
     };
 
-    let limit = query.limit.unwrap_or(50);
-    let offset = query.offset.unwrap_or(0);
+    // Parse filter tanggal dan cursor
+    let start_date = parse_optional_date(&query.start_date)?;
+    let end_date = parse_optional_date(&query.end_date)?;
+    let cursor = match query.cursor.as_deref() {
+        Some(cursor) => match decode_cursor(cursor) {
+            Some(cursor) => Some(cursor),
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Cursor tidak valid."
+                    }))
+                ));
+            }
+        },
+        None => None,
+    };
 
     let mut sql = r#"
         SELECT 
@@ -50,8 +173,18 @@ pub async fn get_user_transaksi(
             t.kategori_id,
             c.nama as kategori_nama,
             t.jumlah,
+            t.minor_unit,
             t.deskripsi,
             t.tanggal,
+            t.note,
+            t.receipt_url,
+            t.tipe,
+            ARRAY(
+                SELECT tg.nama FROM transaksi_tags tt
+                JOIN tags tg ON tg.id = tt.tag_id
+                WHERE tt.transaksi_id = t.id
+                ORDER BY tg.nama
+            ) as tags,
             t.created_at,
             t.updated_at
         FROM transaksi t
@@ -59,51 +192,79 @@ pub async fn get_user_transaksi(
         WHERE t.user_id = $1
     "#.to_string();
 
+    // Filter dipisah dari query utama agar bisa dipakai ulang untuk filtered_total
+    let mut filters = String::new();
     let mut param_count = 2;
     
     // Add kategori filter if provided
     if query.kategori_id.is_some() {
-        sql.push_str(&format!(" AND t.kategori_id = ${}", param_count));
+        filters.push_str(&format!(" AND t.kategori_id = ${}", param_count));
+        param_count += 1;
+    }
+
+    // Add tag filter if provided
+    let tag = query.tag.as_deref()
+        .map(|tag| tag.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase())
+        .filter(|tag| !tag.is_empty());
+    if tag.is_some() {
+        filters.push_str(&format!(
+            " AND EXISTS (SELECT 1 FROM transaksi_tags tt JOIN tags tg ON tg.id = tt.tag_id WHERE tt.transaksi_id = t.id AND tg.nama = ${})",
+            param_count
+        ));
         param_count += 1;
     }
 
     // Add date filters if provided
-    if query.start_date.is_some() {
-        sql.push_str(&format!(" AND t.tanggal >= ${}", param_count));
+    if start_date.is_some() {
+        filters.push_str(&format!(" AND t.tanggal >= ${}", param_count));
         param_count += 1;
     }
 
-    if query.end_date.is_some() {
-        sql.push_str(&format!(" AND t.tanggal <= ${}", param_count));
+    if end_date.is_some() {
+        filters.push_str(&format!(" AND t.tanggal <= ${}", param_count));
         param_count += 1;
     }
 
-    sql.push_str(" ORDER BY t.tanggal DESC, t.created_at DESC");
+    sql.push_str(&filters);
+
+    // Keyset pagination: lanjutkan setelah (tanggal, id) terakhir, offset diabaikan
+    if cursor.is_some() {
+        sql.push_str(&format!(" AND (t.tanggal, t.id) < (${}, ${})", param_count, param_count + 1));
+        param_count += 2;
+    }
+
+    // Ambil satu baris ekstra untuk mengetahui apakah masih ada halaman berikutnya
+    sql.push_str(" ORDER BY t.tanggal DESC, t.id DESC");
     sql.push_str(&format!(" LIMIT ${} OFFSET ${}", param_count, param_count + 1));
 
     let mut query_builder = sqlx::query_as::<_, TransaksiWithCategory>(&sql)
-        .bind(user_uuid)
-        .bind(limit);
+        .bind(user_uuid);
 
     if let Some(kategori_id) = query.kategori_id {
         query_builder = query_builder.bind(kategori_id);
     }
 
-    if let Some(start_date) = query.start_date {
-        if let Ok(date) = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d") {
-            query_builder = query_builder.bind(date);
-        }
+    if let Some(tag) = &tag {
+        query_builder = query_builder.bind(tag);
     }
 
-    if let Some(end_date) = query.end_date {
-        if let Ok(date) = NaiveDate::parse_from_str(&end_date, "%Y-%m-%d") {
-            query_builder = query_builder.bind(date);
-        }
+    if let Some(date) = start_date {
+        query_builder = query_builder.bind(date);
+    }
+
+    if let Some(date) = end_date {
+        query_builder = query_builder.bind(date);
+    }
+
+    if let Some((cursor_tanggal, cursor_id)) = cursor {
+        query_builder = query_builder.bind(cursor_tanggal).bind(cursor_id);
     }
 
-    query_builder = query_builder.bind(offset);
+    query_builder = query_builder
+        .bind(limit + 1)
+        .bind(if cursor.is_some() { 0 } else { offset });
 
-    let transaksi = query_builder
+    let mut transaksi = query_builder
         .fetch_all(&db)
         .await
         .map_err(|err| {
@@ -117,71 +278,241 @@ pub async fn get_user_transaksi(
             )
         })?;
 
-    Ok(Json(json!({
-        "status": "success",
-        "transaksi": transaksi
-    })))
-}
+    // Total pengeluaran seluruh hasil filter (mengabaikan limit/offset/cursor)
+    let total_sql = format!(
+        "SELECT COALESCE(SUM(t.jumlah) FILTER (WHERE t.tipe = 'pengeluaran'), 0)::BIGINT FROM transaksi t WHERE t.user_id = $1{}",
+        filters
+    );
+    let mut total_builder = sqlx::query_scalar::<_, i64>(&total_sql).bind(user_uuid);
 
-// Create new transaction for a user
-pub async fn create_transaksi(
-    State(db): State<Database>,
-    Path(user_id): Path<String>,
-    Json(payload): Json<CreateTransaksiRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    // Parse user_id as UUID
-    let user_uuid = match Uuid::parse_str(&user_id) {
-        Ok(uuid) => uuid,
-        Err(_) => {
-            return Err((
-                StatusCode::BAD_REQUEST,
+    if let Some(kategori_id) = query.kategori_id {
+        total_builder = total_builder.bind(kategori_id);
+    }
+
+    if let Some(tag) = &tag {
+        total_builder = total_builder.bind(tag);
+    }
+
+    if let Some(date) = start_date {
+        total_builder = total_builder.bind(date);
+    }
+
+    if let Some(date) = end_date {
+        total_builder = total_builder.bind(date);
+    }
+
+    let filtered_total = total_builder
+        .fetch_one(&db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
                 Json(json!({
                     "status": "error",
-                    "message": "Invalid user ID format."
+                    "message": "Terjadi kesalahan pada server."
                 }))
-            ));
-        }
+            )
+        })?;
+
+    let next_cursor = if transaksi.len() as i64 > limit {
+        transaksi.truncate(limit as usize);
+        transaksi.last().map(|last| encode_cursor(last.tanggal, last.id))
+    } else {
+        None
     };
 
-    // Validasi input
-    if payload.jumlah <= 0 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "status": "error",
-                "message": "Jumlah harus lebih dari 0."
-            }))
-        ));
+    let preferences = fetch_preferences(&db, user_uuid).await.unwrap_or_default();
+    for item in transaksi.iter_mut() {
+        item.jumlah_formatted = format_amount(item.jumlah as i64, item.minor_unit, &preferences);
     }
 
-    if payload.deskripsi.trim().is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "status": "error",
-                "message": "Deskripsi tidak boleh kosong."
-            }))
-        ));
+    // Halaman berikutnya selalu lewat cursor; prev hanya bisa dihitung pada mode offset
+    let page_links = links(
+        page_url(&uri, &[], &[]),
+        next_cursor.as_ref().map(|cursor| page_url(&uri, &[("cursor", cursor.clone())], &["offset"])),
+        (cursor.is_none() && offset > 0)
+            .then(|| page_url(&uri, &[("limit", limit.to_string()), ("offset", (offset - limit).max(0).to_string())], &[])),
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "transaksi": transaksi,
+        "next_cursor": next_cursor,
+        "links": page_links,
+        "pagination": {
+            "limit": limit,
+            "offset": if cursor.is_some() { 0 } else { offset }
+        },
+        "filtered_total": filtered_total,
+        "filtered_total_formatted": format_amount(filtered_total, currency_minor_unit(&preferences.currency), &preferences)
+    })))
+}
+
+// Hasil validasi body create; dipakai bersama oleh create_transaksi dan preview_transaksi
+struct ValidatedTransaksi<'a> {
+    deskripsi: &'a str,
+    note: Option<&'a str>,
+    receipt_url: Option<&'a str>,
+    tags: Vec<String>,
+    tanggal: NaiveDate,
+    kategori_id: i32, // Dari body, atau dari aturan kategori jika body tidak mengirimnya
+    tipe: String,
+    minor_unit: i16,
+    is_pengeluaran: bool,
+    budget_exists: bool,
+    budget_amount: i32, // amount budget saat ini, atau auto_budget_amount jika budget akan dibuat
+    carry_over: i32,
+    spent: i32,
+    budget_period_start: Option<NaiveDate>, // Override periode budget yang sudah divalidasi
+    counts_to_current_period: bool, // false jika periode transaksi (override atau tanggal) bukan periode budget berjalan
+}
+
+// Parse override budget_period_start: format YYYY-MM-DD dan harus tanggal awal periode
+fn parse_budget_period_start(value: &str) -> Result<NaiveDate, FieldError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .filter(|date| period_start_for(*date) == *date)
+        .ok_or_else(|| FieldError {
+            field: "budget_period_start",
+            message: "budget_period_start harus tanggal awal periode budget (YYYY-MM-01).".to_string(),
+        })
+}
+
+// Pesan untuk override yang bukan periode budget kategori ini (atau bukan pengeluaran)
+const INVALID_BUDGET_PERIOD_MESSAGE: &str =
+    "budget_period_start harus periode berjalan atau periode lama dari budget kategori ini, dan hanya untuk pengeluaran.";
+
+fn invalid_budget_period_error() -> (StatusCode, Json<Value>) {
+    FieldError {
+        field: "budget_period_start",
+        message: INVALID_BUDGET_PERIOD_MESSAGE.to_string(),
+    }
+    .into_response()
+}
+
+// Awal periode budget tempat transaksi dihitung: override jika diisi, selain itu bulan dari tanggal
+fn counted_period(budget_period_start: Option<NaiveDate>, tanggal: NaiveDate) -> NaiveDate {
+    budget_period_start.unwrap_or_else(|| period_start_for(tanggal))
+}
+
+// Hitung ulang spent periode berjalan beserta snapshot budget_history periode yang terlibat.
+// Dipakai untuk transaksi yang (mungkin) dihitung ke periode lain, karena jumlahnya tidak bisa
+// langsung ditambahkan ke atau dikurangkan dari spent berjalan
+async fn recalculate_spent_with_periods(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    kategori_ids: &[i32],
+    periods: &[NaiveDate],
+) -> Result<Vec<(Budget, i32)>, sqlx::Error> {
+    let recalculated = recalculate_spent(tx, user_id, kategori_ids).await?;
+    let mut periods = periods.to_vec();
+    periods.sort_unstable();
+    periods.dedup();
+    for period_start in periods {
+        recalculate_history_spent(tx, user_id, kategori_ids, period_start).await?;
+    }
+    Ok(recalculated)
+}
+
+// Validasi body create transaksi (field, kategori, dan keberadaan budget) tanpa menulis apa pun
+async fn validate_create_transaksi<'a>(
+    db: &Database,
+    user_uuid: Uuid,
+    payload: &'a CreateTransaksiRequest,
+) -> Result<ValidatedTransaksi<'a>, (StatusCode, Json<Value>)> {
+    // Validasi semua field sekaligus agar client menerima seluruh kesalahan dalam satu response
+    let mut errors = FieldErrors::new();
+
+    if payload.jumlah <= 0 {
+        errors.push("jumlah", "Jumlah harus lebih dari 0.");
     }
 
+    // Validasi dan trim input teks
+    let deskripsi = errors.check(required_text("deskripsi", &payload.deskripsi, MAX_DESKRIPSI_LEN)).unwrap_or_default();
+    let note = errors.check(optional_text("note", payload.note.as_deref(), MAX_NOTE_LEN)).flatten();
+    let receipt_url = errors.check(optional_text("receipt_url", payload.receipt_url.as_deref(), MAX_URL_LEN)).flatten();
+    let tags = errors.check(normalize_tags(payload.tags.as_deref().unwrap_or_default())).unwrap_or_default();
+
     // Parse tanggal
-    let tanggal = match NaiveDate::parse_from_str(&payload.tanggal, "%Y-%m-%d") {
-        Ok(date) => date,
-        Err(_) => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(json!({
-                    "status": "error",
-                    "message": "Format tanggal tidak valid. Gunakan format YYYY-MM-DD."
-                }))
-            ));
-        }
+    let tanggal = NaiveDate::parse_from_str(&payload.tanggal, "%Y-%m-%d").unwrap_or_else(|_| {
+        errors.push("tanggal", "Format tanggal tidak valid. Gunakan format YYYY-MM-DD.");
+        NaiveDate::default()
+    });
+
+    // Jenis transaksi, default pengeluaran
+    let tipe = payload.tipe.as_deref().map(str::trim).unwrap_or(TIPE_PENGELUARAN);
+    if tipe != TIPE_PENGELUARAN && tipe != TIPE_PEMASUKAN {
+        errors.push("tipe", "Tipe transaksi harus 'pengeluaran' atau 'pemasukan'.");
+    }
+    let is_pengeluaran = tipe == TIPE_PENGELUARAN;
+
+    if payload.auto_budget_amount.is_some_and(|auto_amount| auto_amount <= 0) {
+        errors.push("auto_budget_amount", "auto_budget_amount harus lebih dari 0.");
+    }
+
+    // Override periode budget: format dicek di sini, kecocokan dengan periode budget setelah kategori diketahui
+    let budget_period_start = payload.budget_period_start.as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .and_then(|value| errors.check(parse_budget_period_start(value)));
+
+    // Kategori tidak dikirim: pakai aturan kategori user yang cocok dengan deskripsi
+    let kategori_id = match payload.kategori_id {
+        Some(kategori_id) => Some(kategori_id),
+        None if deskripsi.is_empty() => None,
+        None => matching_kategori(db, user_uuid, deskripsi)
+            .await
+            .map_err(|err| {
+                eprintln!("Database error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Terjadi kesalahan pada server."
+                    }))
+                )
+            })?,
     };
 
     // Cek apakah kategori exists
-    let category_exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1)")
-        .bind(payload.kategori_id)
-        .fetch_one(&db)
+    let mut kategori_allowed = false;
+    match kategori_id {
+        Some(kategori_id) => {
+            let kategori = kategori_access(db, kategori_id, user_uuid)
+                .await
+                .map_err(|err| {
+                    eprintln!("Database error: {:?}", err);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({
+                            "status": "error",
+                            "message": "Terjadi kesalahan pada server."
+                        }))
+                    )
+                })?;
+
+            match kategori {
+                KategoriAccess::Allowed => kategori_allowed = true,
+                KategoriAccess::NotFound => errors.push("kategori_id", "Kategori tidak ditemukan."),
+                KategoriAccess::Forbidden => return Err(forbidden_kategori_response()),
+            }
+        }
+        None if !deskripsi.is_empty() => {
+            errors.push("kategori_id", "Kategori wajib diisi karena tidak ada aturan kategori yang cocok dengan deskripsi.");
+        }
+        None => {}
+    }
+    let kategori_id = kategori_id.unwrap_or_default();
+
+    // VALIDASI BUDGET: Cek apakah user memiliki budget untuk kategori ini (pemasukan tidak terikat budget)
+    let budget_exists = if is_pengeluaran && kategori_allowed {
+        sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM budgets WHERE user_id = $1 AND kategori_id = $2)"
+        )
+        .bind(user_uuid)
+        .bind(kategori_id)
+        .fetch_one(db)
         .await
         .map_err(|err| {
             eprintln!("Database error: {:?}", err);
@@ -192,44 +523,57 @@ pub async fn create_transaksi(
                     "message": "Terjadi kesalahan pada server."
                 }))
             )
-        })?;
+        })?
+    } else {
+        false
+    };
 
-    if !category_exists {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "status": "error",
-                "message": "Kategori tidak ditemukan."
-            }))
-        ));
+    // Override hanya untuk pengeluaran, ke periode berjalan atau periode lama budget yang sudah ada
+    if let Some(period_start) = budget_period_start {
+        let known_period = budget_exists
+            && is_budget_period(db, user_uuid, kategori_id, period_start)
+                .await
+                .map_err(|err| {
+                    eprintln!("Database error: {:?}", err);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({
+                            "status": "error",
+                            "message": "Terjadi kesalahan pada server."
+                        }))
+                    )
+                })?;
+        // Kategori yang salah sudah dilaporkan sendiri; periode hanya dinilai jika kategorinya valid
+        if !known_period && (kategori_allowed || !is_pengeluaran) {
+            errors.push("budget_period_start", INVALID_BUDGET_PERIOD_MESSAGE);
+        }
     }
 
-    // VALIDASI BUDGET: Cek apakah user memiliki budget untuk kategori ini
-    let budget_exists = sqlx::query_scalar::<_, bool>(
-        "SELECT EXISTS(SELECT 1 FROM budgets WHERE user_id = $1 AND kategori_id = $2)"
-    )
-    .bind(user_uuid)
-    .bind(payload.kategori_id)
-    .fetch_one(&db)
-    .await
-    .map_err(|err| {
-        eprintln!("Database error: {:?}", err);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({
-                "status": "error",
-                "message": "Terjadi kesalahan pada server."
-            }))
-        )
-    })?;
+    // Setelah ini semua nilai dipastikan valid, default hanya dipakai saat ada error
+    errors.into_result()?;
+
+    // Jumlah disimpan dalam minor unit mata uang user; eksponennya dicatat per baris
+    let minor_unit = fetch_preferences(db, user_uuid)
+        .await
+        .map(|preferences| currency_minor_unit(&preferences.currency))
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
 
-    if !budget_exists {
+    if is_pengeluaran && !budget_exists && payload.auto_budget_amount.is_none() {
         // Get category name for better error message
         let category_name = sqlx::query_scalar::<_, String>(
             "SELECT nama FROM categories WHERE id = $1"
         )
-        .bind(payload.kategori_id)
-        .fetch_one(&db)
+        .bind(kategori_id)
+        .fetch_one(db)
         .await
         .unwrap_or_else(|_| "kategori ini".to_string());
 
@@ -242,64 +586,182 @@ pub async fn create_transaksi(
         ));
     }
 
-    // Optional: Cek apakah transaksi melebihi sisa budget
-    let budget_info = sqlx::query_as::<_, (i32, Option<i32>)>(
-        "SELECT amount, COALESCE(spent, 0) as spent FROM budgets WHERE user_id = $1 AND kategori_id = $2"
-    )
-    .bind(user_uuid)
-    .bind(payload.kategori_id)
-    .fetch_one(&db)
-    .await
-    .map_err(|err| {
-        eprintln!("Database error: {:?}", err);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({
-                "status": "error",
-                "message": "Terjadi kesalahan pada server."
-            }))
+    // Angka budget saat ini untuk cek sisa budget (dan proyeksi preview)
+    let (budget_amount, carry_over, spent, current_period_start) = if budget_exists {
+        sqlx::query_as::<_, (i32, i32, i32, NaiveDate)>(
+            "SELECT amount, carry_over, COALESCE(spent, 0) as spent, period_start FROM budgets WHERE user_id = $1 AND kategori_id = $2"
         )
-    })?;
+        .bind(user_uuid)
+        .bind(kategori_id)
+        .fetch_one(db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?
+    } else {
+        // Budget baru akan dibuat otomatis dengan auto_budget_amount
+        (payload.auto_budget_amount.unwrap_or(0), 0, 0, period_start_for(Local::now().naive_local().date()))
+    };
 
-    let (budget_amount, spent) = budget_info;
-    let remaining_budget = budget_amount - spent.unwrap_or(0);
-    
-    if payload.jumlah > remaining_budget {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "status": "error",
-                "message": format!(
-                    "Transaksi sebesar {} melebihi sisa budget Anda ({}). Sisa budget: {}",
-                    payload.jumlah,
-                    budget_amount,
-                    remaining_budget
-                )
-            }))
-        ));
-    }
+    Ok(ValidatedTransaksi {
+        deskripsi,
+        note,
+        receipt_url,
+        tags,
+        tanggal,
+        kategori_id,
+        tipe: tipe.to_string(),
+        minor_unit,
+        is_pengeluaran,
+        budget_exists,
+        budget_amount,
+        carry_over,
+        spent,
+        budget_period_start,
+        counts_to_current_period: counted_period(budget_period_start, tanggal) == current_period_start,
+    })
+}
 
-    // Start transaction to update budget spent if exists
-    let mut tx = db.begin().await.map_err(|err| {
-        eprintln!("Transaction error: {:?}", err);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({
+// Create new transaction for a user
+pub async fn create_transaksi(
+    State(db): State<Database>,
+    State(webhook_client): State<WebhookClient>,
+    Path(user_id): Path<String>,
+    headers: HeaderMap,
+    JsonBody(payload): JsonBody<CreateTransaksiRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let ValidatedTransaksi {
+        deskripsi,
+        note,
+        receipt_url,
+        tags,
+        tanggal,
+        kategori_id,
+        tipe,
+        minor_unit,
+        is_pengeluaran,
+        budget_exists,
+        budget_amount,
+        budget_period_start,
+        counts_to_current_period,
+        ..
+    } = validate_create_transaksi(&db, user_uuid, &payload).await?;
+
+    // Start transaction to update budget spent if exists
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
                 "status": "error",
                 "message": "Terjadi kesalahan pada server."
             }))
         )
     })?;
 
+    // Buat budget otomatis jika belum ada dan auto_budget_amount dikirim. Request bersamaan untuk
+    // kategori yang sama bisa sama-sama melihat budget belum ada; yang kalah cukup memakai budget
+    // yang sudah dibuat (barisnya dikunci oleh lock_budgets di bawah) dan tidak melaporkannya sebagai budget baru
+    let budget_created = if is_pengeluaran && !budget_exists {
+        sqlx::query_scalar::<_, i32>(
+            "INSERT INTO budgets (user_id, kategori_id, amount, minor_unit) VALUES ($1, $2, $3, $4) ON CONFLICT (user_id, kategori_id) DO NOTHING RETURNING id"
+        )
+        .bind(user_uuid)
+        .bind(kategori_id)
+        .bind(budget_amount)
+        .bind(minor_unit)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Gagal membuat budget."
+                }))
+            )
+        })?
+        .is_some()
+    } else {
+        false
+    };
+
+    if budget_created {
+        check_budget_limit(&mut tx, user_uuid).await?;
+    }
+
+    // Cek sisa budget di dalam DB transaction dengan baris budget terkunci, sehingga pengeluaran
+    // bersamaan pada kategori yang sama dicek bergantian dan tidak bisa sama-sama lolos melebihi budget.
+    // Pengeluaran yang dihitung ke periode lama tidak mengurangi sisa budget periode berjalan
+    if is_pengeluaran && counts_to_current_period {
+        let locked = lock_budgets(&mut tx, user_uuid, &[kategori_id]).await.map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+        if let Some(budget) = locked.first() {
+            // Budget efektif termasuk carry-over dari periode sebelumnya (sama dengan API budget)
+            let effective_amount = budget.amount + budget.carry_over;
+            let remaining_budget = effective_amount - budget.spent.unwrap_or(0);
+            if payload.jumlah > remaining_budget {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({
+                        "status": "error",
+                        "message": format!(
+                            "Transaksi sebesar {} melebihi sisa budget Anda ({}). Sisa budget: {}",
+                            payload.jumlah,
+                            effective_amount,
+                            remaining_budget
+                        )
+                    }))
+                ));
+            }
+        }
+    }
+
     // Insert transaksi baru
     let new_transaksi = sqlx::query_as::<_, Transaksi>(
-        "INSERT INTO transaksi (user_id, kategori_id, jumlah, deskripsi, tanggal) VALUES ($1, $2, $3, $4, $5) RETURNING *"
+        "INSERT INTO transaksi (user_id, kategori_id, jumlah, deskripsi, tanggal, note, receipt_url, tipe, minor_unit, budget_period_start) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *"
     )
     .bind(user_uuid)
-    .bind(payload.kategori_id)
+    .bind(kategori_id)
     .bind(payload.jumlah)
-    .bind(&payload.deskripsi.trim())
+    .bind(deskripsi)
     .bind(tanggal)
+    .bind(note)
+    .bind(receipt_url)
+    .bind(tipe)
+    .bind(minor_unit)
+    .bind(budget_period_start)
     .fetch_one(&mut *tx)
     .await
     .map_err(|err| {
@@ -313,25 +775,63 @@ pub async fn create_transaksi(
         )
     })?;
 
-    // Update budget spent if exists for this user and category
-    sqlx::query(
-        "UPDATE budgets SET spent = COALESCE(spent, 0) + $1, updated_at = NOW() WHERE user_id = $2 AND kategori_id = $3"
-    )
-    .bind(payload.jumlah)
-    .bind(user_uuid)
-    .bind(payload.kategori_id)
-    .execute(&mut *tx)
-    .await
-    .map_err(|err| {
-        eprintln!("Database error: {:?}", err);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({
-                "status": "error",
-                "message": "Gagal mengupdate budget."
-            }))
+    // Simpan tag transaksi
+    if !tags.is_empty() {
+        set_transaksi_tags(&mut tx, user_uuid, new_transaksi.id, &tags).await.map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Gagal menyimpan tag."
+                }))
+            )
+        })?;
+    }
+
+    // Update budget spent if exists for this user and category (hanya pengeluaran), beserta spent sebelumnya.
+    // Transaksi dengan override periode atau di luar periode berjalan dihitung ulang karena bisa masuk ke snapshot periode lama
+    let updated_budget = if is_pengeluaran && (budget_period_start.is_some() || !counts_to_current_period) {
+        recalculate_spent_with_periods(&mut tx, user_uuid, &[kategori_id], &[counted_period(budget_period_start, tanggal)])
+            .await
+            .map_err(|err| {
+                eprintln!("Database error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Gagal mengupdate budget."
+                    }))
+                )
+            })?
+            .into_iter()
+            .next()
+    } else if is_pengeluaran && counts_to_current_period {
+        sqlx::query_as::<_, Budget>(
+            "UPDATE budgets SET spent = COALESCE(spent, 0) + $1, updated_at = NOW() WHERE user_id = $2 AND kategori_id = $3 RETURNING *"
         )
-    })?;
+        .bind(payload.jumlah)
+        .bind(user_uuid)
+        .bind(kategori_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Gagal mengupdate budget."
+                }))
+            )
+        })?
+        .map(|budget| {
+            let spent_before = budget.spent.unwrap_or(0) - payload.jumlah;
+            (budget, spent_before)
+        })
+    } else {
+        None
+    };
 
     // Commit transaction
     tx.commit().await.map_err(|err| {
@@ -345,17 +845,111 @@ pub async fn create_transaksi(
         )
     })?;
 
+    // Kirim event webhook jika budget baru saja mencapai 100%
+    if let Some((budget, spent_before)) = &updated_budget {
+        if crossed_threshold(*spent_before, budget) {
+            emit_budget_exceeded(db.clone(), webhook_client.clone(), user_uuid, budget.clone());
+        }
+    }
+
+    if new_transaksi.jumlah >= large_transaksi_threshold() {
+        let detail = json!({
+            "transaksi_id": new_transaksi.id,
+            "jumlah": new_transaksi.jumlah,
+            "tipe": new_transaksi.tipe
+        });
+        audit::record(&db, user_uuid, EVENT_LARGE_TRANSAKSI, client_ip(&headers).as_deref(), Some(detail)).await;
+    }
+
     // Response sukses
     Ok(Json(json!({
         "status": "success",
         "message": "Transaksi berhasil dibuat!",
-        "data": new_transaksi
+        "data": new_transaksi,
+        "tags": tags,
+        "budget_baru": if budget_created { updated_budget.map(|(budget, _)| budget) } else { None }
+    })))
+}
+
+// Persentase spent terhadap budget efektif, dibulatkan 2 desimal
+fn budget_percentage(spent: i32, effective_amount: i32) -> f64 {
+    if effective_amount > 0 {
+        (spent as f64 / effective_amount as f64 * 10000.0).round() / 100.0
+    } else {
+        0.0
+    }
+}
+
+// Preview dampak transaksi terhadap budget tanpa menulis apa pun.
+// Body dan validasi sama dengan create_transaksi sehingga keduanya selalu sepakat
+pub async fn preview_transaksi(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    JsonBody(payload): JsonBody<CreateTransaksiRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let validated = validate_create_transaksi(&db, user_uuid, &payload).await?;
+
+    // Aturan yang sama dengan create_transaksi: jumlah tidak boleh melebihi amount + carry_over - spent
+    let effective_amount = validated.budget_amount + validated.carry_over;
+    let remaining_budget = effective_amount - validated.spent;
+    let would_exceed = validated.is_pengeluaran && validated.counts_to_current_period && payload.jumlah > remaining_budget;
+
+    let budget = validated.is_pengeluaran.then(|| {
+        // Pengeluaran yang dihitung ke periode lama tidak mengubah spent periode berjalan
+        let added = if validated.counts_to_current_period { payload.jumlah } else { 0 };
+        let projected_spent = validated.spent + added;
+        BudgetPreview {
+            kategori_id: validated.kategori_id,
+            budget_baru: !validated.budget_exists,
+            amount: validated.budget_amount,
+            effective_amount,
+            spent_sebelum: validated.spent,
+            percentage_sebelum: budget_percentage(validated.spent, effective_amount),
+            spent: projected_spent,
+            percentage: budget_percentage(projected_spent, effective_amount),
+            sisa: effective_amount - projected_spent,
+        }
+    });
+
+    let message = would_exceed.then(|| {
+        format!(
+            "Transaksi sebesar {} melebihi sisa budget Anda ({}). Sisa budget: {}",
+            payload.jumlah,
+            effective_amount,
+            remaining_budget
+        )
+    });
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": message,
+        "data": TransaksiPreview {
+            tipe: validated.tipe,
+            jumlah: payload.jumlah,
+            budget,
+            would_exceed,
+        }
     })))
 }
 
 // Update transaction
 pub async fn update_transaksi(
     State(db): State<Database>,
+    State(webhook_client): State<WebhookClient>,
     Path((user_id, transaksi_id)): Path<(String, i32)>,
     Json(payload): Json<UpdateTransaksiRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
@@ -373,36 +967,24 @@ pub async fn update_transaksi(
         }
     };
 
-    // Cek apakah transaksi exists dan belongs to user
-    let existing_transaksi = sqlx::query_as::<_, Transaksi>(
-        "SELECT * FROM transaksi WHERE id = $1 AND user_id = $2"
-    )
-    .bind(transaksi_id)
-    .bind(user_uuid)
-    .fetch_optional(&db)
-    .await
-    .map_err(|err| {
-        eprintln!("Database error: {:?}", err);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({
-                "status": "error",
-                "message": "Terjadi kesalahan pada server."
-            }))
-        )
-    })?;
-
-    if existing_transaksi.is_none() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(json!({
-                "status": "error",
-                "message": "Transaksi tidak ditemukan."
-            }))
-        ));
-    }
-
-    let old_transaksi = existing_transaksi.unwrap();
+    // Validasi dan trim input teks (hanya field yang dikirim)
+    let deskripsi = payload.deskripsi.as_deref()
+        .map(|deskripsi| required_text("deskripsi", deskripsi, MAX_DESKRIPSI_LEN))
+        .transpose()
+        .map_err(FieldError::into_response)?;
+    // note/receipt_url: None = tidak diubah, Some(None) = dihapus (null atau string kosong), Some(Some(_)) = diganti
+    let note = payload.note.as_ref()
+        .map(|note| optional_text("note", note.as_deref(), MAX_NOTE_LEN))
+        .transpose()
+        .map_err(FieldError::into_response)?;
+    let receipt_url = payload.receipt_url.as_ref()
+        .map(|receipt_url| optional_text("receipt_url", receipt_url.as_deref(), MAX_URL_LEN))
+        .transpose()
+        .map_err(FieldError::into_response)?;
+    let tags = payload.tags.as_deref()
+        .map(normalize_tags)
+        .transpose()
+        .map_err(FieldError::into_response)?;
 
     // Parse tanggal if provided
     let tanggal = if let Some(tanggal_str) = &payload.tanggal {
@@ -422,11 +1004,16 @@ pub async fn update_transaksi(
         None
     };
 
+    // Override periode budget: None = tidak diubah, Some(None) = dihapus, Some(Some(_)) = diganti
+    let budget_period_start = match payload.budget_period_start.as_ref().map(|value| value.as_deref().map(str::trim)) {
+        None => None,
+        Some(None | Some("")) => Some(None),
+        Some(Some(value)) => Some(Some(parse_budget_period_start(value).map_err(FieldError::into_response)?)),
+    };
+
     // Validasi kategori if provided
     if let Some(kategori_id) = payload.kategori_id {
-        let category_exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1)")
-            .bind(kategori_id)
-            .fetch_one(&db)
+        let kategori = kategori_access(&db, kategori_id, user_uuid)
             .await
             .map_err(|err| {
                 eprintln!("Database error: {:?}", err);
@@ -439,18 +1026,1452 @@ pub async fn update_transaksi(
                 )
             })?;
 
-        if !category_exists {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(json!({
-                    "status": "error",
-                    "message": "Kategori tidak ditemukan."
-                }))
-            ));
+        match kategori {
+            KategoriAccess::Allowed => {}
+            KategoriAccess::NotFound => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Kategori tidak ditemukan."
+                    }))
+                ));
+            }
+            KategoriAccess::Forbidden => return Err(forbidden_kategori_response()),
+        }
+    }
+
+    // Start transaction to update budget spent
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    // Cek apakah transaksi exists dan belongs to user. Baris dikunci (FOR UPDATE) sampai commit supaya
+    // update bersamaan pada transaksi yang sama berjalan bergantian dan selisih spent selalu dihitung
+    // dari nilai terbaru, bukan dari data lama yang sudah ditimpa request lain (lost update)
+    let existing_transaksi = sqlx::query_as::<_, Transaksi>(
+        "SELECT * FROM transaksi WHERE id = $1 AND user_id = $2 FOR UPDATE"
+    )
+    .bind(transaksi_id)
+    .bind(user_uuid)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    if existing_transaksi.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Transaksi tidak ditemukan."
+            }))
+        ));
+    }
+
+    let old_transaksi = existing_transaksi.unwrap();
+
+    // Override yang berlaku setelah update harus periode budget kategori tujuan. Dicek ulang juga
+    // saat kategori berubah, karena periode lama kategori asal belum tentu ada di kategori tujuan
+    let target_period_start = budget_period_start.unwrap_or(old_transaksi.budget_period_start);
+    if let Some(period_start) = target_period_start {
+        let target_kategori_id = payload.kategori_id.unwrap_or(old_transaksi.kategori_id);
+        if budget_period_start.is_some() || target_kategori_id != old_transaksi.kategori_id {
+            let known_period = old_transaksi.tipe == TIPE_PENGELUARAN
+                && is_budget_period(&mut *tx, user_uuid, target_kategori_id, period_start)
+                    .await
+                    .map_err(|err| {
+                        eprintln!("Database error: {:?}", err);
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            Json(json!({
+                                "status": "error",
+                                "message": "Terjadi kesalahan pada server."
+                            }))
+                        )
+                    })?;
+            if !known_period {
+                return Err(invalid_budget_period_error());
+            }
         }
     }
 
-    // Start transaction to update budget spent
+    // Update transaksi (hanya jika updated_at masih sama dengan yang diharapkan client)
+    let updated_transaksi = sqlx::query_as::<_, Transaksi>(
+        r#"UPDATE transaksi SET
+           kategori_id = COALESCE($1, kategori_id),
+           jumlah = COALESCE($2, jumlah),
+           deskripsi = COALESCE($3, deskripsi),
+           tanggal = COALESCE($4, tanggal),
+           note = CASE WHEN $11 THEN $7 ELSE note END,
+           receipt_url = CASE WHEN $12 THEN $8 ELSE receipt_url END,
+           budget_period_start = CASE WHEN $9 THEN $10 ELSE budget_period_start END,
+           updated_at = NOW()
+           WHERE id = $5 AND ($6::timestamptz IS NULL OR updated_at = $6) RETURNING *"#
+    )
+    .bind(payload.kategori_id)
+    .bind(payload.jumlah)
+    .bind(deskripsi)
+    .bind(tanggal)
+    .bind(transaksi_id)
+    .bind(payload.expected_updated_at)
+    .bind(note.flatten())
+    .bind(receipt_url.flatten())
+    .bind(budget_period_start.is_some())
+    .bind(target_period_start)
+    .bind(note.is_some())
+    .bind(receipt_url.is_some())
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal mengupdate transaksi."
+            }))
+        )
+    })?;
+
+    // Tidak ada baris yang terupdate berarti transaksi sudah diubah oleh request lain
+    let updated_transaksi = match updated_transaksi {
+        Some(transaksi) => transaksi,
+        None => {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(json!({
+                    "status": "error",
+                    "message": "Transaksi telah diubah oleh request lain. Muat ulang data dan coba lagi."
+                }))
+            ));
+        }
+    };
+
+    // Perubahan spent budget kategori tujuan jika dihitung ulang (untuk cek threshold webhook)
+    let mut target_spent_added = None;
+
+    // Update budget spent - subtract old amount and add new amount (pemasukan tidak mempengaruhi budget)
+    if old_transaksi.tipe == TIPE_PENGELUARAN {
+        if updated_transaksi.tanggal != old_transaksi.tanggal
+            || period_start_for(old_transaksi.tanggal) != period_start_for(Local::now().naive_local().date())
+            || old_transaksi.budget_period_start.is_some()
+            || updated_transaksi.budget_period_start.is_some()
+        {
+            // Tanggal atau override periode terlibat, atau transaksi di luar periode berjalan: transaksi bisa
+            // keluar/masuk periode budget berjalan, jadi spent kategori lama dan baru (serta snapshot periode
+            // override) dihitung ulang
+            let recalculated = recalculate_spent_with_periods(
+                &mut tx,
+                user_uuid,
+                &[old_transaksi.kategori_id, updated_transaksi.kategori_id],
+                &[
+                    counted_period(old_transaksi.budget_period_start, old_transaksi.tanggal),
+                    counted_period(updated_transaksi.budget_period_start, updated_transaksi.tanggal),
+                ],
+            )
+                .await
+                .map_err(|err| {
+                    eprintln!("Database error: {:?}", err);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({
+                            "status": "error",
+                            "message": "Gagal mengupdate budget."
+                        }))
+                    )
+                })?;
+            target_spent_added = recalculated
+                .iter()
+                .find(|(budget, _)| budget.kategori_id == updated_transaksi.kategori_id)
+                .map(|(budget, spent_before)| budget.spent.unwrap_or(0) - spent_before);
+        } else {
+            let jumlah_diff = updated_transaksi.jumlah - old_transaksi.jumlah;
+    
+            // If category changed, update both old and new category budgets
+            if let Some(new_kategori_id) = payload.kategori_id {
+                if new_kategori_id != old_transaksi.kategori_id {
+                    // Subtract from old category budget
+                    sqlx::query(
+                        "UPDATE budgets SET spent = GREATEST(COALESCE(spent, 0) - $1, 0), updated_at = NOW() WHERE user_id = $2 AND kategori_id = $3"
+                    )
+                    .bind(old_transaksi.jumlah)
+                    .bind(user_uuid)
+                    .bind(old_transaksi.kategori_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|err| {
+                        eprintln!("Database error: {:?}", err);
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            Json(json!({
+                                "status": "error",
+                                "message": "Gagal mengupdate budget."
+                            }))
+                        )
+                    })?;
+
+                    // Add to new category budget
+                    sqlx::query(
+                        "UPDATE budgets SET spent = COALESCE(spent, 0) + $1, updated_at = NOW() WHERE user_id = $2 AND kategori_id = $3"
+                    )
+                    .bind(updated_transaksi.jumlah)
+                    .bind(user_uuid)
+                    .bind(new_kategori_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|err| {
+                        eprintln!("Database error: {:?}", err);
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            Json(json!({
+                                "status": "error",
+                                "message": "Gagal mengupdate budget."
+                            }))
+                        )
+                    })?;
+                } else {
+                    // Same category, just update the difference
+                    sqlx::query(
+                        "UPDATE budgets SET spent = COALESCE(spent, 0) + $1, updated_at = NOW() WHERE user_id = $2 AND kategori_id = $3"
+                    )
+                    .bind(jumlah_diff)
+                    .bind(user_uuid)
+                    .bind(old_transaksi.kategori_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|err| {
+                        eprintln!("Database error: {:?}", err);
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            Json(json!({
+                                "status": "error",
+                                "message": "Gagal mengupdate budget."
+                            }))
+                        )
+                    })?;
+                }
+            } else {
+                // Category not changed, just update the amount difference
+                sqlx::query(
+                    "UPDATE budgets SET spent = COALESCE(spent, 0) + $1, updated_at = NOW() WHERE user_id = $2 AND kategori_id = $3"
+                )
+                .bind(jumlah_diff)
+                .bind(user_uuid)
+                .bind(old_transaksi.kategori_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|err| {
+                    eprintln!("Database error: {:?}", err);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({
+                            "status": "error",
+                            "message": "Gagal mengupdate budget."
+                        }))
+                    )
+                })?;
+            }
+        }
+    }
+
+    // Cek apakah budget kategori tujuan baru saja mencapai 100% (untuk event webhook)
+    let exceeded_budget = if old_transaksi.tipe == TIPE_PENGELUARAN {
+        let budget = sqlx::query_as::<_, Budget>("SELECT * FROM budgets WHERE user_id = $1 AND kategori_id = $2")
+            .bind(user_uuid)
+            .bind(updated_transaksi.kategori_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|err| {
+                eprintln!("Database error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Terjadi kesalahan pada server."
+                    }))
+                )
+            })?;
+
+        let added = target_spent_added.unwrap_or(if updated_transaksi.kategori_id != old_transaksi.kategori_id {
+            updated_transaksi.jumlah
+        } else {
+            updated_transaksi.jumlah - old_transaksi.jumlah
+        });
+        budget.filter(|budget| crossed_threshold(budget.spent.unwrap_or(0) - added, budget))
+    } else {
+        None
+    };
+
+    // Ganti tag jika dikirim, lalu ambil tag terbaru untuk response
+    if let Some(tags) = &tags {
+        set_transaksi_tags(&mut tx, user_uuid, updated_transaksi.id, tags).await.map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Gagal menyimpan tag."
+                }))
+            )
+        })?;
+    }
+
+    let current_tags = get_transaksi_tags(&mut tx, updated_transaksi.id).await.map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    // Commit transaction
+    tx.commit().await.map_err(|err| {
+        eprintln!("Transaction commit error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal menyimpan perubahan."
+            }))
+        )
+    })?;
+
+    if let Some(budget) = exceeded_budget {
+        emit_budget_exceeded(db.clone(), webhook_client.clone(), user_uuid, budget);
+    }
+
+    // Response sukses
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Transaksi berhasil diupdate!",
+        "data": updated_transaksi,
+        "tags": current_tags
+    })))
+}
+
+// Delete transaction
+pub async fn delete_transaksi(
+    State(db): State<Database>,
+    Path((user_id, transaksi_id)): Path<(String, i32)>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    // Start transaction to update budget spent
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    // Cek apakah transaksi exists dan belongs to user. Baris dikunci supaya dua delete bersamaan
+    // tidak sama-sama mengurangi spent; request kedua menunggu lalu mendapat 404
+    let existing_transaksi = sqlx::query_as::<_, Transaksi>(
+        "SELECT * FROM transaksi WHERE id = $1 AND user_id = $2 FOR UPDATE"
+    )
+    .bind(transaksi_id)
+    .bind(user_uuid)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    if existing_transaksi.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Transaksi tidak ditemukan."
+            }))
+        ));
+    }
+
+    let transaksi = existing_transaksi.unwrap();
+
+    // Delete transaksi
+    let deleted = sqlx::query("DELETE FROM transaksi WHERE id = $1")
+        .bind(transaksi_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Gagal menghapus transaksi."
+                }))
+            )
+        })?;
+
+    if deleted.rows_affected() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Transaksi tidak ditemukan."
+            }))
+        ));
+    }
+
+    // Spent dihitung ulang dari transaksi di periode budget berjalan (pemasukan tidak mempengaruhi budget);
+    // transaksi bulan lalu atau dengan override tidak boleh mengurangi spent periode berjalan
+    if transaksi.tipe == TIPE_PENGELUARAN {
+        recalculate_spent_with_periods(&mut tx, user_uuid, &[transaksi.kategori_id], &[counted_period(transaksi.budget_period_start, transaksi.tanggal)])
+            .await
+            .map_err(|err| {
+                eprintln!("Database error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Gagal mengupdate budget."
+                    }))
+                )
+            })?;
+    }
+
+    // Commit transaction
+    tx.commit().await.map_err(|err| {
+        eprintln!("Transaction commit error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal menyimpan perubahan."
+            }))
+        )
+    })?;
+
+    // Response sukses
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Transaksi berhasil dihapus!"
+    })))
+}
+
+// Undo transaksi terakhir: hapus transaksi yang paling baru dibuat (created_at lalu id) dan hitung ulang
+// spent budget kategorinya. Transaksi tidak memiliki soft delete, jadi baris dihapus permanen seperti
+// delete_transaksi; data lengkapnya dikembalikan agar UI bisa menampilkan detailnya.
+// Undo berturut-turut ditolak 409: undo hanya boleh jika ada transaksi baru sejak undo terakhir
+pub async fn undo_last_transaksi(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    // Baris dikunci supaya dua undo bersamaan tidak menghapus dua transaksi berbeda
+    let last_transaksi = sqlx::query_as::<_, Transaksi>(
+        "SELECT * FROM transaksi WHERE user_id = $1 ORDER BY created_at DESC, id DESC LIMIT 1 FOR UPDATE"
+    )
+    .bind(user_uuid)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    let transaksi = match last_transaksi {
+        Some(transaksi) => transaksi,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "error",
+                    "message": "Tidak ada transaksi yang bisa dibatalkan."
+                }))
+            ));
+        }
+    };
+
+    // Aksi terakhir adalah undo jika undo tercatat setelah transaksi terbaru dibuat
+    let already_undone = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM audit_log WHERE user_id = $1 AND event_type = $2 AND created_at >= $3)"
+    )
+    .bind(user_uuid)
+    .bind(EVENT_TRANSAKSI_UNDO)
+    .bind(transaksi.created_at)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    if already_undone {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "status": "error",
+                "message": "Aksi terakhir sudah berupa undo."
+            }))
+        ));
+    }
+
+    sqlx::query("DELETE FROM transaksi WHERE id = $1")
+        .bind(transaksi.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Gagal menghapus transaksi."
+                }))
+            )
+        })?;
+
+    // Spent dihitung ulang dari transaksi di periode budget berjalan (pemasukan tidak mempengaruhi budget)
+    if transaksi.tipe == TIPE_PENGELUARAN {
+        recalculate_spent_with_periods(&mut tx, user_uuid, &[transaksi.kategori_id], &[counted_period(transaksi.budget_period_start, transaksi.tanggal)])
+            .await
+            .map_err(|err| {
+                eprintln!("Database error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Gagal mengupdate budget."
+                    }))
+                )
+            })?;
+    }
+
+    let detail = json!({
+        "transaksi_id": transaksi.id,
+        "jumlah": transaksi.jumlah,
+        "kategori_id": transaksi.kategori_id
+    });
+    audit::record_in_tx(&mut tx, user_uuid, EVENT_TRANSAKSI_UNDO, client_ip(&headers).as_deref(), Some(detail))
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    tx.commit().await.map_err(|err| {
+        eprintln!("Transaction commit error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal menyimpan perubahan."
+            }))
+        )
+    })?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Transaksi terakhir berhasil dibatalkan.",
+        "data": transaksi
+    })))
+}
+
+// Split transaction: hapus transaksi asal dan ganti dengan beberapa transaksi per kategori
+// yang jumlahnya persis sama dengan transaksi asal
+pub async fn split_transaksi(
+    State(db): State<Database>,
+    State(webhook_client): State<WebhookClient>,
+    Path((user_id, transaksi_id)): Path<(String, i32)>,
+    JsonBody(payload): JsonBody<SplitTransaksiRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    // Cek apakah transaksi exists dan belongs to user. Baris dikunci sampai split selesai supaya dua split
+    // bersamaan tidak sama-sama menggandakan bagian; request kedua menunggu lalu mendapat 404
+    let original = sqlx::query_as::<_, Transaksi>(
+        "SELECT * FROM transaksi WHERE id = $1 AND user_id = $2 FOR UPDATE"
+    )
+    .bind(transaksi_id)
+    .bind(user_uuid)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Transaksi tidak ditemukan."
+            }))
+        )
+    })?;
+
+    if payload.splits.len() < 2 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "Split membutuhkan minimal 2 bagian."
+            }))
+        ));
+    }
+
+    let mut errors = FieldErrors::new();
+    let mut deskripsi_list = Vec::with_capacity(payload.splits.len());
+
+    // Kesalahan per bagian dilaporkan di field "splits" dengan nomor bagiannya
+    for (index, split) in payload.splits.iter().enumerate() {
+        let bagian = index + 1;
+
+        if split.jumlah <= 0 {
+            errors.push("splits", format!("Bagian {}: Jumlah harus lebih dari 0.", bagian));
+        }
+
+        let deskripsi = match split.deskripsi.as_deref() {
+            Some(deskripsi) => match required_text("deskripsi", deskripsi, MAX_DESKRIPSI_LEN) {
+                Ok(deskripsi) => deskripsi.to_string(),
+                Err(err) => {
+                    errors.push("splits", format!("Bagian {}: {}", bagian, err.message));
+                    String::new()
+                }
+            },
+            None => original.deskripsi.clone(),
+        };
+        deskripsi_list.push(deskripsi);
+
+        let kategori = kategori_access(&db, split.kategori_id, user_uuid)
+            .await
+            .map_err(|err| {
+                eprintln!("Database error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Terjadi kesalahan pada server."
+                    }))
+                )
+            })?;
+
+        match kategori {
+            KategoriAccess::Allowed => {}
+            KategoriAccess::NotFound => {
+                errors.push("splits", format!("Bagian {}: Kategori tidak ditemukan.", bagian));
+                continue;
+            }
+            KategoriAccess::Forbidden => return Err(forbidden_kategori_response()),
+        }
+
+        // Override periode budget ikut ke setiap bagian, jadi periode itu harus ada di kategori tujuannya
+        if let Some(period_start) = original.budget_period_start {
+            let known_period = is_budget_period(&db, user_uuid, split.kategori_id, period_start)
+                .await
+                .map_err(|err| {
+                    eprintln!("Database error: {:?}", err);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({
+                            "status": "error",
+                            "message": "Terjadi kesalahan pada server."
+                        }))
+                    )
+                })?;
+            if !known_period {
+                errors.push("splits", format!("Bagian {}: Periode budget {} tidak ada di kategori ini.", bagian, period_start));
+            }
+        }
+    }
+
+    errors.into_result()?;
+
+    // Total split harus persis sama dengan transaksi asal
+    let total_split: i64 = payload.splits.iter().map(|split| split.jumlah as i64).sum();
+    if total_split != original.jumlah as i64 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": format!(
+                    "Total split ({}) harus sama dengan jumlah transaksi asal ({}).",
+                    total_split,
+                    original.jumlah
+                )
+            }))
+        ));
+    }
+
+    // Tag transaksi asal ikut disalin ke setiap bagian split
+    let tags = get_transaksi_tags(&mut tx, original.id).await.map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    let deleted = sqlx::query("DELETE FROM transaksi WHERE id = $1")
+        .bind(original.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Gagal menghapus transaksi."
+                }))
+            )
+        })?;
+
+    if deleted.rows_affected() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Transaksi tidak ditemukan."
+            }))
+        ));
+    }
+
+    let mut new_transaksi = Vec::with_capacity(payload.splits.len());
+    for (split, deskripsi) in payload.splits.iter().zip(deskripsi_list) {
+        // Tanggal, catatan, struk, tipe, minor unit, dan override periode budget mengikuti transaksi asal
+        let transaksi = sqlx::query_as::<_, Transaksi>(
+            "INSERT INTO transaksi (user_id, kategori_id, jumlah, deskripsi, tanggal, note, receipt_url, tipe, minor_unit, budget_period_start) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *"
+        )
+        .bind(user_uuid)
+        .bind(split.kategori_id)
+        .bind(split.jumlah)
+        .bind(deskripsi)
+        .bind(original.tanggal)
+        .bind(&original.note)
+        .bind(&original.receipt_url)
+        .bind(&original.tipe)
+        .bind(original.minor_unit)
+        .bind(original.budget_period_start)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Gagal membuat transaksi."
+                }))
+            )
+        })?;
+
+        if !tags.is_empty() {
+            set_transaksi_tags(&mut tx, user_uuid, transaksi.id, &tags).await.map_err(|err| {
+                eprintln!("Database error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Gagal menyimpan tag."
+                    }))
+                )
+            })?;
+        }
+
+        new_transaksi.push(transaksi);
+    }
+
+    // Spent kategori asal dan tujuan dihitung ulang (hanya pengeluaran): transaksi asal bisa berada di
+    // periode lama atau memakai override, jadi selisihnya belum tentu masuk spent berjalan
+    let mut exceeded_budgets = Vec::new();
+    if original.tipe == TIPE_PENGELUARAN {
+        let mut kategori_ids: Vec<i32> = payload.splits.iter().map(|split| split.kategori_id).collect();
+        kategori_ids.push(original.kategori_id);
+        kategori_ids.sort_unstable();
+        kategori_ids.dedup();
+
+        let recalculated = recalculate_spent_with_periods(&mut tx, user_uuid, &kategori_ids, &[counted_period(original.budget_period_start, original.tanggal)])
+            .await
+            .map_err(|err| {
+                eprintln!("Database error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Gagal mengupdate budget."
+                    }))
+                )
+            })?;
+        exceeded_budgets.extend(
+            recalculated
+                .into_iter()
+                .filter(|(budget, spent_before)| crossed_threshold(*spent_before, budget))
+                .map(|(budget, _)| budget),
+        );
+    }
+
+    // Commit transaction
+    tx.commit().await.map_err(|err| {
+        eprintln!("Transaction commit error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal menyimpan perubahan."
+            }))
+        )
+    })?;
+
+    // Kirim event webhook untuk budget yang baru saja mencapai 100%
+    for budget in exceeded_budgets {
+        emit_budget_exceeded(db.clone(), webhook_client.clone(), user_uuid, budget);
+    }
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": format!("Transaksi berhasil dipecah menjadi {} bagian.", new_transaksi.len()),
+        "data": new_transaksi,
+        "tags": tags
+    })))
+}
+
+// Quick input: parse teks seperti "kopi 25000 makanan" lalu buat transaksi lewat create_transaksi.
+// Transaksi dicatat sebagai pengeluaran hari ini; hasil parse ikut dikembalikan untuk konfirmasi.
+pub async fn quick_transaksi(
+    State(db): State<Database>,
+    State(webhook_client): State<WebhookClient>,
+    Path(user_id): Path<String>,
+    headers: HeaderMap,
+    JsonBody(payload): JsonBody<QuickTransaksiRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let parsed = parse_quick_input(&payload.text).map_err(|message| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": message
+            }))
+        )
+    })?;
+
+    // Cari kategori yang terlihat oleh user (sistem atau milik sendiri) tanpa membedakan huruf besar/kecil
+    let matches = sqlx::query_as::<_, Kategori>(
+        "SELECT * FROM categories WHERE LOWER(nama) = LOWER($1) AND (user_id IS NULL OR user_id = $2)"
+    )
+    .bind(&parsed.kategori_nama)
+    .bind(user_uuid)
+    .fetch_all(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    let (kategori, kategori_dibuat) = match matches.as_slice() {
+        [kategori] => (kategori.clone(), false),
+        [] if payload.create_kategori.unwrap_or(false) => {
+            required_text("kategori", &parsed.kategori_nama, MAX_NAMA_LEN).map_err(FieldError::into_response)?;
+
+            // Kategori baru belum punya budget, jadi pengeluaran pasti ditolak tanpa auto_budget_amount
+            if payload.auto_budget_amount.is_none() {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({
+                        "status": "error",
+                        "message": format!("Kategori '{}' akan dibuat baru dan belum memiliki budget. Kirim auto_budget_amount untuk membuat budget sekaligus.", parsed.kategori_nama)
+                    }))
+                ));
+            }
+
+            let mut tx = db.begin().await.map_err(|err| {
+                eprintln!("Transaction error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Terjadi kesalahan pada server."
+                    }))
+                )
+            })?;
+
+            let kategori = sqlx::query_as::<_, Kategori>(
+                "INSERT INTO categories (nama, user_id) VALUES ($1, $2) RETURNING *"
+            )
+            .bind(&parsed.kategori_nama)
+            .bind(user_uuid)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|err| {
+                eprintln!("Database error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Gagal membuat kategori."
+                    }))
+                )
+            })?;
+
+            check_kategori_limit(&mut tx, user_uuid).await?;
+
+            tx.commit().await.map_err(|err| {
+                eprintln!("Transaction commit error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Gagal menyimpan kategori."
+                    }))
+                )
+            })?;
+            (kategori, true)
+        }
+        [] => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("Kategori '{}' tidak ditemukan. Kirim create_kategori: true untuk membuatnya.", parsed.kategori_nama)
+                }))
+            ));
+        }
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("Input ambigu: ada lebih dari satu kategori bernama '{}'.", parsed.kategori_nama)
+                }))
+            ));
+        }
+    };
+
+    let request = CreateTransaksiRequest {
+        kategori_id: Some(kategori.id),
+        jumlah: parsed.jumlah,
+        deskripsi: parsed.deskripsi.clone().unwrap_or_else(|| kategori.nama.clone()),
+        tanggal: Local::now().naive_local().date().format("%Y-%m-%d").to_string(),
+        note: None,
+        receipt_url: None,
+        tipe: None,
+        tags: None,
+        auto_budget_amount: payload.auto_budget_amount,
+        budget_period_start: None,
+    };
+
+    let Json(mut body) = create_transaksi(State(db), State(webhook_client), Path(user_id), headers, JsonBody(request)).await?;
+    body["parsed"] = json!({
+        "deskripsi": parsed.deskripsi,
+        "jumlah": parsed.jumlah,
+        "kategori_id": kategori.id,
+        "kategori_nama": kategori.nama,
+        "kategori_dibuat": kategori_dibuat
+    });
+
+    Ok(Json(body))
+}
+
+// Get transaction by ID
+pub async fn get_transaksi_by_id(
+    State(db): State<Database>,
+    Path((user_id, transaksi_id)): Path<(String, i32)>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let transaksi = sqlx::query_as::<_, TransaksiWithCategory>(
+        r#"
+        SELECT 
+            t.id,
+            t.user_id::text as user_id,
+            t.kategori_id,
+            c.nama as kategori_nama,
+            t.jumlah,
+            t.minor_unit,
+            t.deskripsi,
+            t.tanggal,
+            t.note,
+            t.receipt_url,
+            t.tipe,
+            ARRAY(
+                SELECT tg.nama FROM transaksi_tags tt
+                JOIN tags tg ON tg.id = tt.tag_id
+                WHERE tt.transaksi_id = t.id
+                ORDER BY tg.nama
+            ) as tags,
+            t.created_at,
+            t.updated_at
+        FROM transaksi t
+        JOIN categories c ON t.kategori_id = c.id
+        WHERE t.id = $1 AND t.user_id = $2
+        "#
+    )
+    .bind(transaksi_id)
+    .bind(user_uuid)
+    .fetch_optional(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    match transaksi {
+        Some(mut transaksi) => {
+            let preferences = fetch_preferences(&db, user_uuid).await.unwrap_or_default();
+            transaksi.jumlah_formatted = format_amount(transaksi.jumlah as i64, transaksi.minor_unit, &preferences);
+            Ok(Json(json!({
+                "status": "success",
+                "data": transaksi
+            })))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Transaksi tidak ditemukan."
+            }))
+        ))
+    }
+}
+
+// Get categories the user actually spends in (for filter dropdowns); pemasukan tidak dihitung
+pub async fn get_categories_used(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    Query(query): Query<DateRangeQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    // Parse rentang tanggal opsional
+    let start_date = parse_optional_date(&query.start_date)?;
+    let end_date = parse_optional_date(&query.end_date)?;
+
+    let categories = sqlx::query_as::<_, KategoriUsage>(
+        r#"
+        SELECT
+            c.id as kategori_id,
+            c.nama as kategori_nama,
+            COUNT(t.id) as jumlah_transaksi,
+            SUM(t.jumlah)::BIGINT as total
+        FROM transaksi t
+        JOIN categories c ON t.kategori_id = c.id
+        WHERE t.user_id = $1
+            AND t.tipe = 'pengeluaran'
+            AND ($2::date IS NULL OR t.tanggal >= $2)
+            AND ($3::date IS NULL OR t.tanggal <= $3)
+        GROUP BY c.id, c.nama
+        ORDER BY total DESC, c.nama ASC
+        "#
+    )
+    .bind(user_uuid)
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": categories
+    })))
+}
+
+// Get a single day's spending grouped by category (detail hari di kalender)
+pub async fn get_transaksi_by_day(
+    State(db): State<Database>,
+    Path((user_id, date)): Path<(String, String)>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    // Parse tanggal
+    let tanggal = match NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Format tanggal tidak valid. Gunakan format YYYY-MM-DD."
+                }))
+            ));
+        }
+    };
+
+    let transaksi = sqlx::query_as::<_, TransaksiWithCategory>(
+        r#"
+        SELECT 
+            t.id,
+            t.user_id::text as user_id,
+            t.kategori_id,
+            c.nama as kategori_nama,
+            t.jumlah,
+            t.minor_unit,
+            t.deskripsi,
+            t.tanggal,
+            t.note,
+            t.receipt_url,
+            t.tipe,
+            ARRAY(
+                SELECT tg.nama FROM transaksi_tags tt
+                JOIN tags tg ON tg.id = tt.tag_id
+                WHERE tt.transaksi_id = t.id
+                ORDER BY tg.nama
+            ) as tags,
+            t.created_at,
+            t.updated_at
+        FROM transaksi t
+        JOIN categories c ON t.kategori_id = c.id
+        WHERE t.user_id = $1 AND t.tanggal = $2 AND t.tipe = 'pengeluaran'
+        ORDER BY c.nama ASC, t.kategori_id ASC, t.id ASC
+        "#
+    )
+    .bind(user_uuid)
+    .bind(tanggal)
+    .fetch_all(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    let preferences = fetch_preferences(&db, user_uuid).await.unwrap_or_default();
+
+    // Baris sudah terurut per kategori, jadi cukup mulai grup baru saat kategori berganti
+    let mut kategori: Vec<TransaksiPerKategori> = Vec::new();
+    for mut item in transaksi {
+        item.jumlah_formatted = format_amount(item.jumlah as i64, item.minor_unit, &preferences);
+
+        match kategori.last_mut() {
+            Some(group) if group.kategori_id == item.kategori_id => {
+                group.subtotal += item.jumlah as i64;
+                group.transaksi.push(item);
+            }
+            _ => kategori.push(TransaksiPerKategori {
+                kategori_id: item.kategori_id,
+                kategori_nama: item.kategori_nama.clone(),
+                subtotal: item.jumlah as i64,
+                transaksi: vec![item],
+            }),
+        }
+    }
+
+    let total: i64 = kategori.iter().map(|group| group.subtotal).sum();
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": {
+            "tanggal": tanggal,
+            "total": total,
+            "kategori": kategori
+        }
+    })))
+}
+
+// Get the categories most recently used by a user (quick-pick saat input transaksi)
+pub async fn get_recent_categories(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    Query(query): Query<RecentKategoriQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let limit = query.limit.unwrap_or(5).clamp(1, 20);
+
+    // Satu baris per kategori, diurutkan dari pengeluaran terbaru (pemasukan tidak dihitung)
+    let categories = sqlx::query_as::<_, RecentKategori>(
+        r#"
+        SELECT
+            c.id as kategori_id,
+            c.nama as kategori_nama,
+            MAX(t.tanggal) as terakhir_digunakan
+        FROM transaksi t
+        JOIN categories c ON t.kategori_id = c.id
+        WHERE t.user_id = $1 AND t.tipe = 'pengeluaran'
+        GROUP BY c.id, c.nama
+        ORDER BY MAX(t.tanggal) DESC, MAX(t.created_at) DESC NULLS LAST, c.id DESC
+        LIMIT $2
+        "#
+    )
+    .bind(user_uuid)
+    .bind(limit)
+    .fetch_all(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": categories
+    })))
+}
+
+// Move all of a user's transactions from one category to another
+pub async fn reassign_transaksi_kategori(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    Json(payload): Json<ReassignKategoriRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    if payload.from_kategori_id == payload.to_kategori_id {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "Kategori asal dan tujuan tidak boleh sama."
+            }))
+        ));
+    }
+
+    // Kedua kategori harus ada dan terlihat oleh user (kategori sistem atau milik user)
+    let visible_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM categories WHERE id IN ($1, $2) AND (user_id IS NULL OR user_id = $3)"
+    )
+    .bind(payload.from_kategori_id)
+    .bind(payload.to_kategori_id)
+    .bind(user_uuid)
+    .fetch_one(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    if visible_count != 2 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "Kategori tidak ditemukan."
+            }))
+        ));
+    }
+
     let mut tx = db.begin().await.map_err(|err| {
         eprintln!("Transaction error: {:?}", err);
         (
@@ -462,22 +2483,15 @@ pub async fn update_transaksi(
         )
     })?;
 
-    // Update transaksi
-    let updated_transaksi = sqlx::query_as::<_, Transaksi>(
-        r#"UPDATE transaksi SET 
-           kategori_id = COALESCE($1, kategori_id),
-           jumlah = COALESCE($2, jumlah),
-           deskripsi = COALESCE($3, deskripsi),
-           tanggal = COALESCE($4, tanggal),
-           updated_at = NOW() 
-           WHERE id = $5 RETURNING *"#
+    // Pindahkan semua transaksi
+    let moved_amounts = sqlx::query_as::<_, (i32, String, NaiveDate)>(
+        r#"UPDATE transaksi SET kategori_id = $1, updated_at = NOW() WHERE user_id = $2 AND kategori_id = $3
+           RETURNING jumlah, tipe, COALESCE(budget_period_start, tanggal)"#
     )
-    .bind(payload.kategori_id)
-    .bind(payload.jumlah)
-    .bind(payload.deskripsi.as_ref().map(|s| s.trim()))
-    .bind(tanggal)
-    .bind(transaksi_id)
-    .fetch_one(&mut *tx)
+    .bind(payload.to_kategori_id)
+    .bind(user_uuid)
+    .bind(payload.from_kategori_id)
+    .fetch_all(&mut *tx)
     .await
     .map_err(|err| {
         eprintln!("Database error: {:?}", err);
@@ -485,65 +2499,26 @@ pub async fn update_transaksi(
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({
                 "status": "error",
-                "message": "Gagal mengupdate transaksi."
+                "message": "Gagal memindahkan transaksi."
             }))
         )
     })?;
 
-    // Update budget spent - subtract old amount and add new amount
-    let jumlah_diff = updated_transaksi.jumlah - old_transaksi.jumlah;
-    
-    // If category changed, update both old and new category budgets
-    if let Some(new_kategori_id) = payload.kategori_id {
-        if new_kategori_id != old_transaksi.kategori_id {
-            // Subtract from old category budget
-            sqlx::query(
-                "UPDATE budgets SET spent = GREATEST(COALESCE(spent, 0) - $1, 0), updated_at = NOW() WHERE user_id = $2 AND kategori_id = $3"
-            )
-            .bind(old_transaksi.jumlah)
-            .bind(user_uuid)
-            .bind(old_transaksi.kategori_id)
-            .execute(&mut *tx)
-            .await
-            .map_err(|err| {
-                eprintln!("Database error: {:?}", err);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({
-                        "status": "error",
-                        "message": "Gagal mengupdate budget."
-                    }))
-                )
-            })?;
+    let moved_count = moved_amounts.len();
+    // Hanya pengeluaran yang dihitung ke spent budget
+    let moved_total: i64 = moved_amounts.iter()
+        .filter(|(_, tipe, _)| tipe == TIPE_PENGELUARAN)
+        .map(|(jumlah, _, _)| *jumlah as i64)
+        .sum();
 
-            // Add to new category budget
-            sqlx::query(
-                "UPDATE budgets SET spent = COALESCE(spent, 0) + $1, updated_at = NOW() WHERE user_id = $2 AND kategori_id = $3"
-            )
-            .bind(updated_transaksi.jumlah)
-            .bind(user_uuid)
-            .bind(new_kategori_id)
-            .execute(&mut *tx)
-            .await
-            .map_err(|err| {
-                eprintln!("Database error: {:?}", err);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({
-                        "status": "error",
-                        "message": "Gagal mengupdate budget."
-                    }))
-                )
-            })?;
-        } else {
-            // Same category, just update the difference
-            sqlx::query(
-                "UPDATE budgets SET spent = COALESCE(spent, 0) + $1, updated_at = NOW() WHERE user_id = $2 AND kategori_id = $3"
-            )
-            .bind(jumlah_diff)
-            .bind(user_uuid)
-            .bind(old_transaksi.kategori_id)
-            .execute(&mut *tx)
+    if moved_total > 0 {
+        // Spent periode berjalan dan snapshot periode lama yang tersentuh dihitung ulang untuk kedua kategori
+        let periods: Vec<NaiveDate> = moved_amounts.iter()
+            .filter(|(_, tipe, _)| tipe == TIPE_PENGELUARAN)
+            .map(|(_, _, tanggal)| period_start_for(*tanggal))
+            .collect();
+
+        recalculate_spent_with_periods(&mut tx, user_uuid, &[payload.from_kategori_id, payload.to_kategori_id], &periods)
             .await
             .map_err(|err| {
                 eprintln!("Database error: {:?}", err);
@@ -555,27 +2530,6 @@ pub async fn update_transaksi(
                     }))
                 )
             })?;
-        }
-    } else {
-        // Category not changed, just update the amount difference
-        sqlx::query(
-            "UPDATE budgets SET spent = COALESCE(spent, 0) + $1, updated_at = NOW() WHERE user_id = $2 AND kategori_id = $3"
-        )
-        .bind(jumlah_diff)
-        .bind(user_uuid)
-        .bind(old_transaksi.kategori_id)
-        .execute(&mut *tx)
-        .await
-        .map_err(|err| {
-            eprintln!("Database error: {:?}", err);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({
-                    "status": "error",
-                    "message": "Gagal mengupdate budget."
-                }))
-            )
-        })?;
     }
 
     // Commit transaction
@@ -590,18 +2544,23 @@ pub async fn update_transaksi(
         )
     })?;
 
-    // Response sukses
     Ok(Json(json!({
         "status": "success",
-        "message": "Transaksi berhasil diupdate!",
-        "data": updated_transaksi
+        "message": format!("{} transaksi berhasil dipindahkan.", moved_count),
+        "data": {
+            "moved": moved_count,
+            "total_jumlah": moved_total
+        }
     })))
 }
 
-// Delete transaction
-pub async fn delete_transaksi(
+// Terapkan aturan kategori user ke semua transaksinya dalam satu DB transaction.
+// Transaksi yang deskripsinya cocok dengan aturan tapi kategorinya berbeda dipindahkan,
+// lalu spent budget kategori asal dan tujuan dihitung ulang
+pub async fn auto_categorize_transaksi(
     State(db): State<Database>,
-    Path((user_id, transaksi_id)): Path<(String, i32)>,
+    State(webhook_client): State<WebhookClient>,
+    Path(user_id): Path<String>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Parse user_id as UUID
     let user_uuid = match Uuid::parse_str(&user_id) {
@@ -617,16 +2576,8 @@ pub async fn delete_transaksi(
         }
     };
 
-    // Cek apakah transaksi exists dan belongs to user
-    let existing_transaksi = sqlx::query_as::<_, Transaksi>(
-        "SELECT * FROM transaksi WHERE id = $1 AND user_id = $2"
-    )
-    .bind(transaksi_id)
-    .bind(user_uuid)
-    .fetch_optional(&db)
-    .await
-    .map_err(|err| {
-        eprintln!("Database error: {:?}", err);
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({
@@ -636,65 +2587,55 @@ pub async fn delete_transaksi(
         )
     })?;
 
-    if existing_transaksi.is_none() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(json!({
-                "status": "error",
-                "message": "Transaksi tidak ditemukan."
-            }))
-        ));
-    }
-
-    let transaksi = existing_transaksi.unwrap();
-
-    // Start transaction to update budget spent
-    let mut tx = db.begin().await.map_err(|err| {
-        eprintln!("Transaction error: {:?}", err);
+    // Aturan yang menang per transaksi sama dengan matching_kategori: pattern terpanjang, lalu id terkecil
+    let moved = sqlx::query_as::<_, (i32, i32)>(
+        r#"
+        WITH matched AS (
+            SELECT DISTINCT ON (t.id) t.id, t.kategori_id as old_kategori_id, r.kategori_id as new_kategori_id
+            FROM transaksi t
+            JOIN categorization_rules r
+                ON r.user_id = t.user_id AND POSITION(LOWER(r.pattern) IN LOWER(t.deskripsi)) > 0
+            WHERE t.user_id = $1
+            ORDER BY t.id, LENGTH(r.pattern) DESC, r.id ASC
+        )
+        UPDATE transaksi t SET kategori_id = matched.new_kategori_id, updated_at = NOW()
+        FROM matched
+        WHERE t.id = matched.id AND matched.new_kategori_id <> matched.old_kategori_id
+        RETURNING matched.old_kategori_id, matched.new_kategori_id
+        "#
+    )
+    .bind(user_uuid)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({
                 "status": "error",
-                "message": "Terjadi kesalahan pada server."
+                "message": "Gagal memindahkan transaksi."
             }))
         )
     })?;
 
-    // Delete transaksi
-    sqlx::query("DELETE FROM transaksi WHERE id = $1")
-        .bind(transaksi_id)
-        .execute(&mut *tx)
-        .await
-        .map_err(|err| {
+    let mut kategori_ids: Vec<i32> = moved.iter().flat_map(|(from, to)| [*from, *to]).collect();
+    kategori_ids.sort_unstable();
+    kategori_ids.dedup();
+
+    let recalculated = if kategori_ids.is_empty() {
+        Vec::new()
+    } else {
+        recalculate_spent(&mut tx, user_uuid, &kategori_ids).await.map_err(|err| {
             eprintln!("Database error: {:?}", err);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(json!({
                     "status": "error",
-                    "message": "Gagal menghapus transaksi."
+                    "message": "Gagal mengupdate budget."
                 }))
             )
-        })?;
-
-    // Update budget spent - subtract the deleted transaction amount
-    sqlx::query(
-        "UPDATE budgets SET spent = GREATEST(COALESCE(spent, 0) - $1, 0), updated_at = NOW() WHERE user_id = $2 AND kategori_id = $3"
-    )
-    .bind(transaksi.jumlah)
-    .bind(user_uuid)
-    .bind(transaksi.kategori_id)
-    .execute(&mut *tx)
-    .await
-    .map_err(|err| {
-        eprintln!("Database error: {:?}", err);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({
-                "status": "error",
-                "message": "Gagal mengupdate budget."
-            }))
-        )
-    })?;
+        })?
+    };
 
     // Commit transaction
     tx.commit().await.map_err(|err| {
@@ -708,17 +2649,29 @@ pub async fn delete_transaksi(
         )
     })?;
 
-    // Response sukses
+    // Kirim event webhook untuk budget yang baru saja mencapai 100%
+    for (budget, spent_before) in &recalculated {
+        if crossed_threshold(*spent_before, budget) {
+            emit_budget_exceeded(db.clone(), webhook_client.clone(), user_uuid, budget.clone());
+        }
+    }
+
     Ok(Json(json!({
         "status": "success",
-        "message": "Transaksi berhasil dihapus!"
+        "message": format!("{} transaksi berhasil dikategorikan ulang.", moved.len()),
+        "data": {
+            "recategorized": moved.len(),
+            "budgets": recalculated.into_iter().map(|(budget, _)| budget).collect::<Vec<_>>()
+        }
     })))
 }
 
-// Get transaction by ID
-pub async fn get_transaksi_by_id(
+// Delete all (or all before a date) transactions for a user
+pub async fn clear_transaksi(
     State(db): State<Database>,
-    Path((user_id, transaksi_id)): Path<(String, i32)>,
+    Path(user_id): Path<String>,
+    Query(query): Query<ClearTransaksiQuery>,
+    Json(payload): Json<ClearTransaksiRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // Parse user_id as UUID
     let user_uuid = match Uuid::parse_str(&user_id) {
@@ -734,29 +2687,59 @@ pub async fn get_transaksi_by_id(
         }
     };
 
-    let transaksi = sqlx::query_as::<_, TransaksiWithCategory>(
-        r#"
-        SELECT 
-            t.id,
-            t.user_id::text as user_id,
-            t.kategori_id,
-            c.nama as kategori_nama,
-            t.jumlah,
-            t.deskripsi,
-            t.tanggal,
-            t.created_at,
-            t.updated_at
-        FROM transaksi t
-        JOIN categories c ON t.kategori_id = c.id
-        WHERE t.id = $1 AND t.user_id = $2
-        "#
-    )
-    .bind(transaksi_id)
-    .bind(user_uuid)
-    .fetch_optional(&db)
-    .await
-    .map_err(|err| {
-        eprintln!("Database error: {:?}", err);
+    let before = parse_optional_date(&query.before)?;
+
+    if payload.password.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "Password wajib diisi untuk konfirmasi."
+            }))
+        ));
+    }
+
+    // Konfirmasi password user
+    let password_hash = sqlx::query_scalar::<_, String>("SELECT password_hash FROM users WHERE id = $1")
+        .bind(user_uuid)
+        .fetch_optional(&db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    let password_hash = match password_hash {
+        Some(hash) => hash,
+        None => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "error",
+                    "message": "User tidak ditemukan."
+                }))
+            ));
+        }
+    };
+
+    if !verify_password(&payload.password, &password_hash).valid {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(json!({
+                "status": "error",
+                "message": "Password salah."
+            }))
+        ));
+    }
+
+    let mut tx = db.begin().await.map_err(|err| {
+        eprintln!("Transaction error: {:?}", err);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({
@@ -766,17 +2749,67 @@ pub async fn get_transaksi_by_id(
         )
     })?;
 
-    match transaksi {
-        Some(transaksi) => Ok(Json(json!({
-            "status": "success",
-            "data": transaksi
-        }))),
-        None => Err((
-            StatusCode::NOT_FOUND,
+    // (kategori_id, tipe, tanggal efektif) transaksi yang dihapus, untuk menghitung ulang budget yang tersentuh
+    let deleted_rows = sqlx::query_as::<_, (i32, String, NaiveDate)>(
+        r#"DELETE FROM transaksi WHERE user_id = $1 AND ($2::date IS NULL OR tanggal < $2)
+           RETURNING kategori_id, tipe, COALESCE(budget_period_start, tanggal)"#
+    )
+        .bind(user_uuid)
+        .bind(before)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Gagal menghapus transaksi."
+                }))
+            )
+        })?;
+    let deleted = deleted_rows.len();
+
+    // Hitung ulang spent periode berjalan dan snapshot periode lama dari transaksi pengeluaran yang tersisa
+    let pengeluaran = deleted_rows.iter().filter(|(_, tipe, _)| tipe == TIPE_PENGELUARAN);
+    let mut kategori_ids: Vec<i32> = pengeluaran.clone().map(|(kategori_id, _, _)| *kategori_id).collect();
+    kategori_ids.sort_unstable();
+    kategori_ids.dedup();
+    let periods: Vec<NaiveDate> = pengeluaran.map(|(_, _, tanggal)| period_start_for(*tanggal)).collect();
+
+    if !kategori_ids.is_empty() {
+        recalculate_spent_with_periods(&mut tx, user_uuid, &kategori_ids, &periods)
+            .await
+            .map_err(|err| {
+                eprintln!("Database error: {:?}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({
+                        "status": "error",
+                        "message": "Gagal mengupdate budget."
+                    }))
+                )
+            })?;
+    }
+
+    // Commit transaction
+    tx.commit().await.map_err(|err| {
+        eprintln!("Transaction commit error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({
                 "status": "error",
-                "message": "Transaksi tidak ditemukan."
+                "message": "Gagal menyimpan perubahan."
             }))
-        ))
-    }
+        )
+    })?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": format!("{} transaksi berhasil dihapus.", deleted),
+        "data": {
+            "deleted": deleted,
+            "before": before
+        }
+    })))
 }
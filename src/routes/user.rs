@@ -1,26 +1,119 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, Uri},
     response::Json,
 };
 use serde_json::{json, Value};
 use uuid::Uuid;
 
+use crate::audit::{self, client_ip, EVENT_ACCOUNT_DELETED};
 use crate::database::Database;
-use crate::models::user::{User, CreateUser, UpdateUser};
+use crate::jwt::AdminUser;
+use crate::pagination::{offset_links, Pagination};
+use crate::validate::normalize_email;
+use crate::models::user::{User, UserResponse, UserByEmailQuery, CreateUser, UpdateUser};
 
-pub async fn get_users(State(db): State<Database>) -> Result<Json<Value>, StatusCode> {
-    let users = sqlx::query_as::<_, User>("SELECT * FROM users ORDER BY created_at DESC")
-        .fetch_all(&db)
+// Get all users (admin only, tanpa password_hash)
+pub async fn get_users(
+    State(db): State<Database>,
+    _admin: AdminUser,
+    uri: Uri,
+    Pagination { limit, offset }: Pagination,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
+        .fetch_one(&db)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    let users = sqlx::query_as::<_, UserResponse>(
+        "SELECT id, username, email, role, created_at, updated_at FROM users ORDER BY created_at DESC, id DESC LIMIT $1 OFFSET $2"
+    )
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
 
     Ok(Json(json!({
         "status": "success",
-        "data": users
+        "data": users,
+        "pagination": {
+            "total": total,
+            "limit": limit,
+            "offset": offset
+        },
+        "links": offset_links(&uri, limit, offset, total)
     })))
 }
 
+// Cari user berdasarkan email (admin only, untuk kebutuhan support). Guard admin dijalankan
+// sebelum query parameter dibaca, jadi non-admin tidak bisa memakai endpoint ini untuk mengecek email terdaftar
+pub async fn get_user_by_email(
+    State(db): State<Database>,
+    _admin: AdminUser,
+    Query(query): Query<UserByEmailQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let email = query.email.as_deref().map(normalize_email).unwrap_or_default();
+    if email.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "Parameter email wajib diisi."
+            }))
+        ));
+    }
+
+    let user = sqlx::query_as::<_, UserResponse>(
+        "SELECT id, username, email, role, created_at, updated_at FROM users WHERE email = $1"
+    )
+    .bind(&email)
+    .fetch_optional(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Terjadi kesalahan pada server."
+            }))
+        )
+    })?;
+
+    match user {
+        Some(user) => Ok(Json(json!({
+            "status": "success",
+            "data": user
+        }))),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "User tidak ditemukan."
+            }))
+        )),
+    }
+}
+
 pub async fn get_user_by_id(
     State(db): State<Database>,
     Path(id): Path<Uuid>,
@@ -46,7 +139,8 @@ pub async fn get_user_by_id(
                 "id": user.id,
                 "username": user.username,
                 "email": user.email,
-                "created_at": user.created_at
+                "created_at": user.created_at,
+                "updated_at": user.updated_at
             }
         }))),
         None => Err((
@@ -71,7 +165,7 @@ pub async fn create_user(
     )
     .bind(user_id)
     .bind(&payload.username)
-    .bind(&payload.email)
+    .bind(normalize_email(&payload.email))
     .bind(&payload.password) // In production, hash this password!
     .fetch_one(&db)
     .await
@@ -97,7 +191,7 @@ pub async fn update_user(
          RETURNING *"
     )
     .bind(&payload.username)
-    .bind(&payload.email)
+    .bind(payload.email.as_deref().map(normalize_email))
     .bind(id)
     .fetch_optional(&db)
     .await
@@ -112,22 +206,44 @@ pub async fn update_user(
     }
 }
 
+// Hapus akun user beserta seluruh datanya (admin only)
 pub async fn delete_user(
     State(db): State<Database>,
+    admin: AdminUser,
     Path(id): Path<Uuid>,
-) -> Result<Json<Value>, StatusCode> {
+    headers: HeaderMap,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     let result = sqlx::query("DELETE FROM users WHERE id = $1")
         .bind(id)
         .execute(&db)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
 
     if result.rows_affected() == 0 {
-        return Err(StatusCode::NOT_FOUND);
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "User tidak ditemukan."
+            }))
+        ));
     }
 
+    // audit_log tidak memakai foreign key ke users, jadi event ini tetap ada setelah akun terhapus
+    let detail = json!({ "deleted_by": admin.0.user_id });
+    audit::record(&db, id, EVENT_ACCOUNT_DELETED, client_ip(&headers).as_deref(), Some(detail)).await;
+
     Ok(Json(json!({
         "status": "success",
-        "message": "User deleted successfully"
+        "message": "User berhasil dihapus."
     })))
 }
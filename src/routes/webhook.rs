@@ -0,0 +1,235 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::database::Database;
+use crate::extract::JsonBody;
+use crate::models::webhook::{Webhook, CreateWebhookRequest, UpdateWebhookRequest};
+use crate::validate::{required_text, FieldError, MAX_URL_LEN};
+use crate::webhook::{check_webhook_url, generate_secret};
+
+// Batas panjang secret webhook (sesuai kolom webhooks.secret)
+const MAX_SECRET_LEN: usize = 128;
+
+// URL webhook harus http(s) dan mengarah ke alamat publik (server akan mengirim POST ke sana)
+async fn validate_webhook_url(url: &str) -> Result<&str, (StatusCode, Json<Value>)> {
+    let url = required_text("url", url, MAX_URL_LEN).map_err(FieldError::into_response)?;
+    if let Err(message) = check_webhook_url(url).await {
+        return Err(FieldError { field: "url", message }.into_response());
+    }
+
+    Ok(url)
+}
+
+// Get all webhooks for a user
+pub async fn get_user_webhooks(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let webhooks = sqlx::query_as::<_, Webhook>("SELECT * FROM webhooks WHERE user_id = $1 ORDER BY created_at DESC, id DESC")
+        .bind(user_uuid)
+        .fetch_all(&db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Terjadi kesalahan pada server."
+                }))
+            )
+        })?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "data": webhooks
+    })))
+}
+
+// Register new webhook for a user
+pub async fn create_webhook(
+    State(db): State<Database>,
+    Path(user_id): Path<String>,
+    JsonBody(payload): JsonBody<CreateWebhookRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    // Validasi input
+    let url = validate_webhook_url(&payload.url).await?;
+    let secret = match payload.secret.as_deref() {
+        Some(secret) => required_text("secret", secret, MAX_SECRET_LEN)
+            .map_err(FieldError::into_response)?
+            .to_string(),
+        None => generate_secret(),
+    };
+
+    let webhook = sqlx::query_as::<_, Webhook>(
+        "INSERT INTO webhooks (user_id, url, secret) VALUES ($1, $2, $3) RETURNING *"
+    )
+    .bind(user_uuid)
+    .bind(url)
+    .bind(&secret)
+    .fetch_one(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal membuat webhook."
+            }))
+        )
+    })?;
+
+    // Secret hanya dikembalikan sekali di sini
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Webhook berhasil dibuat!",
+        "data": webhook,
+        "secret": secret
+    })))
+}
+
+// Update webhook (URL atau status aktif)
+pub async fn update_webhook(
+    State(db): State<Database>,
+    Path((user_id, webhook_id)): Path<(String, i32)>,
+    Json(payload): Json<UpdateWebhookRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let url = match payload.url.as_deref() {
+        Some(url) => Some(validate_webhook_url(url).await?),
+        None => None,
+    };
+
+    let webhook = sqlx::query_as::<_, Webhook>(
+        "UPDATE webhooks SET url = COALESCE($1, url), is_active = COALESCE($2, is_active), updated_at = NOW() WHERE id = $3 AND user_id = $4 RETURNING *"
+    )
+    .bind(url)
+    .bind(payload.is_active)
+    .bind(webhook_id)
+    .bind(user_uuid)
+    .fetch_optional(&db)
+    .await
+    .map_err(|err| {
+        eprintln!("Database error: {:?}", err);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "status": "error",
+                "message": "Gagal mengupdate webhook."
+            }))
+        )
+    })?;
+
+    match webhook {
+        Some(webhook) => Ok(Json(json!({
+            "status": "success",
+            "message": "Webhook berhasil diupdate!",
+            "data": webhook
+        }))),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Webhook tidak ditemukan."
+            }))
+        ))
+    }
+}
+
+// Delete webhook
+pub async fn delete_webhook(
+    State(db): State<Database>,
+    Path((user_id, webhook_id)): Path<(String, i32)>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Parse user_id as UUID
+    let user_uuid = match Uuid::parse_str(&user_id) {
+        Ok(uuid) => uuid,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid user ID format."
+                }))
+            ));
+        }
+    };
+
+    let result = sqlx::query("DELETE FROM webhooks WHERE id = $1 AND user_id = $2")
+        .bind(webhook_id)
+        .bind(user_uuid)
+        .execute(&db)
+        .await
+        .map_err(|err| {
+            eprintln!("Database error: {:?}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": "Gagal menghapus webhook."
+                }))
+            )
+        })?;
+
+    if result.rows_affected() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Webhook tidak ditemukan."
+            }))
+        ));
+    }
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Webhook berhasil dihapus!"
+    })))
+}
@@ -0,0 +1,47 @@
+use axum::extract::FromRef;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::RwLock;
+
+use crate::database::Database;
+use crate::mail::Mailer;
+use crate::webhook::WebhookClient;
+
+// State aplikasi yang dibagikan ke semua handler; handler cukup mengekstrak
+// bagian yang dibutuhkan, mis. `State<Database>` atau `State<Arc<dyn Mailer>>`
+#[derive(Clone)]
+pub struct AppState {
+    pub db: Database,
+    pub mailer: Arc<dyn Mailer>,
+    pub spending_ranges_cache: SpendingRangesCache,
+    pub webhook_client: WebhookClient,
+}
+
+// Cache hasil agregat GET /api/statistik/ranges (scan global yang mahal): waktu hitung + response data
+#[derive(Clone, Default)]
+pub struct SpendingRangesCache(pub Arc<RwLock<Option<(Instant, Value)>>>);
+
+impl FromRef<AppState> for Database {
+    fn from_ref(state: &AppState) -> Self {
+        state.db.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn Mailer> {
+    fn from_ref(state: &AppState) -> Self {
+        state.mailer.clone()
+    }
+}
+
+impl FromRef<AppState> for SpendingRangesCache {
+    fn from_ref(state: &AppState) -> Self {
+        state.spending_ranges_cache.clone()
+    }
+}
+
+impl FromRef<AppState> for WebhookClient {
+    fn from_ref(state: &AppState) -> Self {
+        state.webhook_client.clone()
+    }
+}
@@ -0,0 +1,264 @@
+use axum::{http::StatusCode, response::Json};
+use serde_json::{json, Value};
+
+use crate::models::profile::{WEEK_START_MONDAY, WEEK_START_SUNDAY};
+
+// Batas panjang input teks (dalam karakter)
+pub const MAX_NAMA_LEN: usize = 60;
+pub const MAX_DESKRIPSI_LEN: usize = 255;
+pub const MAX_NOTE_LEN: usize = 1000;
+pub const MAX_URL_LEN: usize = 2048;
+pub const MAX_TAGS: usize = 10;
+
+// Kesalahan validasi pada satu field request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl FieldError {
+    // Response 400 dengan format {"status": "error", ...}
+    pub fn into_response(self) -> (StatusCode, Json<Value>) {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": self.message,
+                "field": self.field
+            }))
+        )
+    }
+
+    // Response 400 dengan format {"success": false, ...} (dipakai route profile)
+    pub fn into_success_response(self) -> (StatusCode, Json<Value>) {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "success": false,
+                "message": self.message,
+                "field": self.field
+            }))
+        )
+    }
+}
+
+// Kumpulan kesalahan validasi, agar semua field yang salah dilaporkan sekaligus
+#[derive(Debug, Default)]
+pub struct FieldErrors {
+    errors: Vec<FieldError>,
+}
+
+impl FieldErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, field: &'static str, message: impl Into<String>) {
+        self.errors.push(FieldError { field, message: message.into() });
+    }
+
+    // Simpan error (jika ada) dan kembalikan nilai yang valid sebagai Option
+    pub fn check<T>(&mut self, result: Result<T, FieldError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.errors.push(err);
+                None
+            }
+        }
+    }
+
+    // Response 400 dengan `fields` berisi pesan per field; `message` berisi pesan pertama
+    pub fn into_result(self) -> Result<(), (StatusCode, Json<Value>)> {
+        let Some(first) = self.errors.first() else {
+            return Ok(());
+        };
+
+        let message = first.message.clone();
+        let mut fields = serde_json::Map::new();
+        for err in self.errors {
+            // Pesan pertama per field yang dipertahankan
+            fields.entry(err.field).or_insert(Value::String(err.message));
+        }
+
+        Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": message,
+                "fields": fields
+            }))
+        ))
+    }
+}
+
+// Trim teks wajib; error jika kosong setelah trim atau melebihi batas panjang
+pub fn required_text<'a>(field: &'static str, value: &'a str, max_len: usize) -> Result<&'a str, FieldError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(FieldError {
+            field,
+            message: format!("{} wajib diisi.", field),
+        });
+    }
+
+    check_length(field, value, max_len)?;
+    Ok(value)
+}
+
+// Trim teks opsional; teks kosong setelah trim dianggap tidak diisi
+pub fn optional_text<'a>(field: &'static str, value: Option<&'a str>, max_len: usize) -> Result<Option<&'a str>, FieldError> {
+    match value.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => {
+            check_length(field, value, max_len)?;
+            Ok(Some(value))
+        }
+        None => Ok(None),
+    }
+}
+
+// Email disimpan dan dibandingkan dalam bentuk trim + huruf kecil, jadi satu alamat hanya bisa dipakai satu akun
+pub fn normalize_email(value: &str) -> String {
+    value.trim().to_lowercase()
+}
+
+// Normalisasi daftar tag: trim, huruf kecil, spasi dirapikan, duplikat dan tag kosong dibuang
+pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>, FieldError> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        if tag.is_empty() || normalized.contains(&tag) {
+            continue;
+        }
+
+        check_length("tags", &tag, MAX_NAMA_LEN)?;
+        normalized.push(tag);
+    }
+
+    if normalized.len() > MAX_TAGS {
+        return Err(FieldError {
+            field: "tags",
+            message: format!("tags maksimal {} item.", MAX_TAGS),
+        });
+    }
+
+    Ok(normalized)
+}
+
+// Format tanggal yang didukung frontend
+pub const DATE_FORMATS: [&str; 5] = ["DD/MM/YYYY", "MM/DD/YYYY", "YYYY-MM-DD", "DD-MM-YYYY", "DD MMM YYYY"];
+
+// Kode mata uang ISO-4217: tiga huruf, dikembalikan dalam huruf besar
+pub fn currency_code(value: &str) -> Result<String, FieldError> {
+    let value = value.trim();
+    if value.len() != 3 || !value.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(FieldError {
+            field: "currency",
+            message: "currency harus kode ISO-4217 tiga huruf, mis. IDR.".to_string(),
+        });
+    }
+
+    Ok(value.to_ascii_uppercase())
+}
+
+// Tag locale BCP-47 sederhana: bahasa 2-3 huruf diikuti subtag alfanumerik 2-8 karakter, mis. "id-ID"
+pub fn locale_tag(value: &str) -> Result<String, FieldError> {
+    let value = value.trim().replace('_', "-");
+    let mut parts = value.split('-');
+    let language_ok = parts
+        .next()
+        .is_some_and(|language| (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_alphabetic()));
+    let subtags_ok = parts.all(|subtag| (2..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    if !language_ok || !subtags_ok || value.len() > 35 {
+        return Err(FieldError {
+            field: "locale",
+            message: "locale tidak valid, gunakan format seperti id-ID.".to_string(),
+        });
+    }
+
+    Ok(value)
+}
+
+pub fn date_format(value: &str) -> Result<String, FieldError> {
+    let value = value.trim();
+    match DATE_FORMATS.iter().find(|format| format.eq_ignore_ascii_case(value)) {
+        Some(format) => Ok(format.to_string()),
+        None => Err(FieldError {
+            field: "date_format",
+            message: format!("date_format harus salah satu dari: {}.", DATE_FORMATS.join(", ")),
+        }),
+    }
+}
+
+// Hari pertama minggu: "monday" atau "sunday" (tanpa membedakan huruf besar/kecil)
+pub fn week_start(value: &str) -> Result<String, FieldError> {
+    let value = value.trim().to_ascii_lowercase();
+    if value != WEEK_START_MONDAY && value != WEEK_START_SUNDAY {
+        return Err(FieldError {
+            field: "week_start",
+            message: format!("week_start harus '{}' atau '{}'.", WEEK_START_MONDAY, WEEK_START_SUNDAY),
+        });
+    }
+
+    Ok(value)
+}
+
+fn check_length(field: &'static str, value: &str, max_len: usize) -> Result<(), FieldError> {
+    if value.chars().count() > max_len {
+        return Err(FieldError {
+            field,
+            message: format!("{} maksimal {} karakter.", field, max_len),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{optional_text, required_text, MAX_NAMA_LEN};
+
+    #[test]
+    fn required_text_accepts_up_to_max_length() {
+        let max = MAX_NAMA_LEN;
+        assert_eq!(required_text("nama", &"a".repeat(max - 1), max).unwrap().len(), max - 1);
+        assert_eq!(required_text("nama", &"a".repeat(max), max).unwrap().len(), max);
+
+        let err = required_text("nama", &"a".repeat(max + 1), max).unwrap_err();
+        assert_eq!(err.field, "nama");
+        assert_eq!(err.message, format!("nama maksimal {} karakter.", max));
+    }
+
+    #[test]
+    fn required_text_rejects_whitespace_only() {
+        let err = required_text("deskripsi", " \t\n ", 10).unwrap_err();
+        assert_eq!(err.message, "deskripsi wajib diisi.");
+        assert_eq!(required_text("deskripsi", "  Kopi  ", 10).unwrap(), "Kopi");
+    }
+
+    #[test]
+    fn length_is_counted_in_chars_not_bytes() {
+        // 10 karakter multibyte (lebih dari 10 byte) masih dalam batas
+        let value = "é".repeat(10);
+        assert!(value.len() > 10);
+        assert_eq!(required_text("nama", &value, 10).unwrap(), value);
+        assert!(required_text("nama", &"é".repeat(11), 10).is_err());
+        assert!(required_text("nama", &"🍜".repeat(10), 10).is_ok());
+    }
+
+    #[test]
+    fn length_is_checked_after_trim() {
+        let value = format!("  {}  ", "a".repeat(10));
+        assert_eq!(required_text("nama", &value, 10).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn optional_text_treats_blank_as_missing() {
+        assert_eq!(optional_text("note", Some("   "), 5).unwrap(), None);
+        assert_eq!(optional_text("note", None, 5).unwrap(), None);
+        assert_eq!(optional_text("note", Some(" abcde "), 5).unwrap(), Some("abcde"));
+        assert!(optional_text("note", Some("abcdef"), 5).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rand_core::{OsRng, RngCore};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{redirect, Url};
+use serde_json::json;
+use sha2::Sha256;
+use std::env;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::database::Database;
+use crate::models::budget::Budget;
+use crate::models::webhook::Webhook;
+
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+
+// Tujuan webhook ke alamat privat/loopback hanya diizinkan jika WEBHOOK_ALLOW_PRIVATE=true (development lokal)
+fn allow_private_targets() -> bool {
+    env::var("WEBHOOK_ALLOW_PRIVATE")
+        .map(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+// true jika alamat boleh menjadi tujuan webhook: bukan loopback, jaringan privat, link-local
+// (termasuk metadata cloud 169.254.169.254), CGNAT, multicast, atau alamat tidak spesifik
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(mapped));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (first & 0xfe00) == 0xfc00 // unique local fc00::/7
+                || (first & 0xffc0) == 0xfe80) // link-local fe80::/10
+        }
+    }
+}
+
+// Validasi URL tujuan webhook: http(s) dengan host yang seluruh alamat hasil resolve-nya publik.
+// Mengembalikan pesan error untuk client
+pub async fn check_webhook_url(url: &str) -> Result<(), String> {
+    let parsed = Url::parse(url).map_err(|_| "URL webhook tidak valid.".to_string())?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err("URL webhook harus diawali http:// atau https://.".to_string());
+    }
+    let Some(host) = parsed.host_str() else {
+        return Err("URL webhook harus memiliki host.".to_string());
+    };
+
+    if allow_private_targets() {
+        return Ok(());
+    }
+
+    let port = parsed.port_or_known_default().unwrap_or(443);
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|_| "Host URL webhook tidak dapat di-resolve.".to_string())?
+        .collect();
+
+    if addrs.is_empty() || !addrs.iter().all(|addr| is_public_ip(addr.ip())) {
+        return Err("URL webhook tidak boleh mengarah ke alamat lokal atau jaringan privat.".to_string());
+    }
+
+    Ok(())
+}
+
+// Resolver DNS untuk pengiriman webhook yang membuang alamat non-publik, sehingga host yang
+// lolos validasi tidak bisa kemudian diarahkan (DNS rebinding) ke alamat internal
+struct PublicOnlyResolver;
+
+impl Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("host {} tidak memiliki alamat publik", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+// HTTP client pengiriman webhook, dibuat sekali dan dibagikan lewat AppState.
+// Redirect tidak diikuti agar tujuan tidak bisa dialihkan ke alamat internal
+#[derive(Clone)]
+pub struct WebhookClient(reqwest::Client);
+
+impl WebhookClient {
+    pub fn new() -> Self {
+        let mut builder = reqwest::Client::builder()
+            .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+            .redirect(redirect::Policy::none());
+        if !allow_private_targets() {
+            builder = builder.dns_resolver(Arc::new(PublicOnlyResolver));
+        }
+        WebhookClient(builder.build().expect("Gagal membuat HTTP client webhook"))
+    }
+}
+
+impl Default for WebhookClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// URL tersimpan yang host-nya alamat IP literal tidak melewati resolver, jadi dicek terpisah saat kirim
+fn is_allowed_target(url: &str) -> bool {
+    if allow_private_targets() {
+        return true;
+    }
+    let Some(host) = Url::parse(url).ok().and_then(|url| url.host_str().map(str::to_string)) else {
+        return false;
+    };
+    match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => is_public_ip(ip),
+        Err(_) => true,
+    }
+}
+
+// Secret acak untuk webhook baru jika user tidak mengirim secret sendiri
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+// Tanda tangan HMAC-SHA256 (hex) dari body request, dikirim di header X-Savior-Signature
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC menerima key dengan panjang berapa pun");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// true jika spent budget baru saja mencapai/melewati 100% budget efektif (amount + carry_over)
+pub fn crossed_threshold(spent_before: i32, budget: &Budget) -> bool {
+    let effective_amount = budget.amount + budget.carry_over;
+    spent_before < effective_amount && budget.spent.unwrap_or(0) >= effective_amount
+}
+
+// Kirim event "budget.exceeded" ke semua webhook aktif milik user.
+// Dijalankan di task terpisah sehingga tidak memperlambat response; kegagalan hanya dicatat di log.
+pub fn emit_budget_exceeded(db: Database, client: WebhookClient, user_id: Uuid, budget: Budget) {
+    tokio::spawn(async move {
+        let webhooks = match sqlx::query_as::<_, Webhook>(
+            "SELECT * FROM webhooks WHERE user_id = $1 AND is_active = TRUE"
+        )
+        .bind(user_id)
+        .fetch_all(&db)
+        .await
+        {
+            Ok(webhooks) => webhooks,
+            Err(err) => {
+                eprintln!("Webhook error: gagal mengambil webhook user {}: {:?}", user_id, err);
+                return;
+            }
+        };
+
+        if webhooks.is_empty() {
+            return;
+        }
+
+        let kategori_nama = sqlx::query_scalar::<_, String>("SELECT nama FROM categories WHERE id = $1")
+            .bind(budget.kategori_id)
+            .fetch_optional(&db)
+            .await
+            .ok()
+            .flatten();
+
+        let body = json!({
+            "event": "budget.exceeded",
+            "user_id": user_id,
+            "data": {
+                "budget_id": budget.id,
+                "kategori_id": budget.kategori_id,
+                "kategori_nama": kategori_nama,
+                "amount": budget.amount,
+                "effective_amount": budget.amount + budget.carry_over,
+                "spent": budget.spent.unwrap_or(0)
+            },
+            "occurred_at": Utc::now()
+        })
+        .to_string();
+
+        for webhook in webhooks {
+            if !is_allowed_target(&webhook.url) {
+                eprintln!("Webhook {} dilewati: URL mengarah ke alamat non-publik", webhook.id);
+                continue;
+            }
+
+            let signature = sign_payload(&webhook.secret, body.as_bytes());
+            let result = client
+                .0
+                .post(&webhook.url)
+                .header("Content-Type", "application/json")
+                .header("X-Savior-Event", "budget.exceeded")
+                .header("X-Savior-Signature", format!("sha256={}", signature))
+                .body(body.clone())
+                .send()
+                .await;
+
+            match result {
+                Ok(response) if response.status().is_success() => {}
+                Ok(response) => eprintln!("Webhook {} merespons status {}", webhook.id, response.status()),
+                Err(err) => eprintln!("Webhook {} gagal dikirim: {:?}", webhook.id, err),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_public_ip;
+    use std::net::IpAddr;
+
+    fn public(ip: &str) -> bool {
+        is_public_ip(ip.parse::<IpAddr>().unwrap())
+    }
+
+    #[test]
+    fn internal_addresses_are_not_public() {
+        for ip in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "0.0.0.0", "100.64.0.1", "::1", "fe80::1", "fd00::1", "::ffff:127.0.0.1"] {
+            assert!(!public(ip), "{} seharusnya ditolak", ip);
+        }
+    }
+
+    #[test]
+    fn public_addresses_are_allowed() {
+        for ip in ["93.184.216.34", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(public(ip), "{} seharusnya diizinkan", ip);
+        }
+    }
+}
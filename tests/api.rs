@@ -0,0 +1,698 @@
+mod common;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Request, StatusCode},
+};
+use chrono::{Datelike, Duration, Local};
+use serde_json::json;
+use tower::ServiceExt;
+
+use common::{send, send_authorized, signup_and_signin, TestDb};
+use savior_be::retention::purge_expired;
+
+#[tokio::test]
+async fn signup_signin_transaksi_statistik_flow() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "flow@savior.test", "rahasia123").await.id;
+
+    // Kategori default dibuat saat signup
+    let (status, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let kategori_id = body["data"][0]["id"].as_i64().expect("kategori default tidak ada");
+
+    let (status, body) = send(
+        &app,
+        "POST",
+        &format!("/api/budget/{}", user_id),
+        Some(json!({ "kategori_id": kategori_id, "amount": 100000 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "success");
+
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let (status, body) = send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(json!({
+            "kategori_id": kategori_id,
+            "jumlah": 25000,
+            "deskripsi": "Makan siang",
+            "tanggal": today
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["jumlah"], 25000);
+
+    let (status, body) = send(&app, "GET", &format!("/api/statistik/{}", user_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "success");
+    assert_eq!(body["data"]["ringkasan"]["total_pengeluaran"], 25000);
+    // Tanpa pemasukan, persentase terhadap pemasukan tidak bisa dihitung
+    assert!(body["data"]["pengeluaran_per_kategori"][0]["percentage_of_income"].is_null());
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(json!({ "kategori_id": kategori_id, "jumlah": 100000, "deskripsi": "Gaji", "tanggal": today, "tipe": "pemasukan" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = send(&app, "GET", &format!("/api/statistik/{}", user_id), None).await;
+    assert_eq!(body["data"]["ringkasan"]["total_pemasukan"], 100000);
+    assert_eq!(body["data"]["pengeluaran_per_kategori"][0]["percentage_of_income"], 25.0);
+
+    // top=1: kategori terbesar saja, sisanya digabung menjadi "Lainnya"
+    let (status, body) = send(&app, "GET", &format!("/api/statistik/{}?top=1", user_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let kategori = body["data"]["pengeluaran_per_kategori"].as_array().unwrap();
+    assert_eq!(kategori.len(), 2);
+    assert_eq!(kategori[0]["total_pengeluaran"], 25000);
+    assert_eq!(kategori[1]["kategori_nama"], "Lainnya");
+    assert_eq!(kategori[1]["total_pengeluaran"], 0);
+
+    // Statistik satu kategori
+    let (status, body) = send(&app, "GET", &format!("/api/statistik/{}/kategori/{}?filter=monthly", user_id, kategori_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["total_pengeluaran"], 25000);
+    assert_eq!(body["data"]["total_transaksi"], 1);
+    assert_eq!(body["data"]["rata_rata_transaksi"], 25000.0);
+    assert_eq!(body["data"]["harian"][0]["tanggal"], today);
+
+    let (status, _) = send(&app, "GET", &format!("/api/statistik/{}/kategori/999999", user_id), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // year/month di luar rentang ditolak dengan 400, bukan panic
+    for query in ["month=13", "month=0", "filter=monthly&year=0&month=5"] {
+        let (status, body) = send(&app, "GET", &format!("/api/statistik/{}?{}", user_id, query), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["status"], "error");
+    }
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn invalid_user_id_is_rejected() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let (status, body) = send(&app, "GET", "/api/transaksi/bukan-uuid", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["status"], "error");
+    assert_eq!(body["message"], "Invalid user ID format.");
+    // Body error membawa request id untuk dicocokkan dengan log server
+    assert!(body["request_id"].is_string());
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn me_returns_current_user_from_token() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user = signup_and_signin(&app, "me@savior.test", "rahasia123").await;
+
+    let (status, body) = send_authorized(&app, "GET", "/api/me", &user.token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["profile"]["id"], user.id.as_str());
+    assert_eq!(body["profile"]["email"], "me@savior.test");
+    assert!(body["profile"].get("password_hash").is_none());
+
+    let (status, _) = send(&app, "GET", "/api/me", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send_authorized(&app, "GET", "/api/me", "bukan-token", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Validasi token tanpa mengambil data
+    let (status, body) = send_authorized(&app, "GET", "/api/auth/validate", &user.token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["valid"], true);
+    assert_eq!(body["user_id"], user.id.as_str());
+    assert!(body["expires_at"].is_string());
+
+    let (status, _) = send_authorized(&app, "GET", "/api/auth/validate", "bukan-token", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn password_change_invalidates_existing_tokens() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user = signup_and_signin(&app, "sesi@savior.test", "rahasia123").await;
+    let (status, _) = send_authorized(&app, "GET", "/api/me", &user.token, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(
+        &app,
+        "PUT",
+        &format!("/api/profile/{}/password", user.id),
+        Some(json!({ "current_password": "rahasia123", "new_password": "rahasiaBaru456" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Access dan refresh token lama sudah tidak berlaku
+    let (status, _) = send_authorized(&app, "GET", "/api/me", &user.token, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send(&app, "POST", "/api/refresh", Some(json!({ "refresh_token": user.refresh_token }))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Login ulang dengan password baru menghasilkan token yang valid
+    let (_, body) = send(&app, "POST", "/signin", Some(json!({ "email": "sesi@savior.test", "password": "rahasiaBaru456" }))).await;
+    let token = body["token"].as_str().unwrap().to_string();
+    let (status, _) = send_authorized(&app, "GET", "/api/me", &token, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Login dan ganti password tercatat di activity, terbaru lebih dulu
+    let (status, body) = send_authorized(&app, "GET", &format!("/api/profile/{}/activity", user.id), &token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let events: Vec<&str> = body["activity"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|event| event["event_type"].as_str().unwrap())
+        .collect();
+    assert_eq!(events, ["login", "password_change", "login"]);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn retention_purge_removes_old_revoked_tokens() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user = signup_and_signin(&app, "purge@savior.test", "rahasia123").await;
+    let (status, _) = send(&app, "POST", "/api/logout", Some(json!({ "refresh_token": user.refresh_token }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Token yang baru dicabut masih dalam masa retensi
+    assert_eq!(purge_expired(&db.pool, 7).await.unwrap(), 0);
+
+    sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() - INTERVAL '8 days'")
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(purge_expired(&db.pool, 7).await.unwrap(), 1);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn wrong_method_returns_405_with_allow_header() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let request = Request::builder()
+        .method("DELETE")
+        .uri("/api/statistik/ranges")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    let allow = response.headers().get(header::ALLOW).unwrap().to_str().unwrap();
+    assert!(allow.contains("GET"));
+
+    let (status, body) = send(&app, "PATCH", "/api/budget/bukan-uuid", None).await;
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(body["status"], "error");
+
+    // Path API yang tidak dikenal tetap 404, bukan diteruskan ke frontend
+    let (status, body) = send(&app, "GET", "/api/tidak-ada", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["status"], "error");
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn json_body_errors_use_error_envelope() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let signin = |content_type: &'static str, body: &'static str| {
+        Request::builder()
+            .method("POST")
+            .uri("/signin")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .unwrap()
+    };
+    let read_json = |response: axum::response::Response| async move {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+    };
+
+    // Body JSON dengan Content-Type yang salah
+    let response = app.clone().oneshot(signin("text/plain", r#"{"email":"a@b.c","password":"x"}"#)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let body = read_json(response).await;
+    assert_eq!(body["status"], "error");
+    assert!(body["message"].as_str().unwrap().contains("application/json"));
+
+    // JSON rusak dan field wajib yang hilang juga memakai envelope yang sama
+    let response = app.clone().oneshot(signin("application/json", "{bukan json")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(read_json(response).await["status"], "error");
+
+    let response = app.clone().oneshot(signin("application/json", r#"{"email":"a@b.c"}"#)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(read_json(response).await["status"], "error");
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn email_is_case_insensitive_for_signup_and_signin() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user = signup_and_signin(&app, "  Campur@Savior.Test ", "rahasia123").await;
+    let (_, body) = send(&app, "GET", &format!("/api/user/{}", user.id), None).await;
+    assert_eq!(body["data"]["email"], "campur@savior.test");
+
+    let (status, _) = send(&app, "POST", "/signin", Some(json!({ "email": "CAMPUR@savior.test", "password": "rahasia123" }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(&app, "POST", "/signup", Some(json!({ "email": "campur@SAVIOR.test", "password": "rahasia123" }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn display_name_is_independent_of_first_last_name() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user = signup_and_signin(&app, "nama@savior.test", "rahasia123").await;
+    let profile_uri = format!("/api/profile/{}", user.id);
+
+    let (status, body) = send(&app, "PUT", &format!("{}/display-name", profile_uri), Some(json!({ "display_name": "  budi_hemat " }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["profile"]["display_name"], "budi_hemat");
+
+    let (status, _) = send(&app, "PUT", &format!("{}/display-name", profile_uri), Some(json!({ "display_name": "   " }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Update nama depan/belakang tidak lagi menimpa display name
+    let (status, _) = send(&app, "PUT", &profile_uri, Some(json!({ "first_name": "Budi", "last_name": "Santoso" }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = send(&app, "GET", &profile_uri, None).await;
+    assert_eq!(body["profile"]["first_name"], "Budi");
+    assert_eq!(body["profile"]["last_name"], "Santoso");
+    assert_eq!(body["profile"]["display_name"], "budi_hemat");
+
+    // Display name unik antar user
+    let other = signup_and_signin(&app, "nama-lain@savior.test", "rahasia123").await;
+    let (status, _) = send(&app, "PUT", &format!("/api/profile/{}/display-name", other.id), Some(json!({ "display_name": "budi_hemat" }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn calendar_week_follows_week_start_preference() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "minggu@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().expect("kategori default tidak ada");
+    let (status, _) = send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": kategori_id, "amount": 100000 }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Transaksi hari ini dan pada hari Minggu terakhir (bisa sama dengan hari ini)
+    let today = Local::now().date_naive();
+    let last_sunday = today - Duration::days(today.weekday().num_days_from_sunday() as i64);
+    let monday_start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    for (tanggal, jumlah) in [(today, 1000), (last_sunday, 5000)] {
+        let (status, _) = send(
+            &app,
+            "POST",
+            &format!("/api/transaksi/{}", user_id),
+            Some(json!({ "kategori_id": kategori_id, "jumlah": jumlah, "deskripsi": "Belanja", "tanggal": tanggal.to_string() })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let monday_total = if last_sunday >= monday_start { 6000 } else { 1000 };
+
+    // Default: minggu dimulai hari Senin
+    let (_, body) = send(&app, "GET", &format!("/api/profile/{}/preferences", user_id), None).await;
+    assert_eq!(body["preferences"]["week_start"], "monday");
+
+    let (status, body) = send(&app, "GET", &format!("/api/statistik/{}?filter=calendar_week", user_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["ringkasan"]["total_pengeluaran"], monday_total);
+
+    let (status, _) = send(&app, "PUT", &format!("/api/profile/{}/preferences", user_id), Some(json!({ "week_start": "friday" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = send(&app, "PUT", &format!("/api/profile/{}/preferences", user_id), Some(json!({ "week_start": "Sunday" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["preferences"]["week_start"], "sunday");
+
+    let (_, body) = send(&app, "GET", &format!("/api/statistik/{}?filter=calendar_week", user_id), None).await;
+    assert_eq!(body["data"]["ringkasan"]["total_pengeluaran"], 6000);
+
+    let (status, body) = send(&app, "GET", &format!("/api/dashboard/{}", user_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["total_minggu_ini"], 6000);
+
+    // Grafik mingguan dashboard bisa berakhir di tanggal lampau, tapi tidak di masa depan
+    let (status, body) = send(&app, "GET", &format!("/api/dashboard/{}?end_date={}", user_id, last_sunday), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["weekly_range"]["end_date"], last_sunday.to_string());
+    assert_eq!(body["data"]["pengeluaran_mingguan"][6]["jumlah"], if last_sunday == today { 6000 } else { 5000 });
+
+    let tomorrow = today + Duration::days(1);
+    let (status, _) = send(&app, "GET", &format!("/api/dashboard/{}?end_date={}", user_id, tomorrow), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn admin_can_look_up_user_by_email() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user = signup_and_signin(&app, "dicari@savior.test", "rahasia123").await;
+    let lookup_uri = "/api/admin/users/by-email?email=%20Dicari@Savior.test";
+
+    // Non-admin ditolak sebelum email dicek, jadi tidak bisa dipakai untuk enumerasi akun
+    let (status, _) = send(&app, "GET", lookup_uri, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = send_authorized(&app, "GET", lookup_uri, &user.token, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    signup_and_signin(&app, "admin-support@savior.test", "rahasia123").await;
+    sqlx::query("UPDATE users SET role = 'admin' WHERE email = 'admin-support@savior.test'")
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let (_, body) = send(&app, "POST", "/signin", Some(json!({ "email": "admin-support@savior.test", "password": "rahasia123" }))).await;
+    let admin_token = body["token"].as_str().unwrap();
+
+    let (status, body) = send_authorized(&app, "GET", lookup_uri, admin_token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["id"], user.id.as_str());
+    assert!(body["data"].get("password_hash").is_none());
+
+    let (status, _) = send_authorized(&app, "GET", "/api/admin/users/by-email?email=tidak-ada@savior.test", admin_token, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Role dibaca dari database: admin yang diturunkan langsung ditolak meski tokennya masih berlaku
+    sqlx::query("UPDATE users SET role = 'user' WHERE email = 'admin-support@savior.test'")
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let (status, _) = send_authorized(&app, "GET", lookup_uri, admin_token, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn activity_is_private_and_account_deletion_is_recorded() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user = signup_and_signin(&app, "pemilik@savior.test", "rahasia123").await;
+    let other = signup_and_signin(&app, "penyusup@savior.test", "rahasia123").await;
+    let activity_uri = format!("/api/profile/{}/activity", user.id);
+
+    let (status, _) = send(&app, "GET", &activity_uri, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = send_authorized(&app, "GET", &activity_uri, &other.token, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["success"], false);
+    let (status, _) = send_authorized(&app, "GET", &activity_uri, &user.token, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Hapus akun hanya untuk admin; event tetap tersimpan setelah user terhapus
+    let delete_uri = format!("/api/admin/users/{}", user.id);
+    let (status, _) = send_authorized(&app, "DELETE", &delete_uri, &other.token, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    sqlx::query("UPDATE users SET role = 'admin' WHERE email = 'penyusup@savior.test'")
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let (_, body) = send(&app, "POST", "/signin", Some(json!({ "email": "penyusup@savior.test", "password": "rahasia123" }))).await;
+    let admin_token = body["token"].as_str().unwrap();
+
+    let (status, _) = send_authorized(&app, "DELETE", &delete_uri, admin_token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_authorized(&app, "DELETE", &delete_uri, admin_token, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let deleted_by: serde_json::Value = sqlx::query_scalar(
+        "SELECT detail->'deleted_by' FROM audit_log WHERE user_id = $1::uuid AND event_type = 'account_deleted'"
+    )
+    .bind(&user.id)
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(deleted_by, other.id.as_str());
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn streaks_count_days_under_threshold_and_logged_days() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "streak@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().expect("kategori default tidak ada");
+    let (status, _) = send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": kategori_id, "amount": 1000000 }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Hari ke-3 sebelum hari ini kosong (celah), hari ke-2 melebihi threshold, hari ini belum ada transaksi
+    let today = Local::now().date_naive();
+    for (days_ago, jumlah) in [(5, 5000), (4, 5000), (2, 50000), (1, 5000)] {
+        let tanggal = today - Duration::days(days_ago);
+        let (status, _) = send(
+            &app,
+            "POST",
+            &format!("/api/transaksi/{}", user_id),
+            Some(json!({ "kategori_id": kategori_id, "jumlah": jumlah, "deskripsi": "Jajan", "tanggal": tanggal.to_string() })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    // Tanpa daily_limit dan threshold hanya streak pencatatan yang bisa dihitung
+    let (status, body) = send(&app, "GET", &format!("/api/statistik/{}/streaks", user_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["data"]["current_streak"].is_null());
+    assert_eq!(body["data"]["current_logging_streak"], 2);
+    assert_eq!(body["data"]["start_date"], (today - Duration::days(5)).to_string());
+
+    let (_, body) = send(&app, "GET", &format!("/api/statistik/{}/streaks?threshold=20000", user_id), None).await;
+    assert_eq!(body["data"]["threshold"], 20000);
+    assert_eq!(body["data"]["current_streak"], 2);
+    assert_eq!(body["data"]["longest_streak"], 3);
+
+    // Default threshold dari daily_limit user
+    let (status, _) = send(&app, "PUT", &format!("/api/profile/{}/daily-limit", user_id), Some(json!({ "daily_limit": 4000 }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = send(&app, "GET", &format!("/api/statistik/{}/streaks", user_id), None).await;
+    assert_eq!(body["data"]["threshold"], 4000);
+    assert_eq!(body["data"]["current_streak"], 1);
+    assert_eq!(body["data"]["longest_streak"], 1);
+
+    let (status, _) = send(&app, "GET", &format!("/api/statistik/{}/streaks?threshold=-1", user_id), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn monthly_report_combines_breakdown_series_and_budget() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "laporan@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().expect("kategori default tidak ada");
+    let (status, _) = send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": kategori_id, "amount": 100000 }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let today = Local::now().date_naive();
+    for (jumlah, deskripsi) in [(30000, "Belanja, mingguan"), (10000, "Kopi")] {
+        let (status, _) = send(
+            &app,
+            "POST",
+            &format!("/api/transaksi/{}", user_id),
+            Some(json!({ "kategori_id": kategori_id, "jumlah": jumlah, "deskripsi": deskripsi, "tanggal": today.to_string() })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = send(&app, "GET", &format!("/api/statistik/{}/report", user_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let laporan = &body["data"];
+    assert_eq!(laporan["bulan"], today.format("%Y-%m").to_string());
+    assert_eq!(laporan["ringkasan"]["total_pengeluaran"], 40000);
+    assert_eq!(laporan["ringkasan"]["total_transaksi"], 2);
+    assert_eq!(laporan["transaksi_terbesar"][0]["jumlah"], 30000);
+    assert_eq!(laporan["budget_vs_aktual"][0]["actual"], 40000);
+    assert_eq!(laporan["budget_vs_aktual"][0]["variance"], 60000);
+
+    // Deret harian mencakup setiap tanggal dalam bulan
+    let harian = laporan["harian"].as_array().unwrap();
+    assert_eq!(harian.first().unwrap()["tanggal"], today.format("%Y-%m-01").to_string());
+    assert_eq!(harian[today.day0() as usize]["total"], 40000);
+
+    let request = Request::builder()
+        .uri(format!("/api/statistik/{}/report?format=csv", user_id))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/csv"));
+    let csv = String::from_utf8(to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap();
+    assert!(csv.contains("\"Belanja, mingguan\",30000"));
+    assert!(csv.contains("Budget vs aktual"));
+
+    let (status, _) = send(&app, "GET", &format!("/api/statistik/{}/report?month=2026-13", user_id), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn currency_change_is_rejected_once_amounts_exist() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "mata-uang@savior.test", "rahasia123").await.id;
+    let preferences_uri = format!("/api/profile/{}/preferences", user_id);
+
+    // Belum ada transaksi/budget: mata uang bebas diganti
+    let (status, body) = send(&app, "PUT", &preferences_uri, Some(json!({ "currency": "USD" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["preferences"]["currency"], "USD");
+    let (status, _) = send(&app, "PUT", &preferences_uri, Some(json!({ "currency": "IDR" }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+    send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": kategori_id, "amount": 100000 }))).await;
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(json!({ "kategori_id": kategori_id, "jumlah": 150, "deskripsi": "Parkir", "tanggal": today })),
+    )
+    .await;
+
+    let (status, body) = send(&app, "PUT", &preferences_uri, Some(json!({ "currency": "USD" }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["field"], "currency");
+
+    // Mata uang yang sama dan field lain tetap bisa diupdate
+    let (status, body) = send(&app, "PUT", &preferences_uri, Some(json!({ "currency": "IDR", "locale": "en-US" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["preferences"]["currency"], "IDR");
+
+    let (_, body) = send(&app, "GET", &format!("/api/transaksi/{}", user_id), None).await;
+    assert_eq!(body["transaksi"][0]["jumlah_formatted"], "Rp 150");
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn legacy_password_hashes_are_upgraded_on_signin() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let plaintext_user = signup_and_signin(&app, "lama-plain@savior.test", "rahasia123").await;
+    let bcrypt_user = signup_and_signin(&app, "lama-bcrypt@savior.test", "rahasia123").await;
+
+    // Simulasikan baris lama: password tersimpan plaintext dan bcrypt
+    let bcrypt_hash = bcrypt::hash("rahasia123", 4).unwrap();
+    for (user_id, stored) in [(&plaintext_user.id, "rahasia123".to_string()), (&bcrypt_user.id, bcrypt_hash)] {
+        sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1::uuid")
+            .bind(user_id)
+            .bind(stored)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+
+    for (email, user_id) in [("lama-plain@savior.test", &plaintext_user.id), ("lama-bcrypt@savior.test", &bcrypt_user.id)] {
+        let (status, _) = send(&app, "POST", "/signin", Some(json!({ "email": email, "password": "salah12345" }))).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = send(&app, "POST", "/signin", Some(json!({ "email": email, "password": "rahasia123" }))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let stored: String = sqlx::query_scalar("SELECT password_hash FROM users WHERE id = $1::uuid")
+            .bind(user_id)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert!(stored.starts_with("$argon2"), "{} tidak di-upgrade: {}", email, stored);
+
+        // Login berikutnya memakai hash Argon2 yang baru
+        let (status, body) = send(&app, "POST", "/signin", Some(json!({ "email": email, "password": "rahasia123" }))).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send_authorized(&app, "GET", "/api/me", body["token"].as_str().unwrap(), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn webhook_urls_to_internal_addresses_are_rejected() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "webhook-ssrf@savior.test", "rahasia123").await.id;
+    let webhooks_uri = format!("/api/webhooks/{}", user_id);
+
+    for url in [
+        "ftp://93.184.216.34/hook",
+        "http://localhost:8080/hook",
+        "http://127.0.0.1/hook",
+        "http://169.254.169.254/latest/meta-data",
+        "https://10.0.0.5/hook",
+        "http://[::1]/hook",
+    ] {
+        let (status, body) = send(&app, "POST", &webhooks_uri, Some(json!({ "url": url }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", url);
+        assert_eq!(body["field"], "url");
+    }
+
+    let (status, body) = send(&app, "POST", &webhooks_uri, Some(json!({ "url": "https://93.184.216.34/hook" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let webhook_id = body["data"]["id"].as_i64().unwrap();
+
+    // Mengganti URL ke alamat internal juga ditolak
+    let (status, _) = send(&app, "PUT", &format!("{}/{}", webhooks_uri, webhook_id), Some(json!({ "url": "http://192.168.1.10/hook" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    db.cleanup().await;
+}
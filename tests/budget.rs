@@ -0,0 +1,457 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Datelike, Local, Months};
+use serde_json::json;
+
+use common::{send, send_authorized, signup_and_signin, TestDb};
+
+#[tokio::test]
+async fn acknowledged_alert_rearms_at_next_threshold() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "alert@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+
+    let (_, body) = send(
+        &app,
+        "POST",
+        &format!("/api/budget/{}", user_id),
+        Some(json!({ "kategori_id": kategori_id, "amount": 100000 })),
+    )
+    .await;
+    let budget_id = body["data"]["id"].as_i64().unwrap();
+
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let transaksi_uri = format!("/api/transaksi/{}", user_id);
+    let transaksi = |jumlah: i64| json!({ "kategori_id": kategori_id, "jumlah": jumlah, "deskripsi": "Belanja", "tanggal": today });
+    let alerts_uri = format!("/api/budget/{}/alerts", user_id);
+
+    send(&app, "POST", &transaksi_uri, Some(transaksi(60000))).await;
+    let (status, body) = send(&app, "GET", &alerts_uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"][0]["budget_id"], budget_id);
+    assert_eq!(body["data"][0]["threshold"], 50);
+
+    let (status, body) = send(
+        &app,
+        "POST",
+        &format!("{}/ack", alerts_uri),
+        Some(json!({ "budget_ids": [budget_id, 999999] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["acknowledged"], json!([budget_id]));
+    assert_eq!(body["data"]["skipped"], json!([999999]));
+
+    let (_, body) = send(&app, "GET", &alerts_uri, None).await;
+    assert_eq!(body["data"], json!([]));
+
+    let (_, body) = send(&app, "GET", &format!("{}?include_acknowledged=true", alerts_uri), None).await;
+    assert!(body["data"][0]["acknowledged_at"].is_string());
+
+    // Naik ke threshold 80% membuat alert muncul lagi
+    send(&app, "POST", &transaksi_uri, Some(transaksi(25000))).await;
+    let (_, body) = send(&app, "GET", &alerts_uri, None).await;
+    assert_eq!(body["data"][0]["threshold"], 80);
+    assert!(body["data"][0]["acknowledged_at"].is_null());
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn admin_recalculate_fixes_spent_drift() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user = signup_and_signin(&app, "admin@savior.test", "rahasia123").await;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user.id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+
+    let (_, body) = send(
+        &app,
+        "POST",
+        &format!("/api/budget/{}", user.id),
+        Some(json!({ "kategori_id": kategori_id, "amount": 100000 })),
+    )
+    .await;
+    let budget_id = body["data"]["id"].as_i64().unwrap();
+    send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user.id),
+        Some(json!({
+            "kategori_id": kategori_id,
+            "jumlah": 40000,
+            "deskripsi": "Belanja",
+            "tanggal": Local::now().date_naive().format("%Y-%m-%d").to_string()
+        })),
+    )
+    .await;
+
+    // User biasa tidak boleh menjalankan recalculate
+    let (status, _) = send_authorized(&app, "POST", "/api/admin/recalculate-budgets", &user.token, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Simulasikan spent yang melenceng, lalu login ulang sebagai admin
+    sqlx::query("UPDATE budgets SET spent = 55000 WHERE id = $1")
+        .bind(budget_id as i32)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE users SET role = 'admin' WHERE id = $1::uuid")
+        .bind(&user.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let (_, body) = send(&app, "POST", "/signin", Some(json!({ "email": "admin@savior.test", "password": "rahasia123" }))).await;
+    let admin_token = body["token"].as_str().unwrap();
+
+    let (status, body) = send_authorized(&app, "POST", "/api/admin/recalculate-budgets", admin_token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["checked"], 1);
+    assert_eq!(body["data"]["corrected"], 1);
+    assert_eq!(body["data"]["total_drift"], 15000);
+
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}", user.id), None).await;
+    assert_eq!(body["budgets"][0]["spent"], 40000);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn upsert_creates_then_updates_budget() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "upsert@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+    let upsert_uri = format!("/api/budget/{}/kategori/{}", user_id, kategori_id);
+
+    let (status, body) = send(&app, "PUT", &upsert_uri, Some(json!({ "amount": 100000 }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["created"], true);
+    let budget_id = body["data"]["id"].clone();
+
+    let (status, body) = send(&app, "PUT", &upsert_uri, Some(json!({ "amount": 150000 }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["created"], false);
+    assert_eq!(body["data"]["id"], budget_id);
+    assert_eq!(body["data"]["amount"], 150000);
+
+    let (status, _) = send(&app, "PUT", &upsert_uri, Some(json!({ "amount": 0 }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn suggestions_cover_unbudgeted_categories_with_history() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "saran@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori: Vec<i64> = body["data"].as_array().unwrap().iter().map(|k| k["id"].as_i64().unwrap()).collect();
+    let (rutin, sekali, sudah_budget) = (kategori[0], kategori[1], kategori[2]);
+
+    send(
+        &app,
+        "POST",
+        &format!("/api/budget/{}", user_id),
+        Some(json!({ "kategori_id": sudah_budget, "amount": 100000 })),
+    )
+    .await;
+
+    // Riwayat 3 bulan penuh terakhir: `rutin` tiap bulan, `sekali` hanya satu bulan
+    let start_of_month = Local::now().date_naive().with_day(1).unwrap();
+    let mut seed = vec![(sekali, 1, 90000)];
+    for months_ago in 1..=3 {
+        seed.push((rutin, months_ago, 41234));
+        seed.push((sudah_budget, months_ago, 50000));
+    }
+    for (kategori_id, months_ago, jumlah) in seed {
+        sqlx::query("INSERT INTO transaksi (user_id, kategori_id, jumlah, deskripsi, tanggal) VALUES ($1::uuid, $2, $3, 'Riwayat', $4)")
+            .bind(&user_id)
+            .bind(kategori_id as i32)
+            .bind(jumlah)
+            .bind(start_of_month - Months::new(months_ago))
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+
+    let (status, body) = send(&app, "GET", &format!("/api/budget/{}/suggestions", user_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let suggestions = body["data"].as_array().unwrap();
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0]["kategori_id"], rutin);
+    assert_eq!(suggestions[0]["rata_rata_bulanan"], 41234);
+    assert_eq!(suggestions[0]["suggested_amount"], 42000);
+    assert_eq!(suggestions[0]["bulan_aktif"], 3);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn budget_page_combines_list_summary_alerts_and_candidates() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "halaman@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+    let total_kategori = body["pagination"]["total"].as_i64().unwrap();
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        &format!("/api/budget/{}", user_id),
+        Some(json!({ "kategori_id": kategori_id, "amount": 100000 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(json!({
+            "kategori_id": kategori_id,
+            "jumlah": 85000,
+            "deskripsi": "Belanja bulanan",
+            "tanggal": Local::now().date_naive().format("%Y-%m-%d").to_string()
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = send(&app, "GET", &format!("/api/budget/{}/page", user_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let data = &body["data"];
+    assert_eq!(data["budgets"].as_array().unwrap().len(), 1);
+    assert_eq!(data["budgets"][0]["kategori_id"], kategori_id);
+    assert_eq!(data["summary"]["jumlah_budget"], 1);
+    assert_eq!(data["summary"]["total_budget"], 100000);
+    assert_eq!(data["summary"]["total_terpakai"], 85000);
+    assert_eq!(data["alerts"][0]["threshold"], 80);
+
+    let candidates = data["kategori_tanpa_budget"].as_array().unwrap();
+    assert_eq!(candidates.len() as i64, total_kategori - 1);
+    assert!(candidates.iter().all(|kategori| kategori["id"] != kategori_id));
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn budget_percentage_is_rounded_to_two_decimals() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "persen@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+
+    let (_, body) = send(
+        &app,
+        "POST",
+        &format!("/api/budget/{}", user_id),
+        Some(json!({ "kategori_id": kategori_id, "amount": 30000 })),
+    )
+    .await;
+    let budget_id = body["data"]["id"].as_i64().unwrap();
+    let (status, _) = send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(json!({
+            "kategori_id": kategori_id,
+            "jumlah": 10000,
+            "deskripsi": "Sepertiga budget",
+            "tanggal": Local::now().date_naive().format("%Y-%m-%d").to_string()
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}", user_id), None).await;
+    assert_eq!(body["budgets"][0]["percentage"], 33.33);
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}/{}", user_id, budget_id), None).await;
+    assert_eq!(body["data"]["percentage"], 33.33);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn budget_list_filters_by_status() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "status-budget@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_ids: Vec<i64> = body["data"].as_array().unwrap().iter().take(3).map(|k| k["id"].as_i64().unwrap()).collect();
+
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let budget_uri = format!("/api/budget/{}", user_id);
+    let mut budget_ids = Vec::new();
+    for (kategori_id, jumlah) in kategori_ids.iter().zip([30000, 85000, 100000]) {
+        let (_, body) = send(&app, "POST", &budget_uri, Some(json!({ "kategori_id": kategori_id, "amount": 100000 }))).await;
+        budget_ids.push(body["data"]["id"].as_i64().unwrap());
+        send(
+            &app,
+            "POST",
+            &format!("/api/transaksi/{}", user_id),
+            Some(json!({ "kategori_id": kategori_id, "jumlah": jumlah, "deskripsi": "Belanja", "tanggal": today })),
+        )
+        .await;
+    }
+    // Budget dikecilkan setelah terpakai penuh sehingga terlampaui
+    let (status, _) = send(&app, "PUT", &format!("{}/{}", budget_uri, budget_ids[2]), Some(json!({ "amount": 60000 }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let ids = |body: &serde_json::Value| -> Vec<i64> {
+        body["budgets"].as_array().unwrap().iter().map(|b| b["id"].as_i64().unwrap()).collect()
+    };
+
+    let (status, body) = send(&app, "GET", &budget_uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ids(&body).len(), 3);
+
+    for (status_filter, expected) in [("under", budget_ids[0]), ("near", budget_ids[1]), ("over", budget_ids[2])] {
+        let (status, body) = send(&app, "GET", &format!("{}?status={}", budget_uri, status_filter), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ids(&body), vec![expected], "status={}", status_filter);
+    }
+
+    let (status, body) = send(&app, "GET", &format!("{}?status=habis", budget_uri), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["status"], "error");
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn budget_create_stops_at_per_user_cap() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "batas-budget@savior.test", "rahasia123").await.id;
+
+    // 201 kategori pribadi, 199 di antaranya langsung diberi budget (batas default 200)
+    sqlx::query("INSERT INTO categories (nama, user_id) SELECT 'Pos ' || n, $1::uuid FROM generate_series(1, 201) AS n")
+        .bind(&user_id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO budgets (user_id, kategori_id, amount)
+        SELECT $1::uuid, id, 100000 FROM categories
+        WHERE user_id = $1::uuid AND nama LIKE 'Pos %'
+        ORDER BY id LIMIT 199
+        "#,
+    )
+    .bind(&user_id)
+    .execute(&db.pool)
+    .await
+    .unwrap();
+
+    let tanpa_budget: Vec<i32> = sqlx::query_scalar(
+        "SELECT id FROM categories c WHERE c.user_id = $1::uuid AND c.nama LIKE 'Pos %' AND NOT EXISTS (SELECT 1 FROM budgets b WHERE b.kategori_id = c.id) ORDER BY id",
+    )
+    .bind(&user_id)
+    .fetch_all(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(tanpa_budget.len(), 2);
+
+    let budget_uri = format!("/api/budget/{}", user_id);
+    let (status, _) = send(&app, "POST", &budget_uri, Some(json!({ "kategori_id": tanpa_budget[0], "amount": 50000 }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = send(&app, "POST", &budget_uri, Some(json!({ "kategori_id": tanpa_budget[1], "amount": 50000 }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(body["message"].as_str().unwrap().contains("200"));
+
+    // Jalur lain yang membuat budget juga dibatasi; update budget yang sudah ada tetap boleh
+    let (status, _) = send(&app, "PUT", &format!("{}/kategori/{}", budget_uri, tanpa_budget[1]), Some(json!({ "amount": 50000 }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = send(&app, "PUT", &format!("{}/kategori/{}", budget_uri, tanpa_budget[0]), Some(json!({ "amount": 60000 }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&app, "POST", &format!("{}/bulk", budget_uri), Some(json!({ "budgets": [{ "kategori_id": tanpa_budget[1], "amount": 50000 }] }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(json!({
+            "kategori_id": tanpa_budget[1],
+            "jumlah": 10000,
+            "deskripsi": "Belanja",
+            "tanggal": Local::now().date_naive().format("%Y-%m-%d").to_string(),
+            "auto_budget_amount": 50000
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM budgets WHERE user_id = $1::uuid")
+        .bind(&user_id)
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 200);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn update_budget_rejects_non_positive_amount_and_negative_spent() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "update-budget@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+    let (_, body) = send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": kategori_id, "amount": 100000 }))).await;
+    let budget_uri = format!("/api/budget/{}/{}", user_id, body["data"]["id"].as_i64().unwrap());
+
+    for payload in [json!({ "amount": 0 }), json!({ "amount": -1 }), json!({ "spent": -1 })] {
+        let (status, body) = send(&app, "PUT", &budget_uri, Some(payload.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", payload);
+        assert_eq!(body["status"], "error");
+    }
+
+    let (status, body) = send(&app, "PUT", &budget_uri, Some(json!({ "spent": 0 }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["spent"], 0);
+    assert_eq!(body["data"]["amount"], 100000);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn budget_report_rejects_months_outside_supported_range() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "laporan-budget@savior.test", "rahasia123").await.id;
+    let report_uri = format!("/api/budget/{}/report", user_id);
+
+    let (status, body) = send(&app, "GET", &format!("{}?month=2025-02", report_uri), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["summary"]["end_date"], "2025-02-28");
+
+    let (status, body) = send(&app, "GET", &format!("{}?month=2025-13", report_uri), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["status"], "error");
+
+    // Bulan terakhir yang bisa diwakili chrono tidak punya bulan berikutnya
+    let (status, body) = send(&app, "GET", &format!("{}?month=%2B262142-12", report_uri), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Bulan di luar rentang yang didukung.");
+
+    db.cleanup().await;
+}
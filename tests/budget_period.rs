@@ -0,0 +1,373 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Local, Months, NaiveDate};
+use serde_json::json;
+
+use common::{send, signup_and_signin, TestDb};
+use savior_be::budget_period::{period_elapsed_fraction, period_start_for, reset_expired_periods};
+
+#[tokio::test]
+async fn reset_records_history_and_rolls_over_remainder() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "periode@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+
+    let (status, body) = send(
+        &app,
+        "POST",
+        &format!("/api/budget/{}", user_id),
+        Some(json!({ "kategori_id": kategori_id, "amount": 100000, "rollover": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let budget_id = body["data"]["id"].as_i64().unwrap();
+
+    // Simulasikan budget yang masih berada di periode bulan lalu dengan pengeluaran 30.000
+    let today = Local::now().date_naive();
+    let last_period = period_start_for(today).checked_sub_months(Months::new(1)).unwrap();
+    sqlx::query("UPDATE budgets SET period_start = $1 WHERE id = $2")
+        .bind(last_period)
+        .bind(budget_id as i32)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let (status, _) = send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(json!({
+            "kategori_id": kategori_id,
+            "jumlah": 30000,
+            "deskripsi": "Belanja bulan lalu",
+            "tanggal": last_period.format("%Y-%m-%d").to_string()
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert_eq!(reset_expired_periods(&db.pool, today).await.unwrap(), 1);
+    // Reset kedua pada periode yang sama tidak mengubah apa pun
+    assert_eq!(reset_expired_periods(&db.pool, today).await.unwrap(), 0);
+
+    let (status, body) = send(&app, "GET", &format!("/api/budget/{}/{}", user_id, budget_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["spent"], 0);
+    assert_eq!(body["data"]["carry_over"], 70000);
+    assert_eq!(body["data"]["effective_amount"], 170000);
+
+    let (status, body) = send(&app, "GET", &format!("/api/budget/{}/{}/history", user_id, budget_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["pagination"]["total"], 1);
+    assert_eq!(body["data"][0]["period_start"], last_period.format("%Y-%m-%d").to_string());
+    assert_eq!(body["data"][0]["spent"], 30000);
+    assert_eq!(body["data"][0]["amount"], 100000);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn budget_period_override_counts_spend_to_previous_period() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "override@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+
+    let (status, body) = send(
+        &app,
+        "POST",
+        &format!("/api/budget/{}", user_id),
+        Some(json!({ "kategori_id": kategori_id, "amount": 100000 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let budget_id = body["data"]["id"].as_i64().unwrap();
+
+    // Pindahkan budget ke bulan lalu lalu reset, sehingga periode bulan lalu tercatat di budget_history
+    let today = Local::now().date_naive();
+    let last_period = period_start_for(today).checked_sub_months(Months::new(1)).unwrap();
+    sqlx::query("UPDATE budgets SET period_start = $1 WHERE id = $2")
+        .bind(last_period)
+        .bind(budget_id as i32)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(reset_expired_periods(&db.pool, today).await.unwrap(), 1);
+
+    let create = |period: String| {
+        json!({
+            "kategori_id": kategori_id,
+            "jumlah": 40000,
+            "deskripsi": "Tagihan bulan lalu",
+            "tanggal": today.format("%Y-%m-%d").to_string(),
+            "budget_period_start": period
+        })
+    };
+
+    // Bukan awal periode, atau periode yang tidak pernah ada untuk budget ini
+    let two_periods_ago = last_period.checked_sub_months(Months::new(1)).unwrap();
+    for period in [today.format("%Y-%m-15").to_string(), two_periods_ago.format("%Y-%m-%d").to_string()] {
+        let (status, body) = send(&app, "POST", &format!("/api/transaksi/{}", user_id), Some(create(period))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["fields"]["budget_period_start"].is_string());
+    }
+
+    let (status, body) = send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(create(last_period.format("%Y-%m-%d").to_string())),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["budget_period_start"], last_period.format("%Y-%m-%d").to_string());
+    let transaksi_id = body["data"]["id"].as_i64().unwrap();
+
+    // Spent periode berjalan tidak berubah, pengeluaran masuk ke snapshot bulan lalu
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}/{}", user_id, budget_id), None).await;
+    assert_eq!(body["data"]["spent"], 0);
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}/{}/history", user_id, budget_id), None).await;
+    assert_eq!(body["data"][0]["spent"], 40000);
+
+    // Menghapus override mengembalikan transaksi ke periode dari tanggal
+    let (status, _) = send(
+        &app,
+        "PUT",
+        &format!("/api/transaksi/{}/{}", user_id, transaksi_id),
+        Some(json!({ "budget_period_start": "" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}/{}", user_id, budget_id), None).await;
+    assert_eq!(body["data"]["spent"], 40000);
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}/{}/history", user_id, budget_id), None).await;
+    assert_eq!(body["data"][0]["spent"], 0);
+
+    db.cleanup().await;
+}
+
+#[test]
+fn elapsed_fraction_is_prorated_from_effective_start() {
+    let date = |day: u32| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+
+    // Budget dibuat tanggal 16 Oktober: 16 hari efektif, hari pertama = 1/16
+    assert_eq!(period_elapsed_fraction(date(16), date(31), date(16)), 1.0 / 16.0);
+    assert_eq!(period_elapsed_fraction(date(16), date(31), date(23)), 0.5);
+    assert_eq!(period_elapsed_fraction(date(16), date(31), date(15)), 0.0);
+    assert_eq!(period_elapsed_fraction(date(1), date(31), NaiveDate::from_ymd_opt(2026, 11, 3).unwrap()), 1.0);
+}
+
+#[tokio::test]
+async fn reset_walks_every_missed_period() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "periode-terlewat@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+    let (_, body) = send(
+        &app,
+        "POST",
+        &format!("/api/budget/{}", user_id),
+        Some(json!({ "kategori_id": kategori_id, "amount": 100000, "rollover": true })),
+    )
+    .await;
+    let budget_id = body["data"]["id"].as_i64().unwrap();
+
+    // Budget tertinggal tiga periode, dengan pengeluaran di tiap periode, periode berjalan, dan bulan depan
+    let today = Local::now().date_naive();
+    let current = period_start_for(today);
+    let months_ago = |n: u32| current.checked_sub_months(Months::new(n)).unwrap();
+    let next_month = current.checked_add_months(Months::new(1)).unwrap();
+    sqlx::query("UPDATE budgets SET period_start = $1 WHERE id = $2")
+        .bind(months_ago(3))
+        .bind(budget_id as i32)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    for (tanggal, jumlah) in [(months_ago(3), 30000), (months_ago(2), 120000), (months_ago(1), 20000), (current, 10000), (next_month, 5000)] {
+        sqlx::query("INSERT INTO transaksi (user_id, kategori_id, jumlah, deskripsi, tanggal) VALUES ($1::uuid, $2, $3, 'Belanja', $4)")
+            .bind(&user_id)
+            .bind(kategori_id as i32)
+            .bind(jumlah)
+            .bind(tanggal)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(reset_expired_periods(&db.pool, today).await.unwrap(), 1);
+    assert_eq!(reset_expired_periods(&db.pool, today).await.unwrap(), 0);
+
+    // Carry-over berantai per periode: 100.000-30.000, lalu 170.000-120.000, lalu 150.000-20.000
+    let history: Vec<(NaiveDate, NaiveDate, i32, i32)> = sqlx::query_as(
+        "SELECT period_start, period_end, carry_over, spent FROM budget_history WHERE budget_id = $1 ORDER BY period_start",
+    )
+    .bind(budget_id as i32)
+    .fetch_all(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        history,
+        vec![
+            (months_ago(3), months_ago(2).pred_opt().unwrap(), 0, 30000),
+            (months_ago(2), months_ago(1).pred_opt().unwrap(), 70000, 120000),
+            (months_ago(1), current.pred_opt().unwrap(), 50000, 20000),
+        ]
+    );
+
+    // Transaksi bulan depan belum dihitung ke periode berjalan
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}/{}", user_id, budget_id), None).await;
+    assert_eq!(body["data"]["period_start"], current.format("%Y-%m-%d").to_string());
+    assert_eq!(body["data"]["carry_over"], 130000);
+    assert_eq!(body["data"]["spent"], 10000);
+    assert_eq!(body["data"]["transaction_count"], 1);
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(json!({ "kategori_id": kategori_id, "jumlah": 7000, "deskripsi": "Pesan tiket", "tanggal": next_month.format("%Y-%m-%d").to_string() })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}/{}", user_id, budget_id), None).await;
+    assert_eq!(body["data"]["spent"], 10000);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn delete_reassign_and_clear_only_touch_their_own_periods() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "periode-hapus@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let asal = body["data"][0]["id"].as_i64().unwrap();
+    let tujuan = body["data"][1]["id"].as_i64().unwrap();
+    for kategori_id in [asal, tujuan] {
+        send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": kategori_id, "amount": 100000 }))).await;
+    }
+
+    // Kedua budget dimulai bulan lalu, lalu di-reset supaya periode bulan lalu tercatat di budget_history
+    let today = Local::now().date_naive();
+    let last_period = period_start_for(today).checked_sub_months(Months::new(1)).unwrap();
+    sqlx::query("UPDATE budgets SET period_start = $1 WHERE user_id = $2::uuid")
+        .bind(last_period)
+        .bind(&user_id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let transaksi_uri = format!("/api/transaksi/{}", user_id);
+    let mut ids = Vec::new();
+    for (jumlah, tanggal) in [(30000, last_period), (5000, last_period), (20000, today)] {
+        let (status, body) = send(
+            &app,
+            "POST",
+            &transaksi_uri,
+            Some(json!({ "kategori_id": asal, "jumlah": jumlah, "deskripsi": "Belanja", "tanggal": tanggal.format("%Y-%m-%d").to_string() })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        ids.push(body["data"]["id"].as_i64().unwrap());
+    }
+    reset_expired_periods(&db.pool, today).await.unwrap();
+
+    // (spent periode berjalan, spent snapshot bulan lalu) per kategori
+    let spent = |kategori_id: i64| {
+        let pool = db.pool.clone();
+        let user_id = user_id.clone();
+        async move {
+            sqlx::query_as::<_, (i32, i32)>(
+                r#"SELECT b.spent, h.spent FROM budgets b JOIN budget_history h ON h.budget_id = b.id
+                   WHERE b.user_id = $1::uuid AND b.kategori_id = $2 AND h.period_start = $3"#
+            )
+            .bind(&user_id)
+            .bind(kategori_id as i32)
+            .bind(last_period)
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+        }
+    };
+    assert_eq!(spent(asal).await, (20000, 35000));
+
+    // Menghapus transaksi bulan lalu tidak mengurangi spent periode berjalan
+    let (status, _) = send(&app, "DELETE", &format!("{}/{}", transaksi_uri, ids[1]), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(spent(asal).await, (20000, 30000));
+
+    // Pindah kategori membawa spent per periode, bukan total sepanjang waktu
+    let (status, _) = send(
+        &app,
+        "POST",
+        &format!("{}/reassign", transaksi_uri),
+        Some(json!({ "from_kategori_id": asal, "to_kategori_id": tujuan })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(spent(asal).await, (0, 0));
+    assert_eq!(spent(tujuan).await, (20000, 30000));
+
+    // Hapus transaksi sebelum bulan ini: spent berjalan tetap, snapshot bulan lalu menjadi 0
+    let (status, body) = send(
+        &app,
+        "DELETE",
+        &format!("{}/all?before={}", transaksi_uri, period_start_for(today).format("%Y-%m-%d")),
+        Some(json!({ "password": "rahasia123" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["deleted"], 1);
+    assert_eq!(spent(tujuan).await, (20000, 0));
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn carry_over_counts_toward_remaining_budget() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "carry-over@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+    let (_, body) = send(
+        &app,
+        "POST",
+        &format!("/api/budget/{}", user_id),
+        Some(json!({ "kategori_id": kategori_id, "amount": 100000, "rollover": true })),
+    )
+    .await;
+    sqlx::query("UPDATE budgets SET carry_over = 50000 WHERE id = $1")
+        .bind(body["data"]["id"].as_i64().unwrap() as i32)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let transaksi = |jumlah: i32| json!({ "kategori_id": kategori_id, "jumlah": jumlah, "deskripsi": "Belanja", "tanggal": today });
+
+    // Melebihi amount dasar tetapi masih di bawah amount + carry_over
+    let (status, body) = send(&app, "POST", &format!("/api/transaksi/{}/preview", user_id), Some(transaksi(120000))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["would_exceed"], false);
+    assert_eq!(body["data"]["budget"]["sisa"], 30000);
+    let (status, _) = send(&app, "POST", &format!("/api/transaksi/{}", user_id), Some(transaksi(120000))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = send(&app, "POST", &format!("/api/transaksi/{}/preview", user_id), Some(transaksi(40000))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["would_exceed"], true);
+    let (status, body) = send(&app, "POST", &format!("/api/transaksi/{}", user_id), Some(transaksi(40000))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Transaksi sebesar 40000 melebihi sisa budget Anda (150000). Sisa budget: 30000");
+
+    db.cleanup().await;
+}
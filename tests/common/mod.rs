@@ -0,0 +1,127 @@
+#![allow(dead_code)]
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use serde_json::Value;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{Executor, PgPool};
+use std::str::FromStr;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use savior_be::app;
+
+// Database test dengan schema sekali pakai; schema di-drop saat `TestDb` di-drop
+pub struct TestDb {
+    pub pool: PgPool,
+    admin_pool: PgPool,
+    schema: String,
+}
+
+impl TestDb {
+    // None jika TEST_DATABASE_URL tidak diset, sehingga test dilewati
+    pub async fn new() -> Option<Self> {
+        let Ok(database_url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("TEST_DATABASE_URL tidak diset, integration test dilewati");
+            return None;
+        };
+
+        std::env::set_var("JWT_SECRET", "savior-test-jwt-secret");
+
+        let schema = format!("test_{}", Uuid::new_v4().simple());
+        let admin_pool = PgPoolOptions::new()
+            .max_connections(1)
+            .connect(&database_url)
+            .await
+            .expect("Gagal terhubung ke TEST_DATABASE_URL");
+        admin_pool
+            .execute(format!("CREATE SCHEMA {}", schema).as_str())
+            .await
+            .expect("Gagal membuat schema test");
+
+        let options = PgConnectOptions::from_str(&database_url)
+            .expect("TEST_DATABASE_URL tidak valid")
+            .options([("search_path", schema.as_str())]);
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .connect_with(options)
+            .await
+            .expect("Gagal terhubung ke schema test");
+
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await
+            .expect("Gagal menjalankan migrations");
+
+        Some(TestDb { pool, admin_pool, schema })
+    }
+
+    pub fn app(&self) -> Router {
+        app(self.pool.clone())
+    }
+
+    pub async fn cleanup(self) {
+        self.pool.close().await;
+        let _ = self
+            .admin_pool
+            .execute(format!("DROP SCHEMA {} CASCADE", self.schema).as_str())
+            .await;
+    }
+}
+
+// Kirim request JSON ke router dan kembalikan status + body JSON (Null jika body kosong)
+pub async fn send(app: &Router, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    send_request(app, method, uri, None, body).await
+}
+
+// Sama seperti `send`, dengan header `Authorization: Bearer <token>`
+pub async fn send_authorized(app: &Router, method: &str, uri: &str, token: &str, body: Option<Value>) -> (StatusCode, Value) {
+    send_request(app, method, uri, Some(token), body).await
+}
+
+async fn send_request(app: &Router, method: &str, uri: &str, token: Option<&str>, body: Option<Value>) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    if let Some(token) = token {
+        request = request.header("authorization", format!("Bearer {}", token));
+    }
+    let request = match body {
+        Some(body) => request.body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, json)
+}
+
+// User hasil signup + signin
+pub struct TestUser {
+    pub id: String,
+    pub token: String,
+    pub refresh_token: String,
+}
+
+// Daftar + login user baru
+pub async fn signup_and_signin(app: &Router, email: &str, password: &str) -> TestUser {
+    let credentials = serde_json::json!({ "email": email, "password": password });
+
+    let (status, _) = send(app, "POST", "/signup", Some(credentials.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = send(app, "POST", "/signin", Some(credentials)).await;
+    assert_eq!(status, StatusCode::OK);
+    TestUser {
+        id: body["user"]["id"].as_str().expect("signin tidak mengembalikan user.id").to_string(),
+        token: body["token"].as_str().expect("signin tidak mengembalikan token").to_string(),
+        refresh_token: body["refresh_token"].as_str().expect("signin tidak mengembalikan refresh_token").to_string(),
+    }
+}
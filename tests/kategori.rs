@@ -0,0 +1,248 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::Local;
+use serde_json::{json, Value};
+
+use common::{send, signup_and_signin, TestDb};
+
+#[tokio::test]
+async fn foreign_private_kategori_is_rejected() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let pemilik = signup_and_signin(&app, "pemilik@savior.test", "rahasia123").await.id;
+    let lain = signup_and_signin(&app, "lain@savior.test", "rahasia123").await.id;
+
+    // Kategori default hasil signup adalah kategori pribadi milik user tersebut
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", pemilik), None).await;
+    let kategori_id = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|kategori| kategori["user_id"] == Value::String(pemilik.clone()))
+        .and_then(|kategori| kategori["id"].as_i64())
+        .expect("kategori pribadi pemilik tidak ada");
+
+    let (status, body) = send(
+        &app,
+        "POST",
+        &format!("/api/budget/{}", lain),
+        Some(json!({ "kategori_id": kategori_id, "amount": 50000 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["status"], "error");
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", lain),
+        Some(json!({
+            "kategori_id": kategori_id,
+            "jumlah": 10000,
+            "deskripsi": "Pinjam kategori",
+            "tanggal": Local::now().date_naive().format("%Y-%m-%d").to_string()
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Pemilik tetap boleh memakai kategorinya sendiri
+    let (status, _) = send(
+        &app,
+        "POST",
+        &format!("/api/budget/{}", pemilik),
+        Some(json!({ "kategori_id": kategori_id, "amount": 50000 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn kategori_list_is_paginated() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "halaman@savior.test", "rahasia123").await.id;
+
+    let (status, body) = send(&app, "GET", &format!("/api/kategori?user_id={}&limit=2&offset=1", user_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"].as_array().unwrap().len(), 2);
+    assert_eq!(body["pagination"]["limit"], 2);
+    assert_eq!(body["pagination"]["offset"], 1);
+    assert!(body["links"]["prev"].is_string());
+
+    // limit di luar batas dijepit ke [1, MAX_PAGE_SIZE], offset negatif menjadi 0
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}&limit=100000&offset=-5", user_id), None).await;
+    assert_eq!(body["pagination"]["limit"], 100);
+    assert_eq!(body["pagination"]["offset"], 0);
+
+    let (status, _) = send(&app, "GET", &format!("/api/kategori?user_id={}&limit=banyak", user_id), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn kategori_sync_creates_only_missing_names() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    // Signup sudah membuat kategori default (Makanan, Transport, Hiburan, Tagihan)
+    let user_id = signup_and_signin(&app, "sync@savior.test", "rahasia123").await.id;
+
+    let (status, body) = send(
+        &app,
+        "POST",
+        "/api/kategori/sync",
+        Some(json!({ "user_id": user_id, "categories": ["makanan", " Kesehatan ", "KESEHATAN", "Transport"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["created_count"], 1);
+    let names: Vec<&str> = body["data"].as_array().unwrap().iter().map(|kategori| kategori["nama"].as_str().unwrap()).collect();
+    assert_eq!(names, ["Makanan", "Kesehatan", "Transport"]);
+
+    // Sync ulang idempotent
+    let (status, body) = send(&app, "POST", "/api/kategori/sync", Some(json!({ "user_id": user_id, "categories": ["Kesehatan"] }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["created_count"], 0);
+
+    let (status, _) = send(&app, "POST", "/api/kategori/sync", Some(json!({ "user_id": user_id, "categories": ["  "] }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn kategori_reorder_sets_list_order() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "urutan@savior.test", "rahasia123").await.id;
+    let list_uri = format!("/api/kategori?user_id={}", user_id);
+    let names = |body: &serde_json::Value| -> Vec<String> {
+        body["data"].as_array().unwrap().iter().map(|kategori| kategori["nama"].as_str().unwrap().to_string()).collect()
+    };
+
+    // Sebelum diatur, kategori diurutkan per nama
+    let (_, body) = send(&app, "GET", &list_uri, None).await;
+    assert_eq!(names(&body), ["Hiburan", "Makanan", "Tagihan", "Transport"]);
+    let mut ids: Vec<i64> = body["data"].as_array().unwrap().iter().map(|kategori| kategori["id"].as_i64().unwrap()).collect();
+    ids.reverse();
+
+    // Daftar harus lengkap dan tanpa duplikat
+    for kategori_ids in [json!(&ids[1..]), json!([ids[0], ids[0], ids[1], ids[2]])] {
+        let (status, body) = send(&app, "PUT", "/api/kategori/reorder", Some(json!({ "user_id": user_id, "kategori_ids": kategori_ids }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["field"], "kategori_ids");
+    }
+
+    let (status, body) = send(&app, "PUT", "/api/kategori/reorder", Some(json!({ "user_id": user_id, "kategori_ids": ids }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(names(&body), ["Transport", "Tagihan", "Makanan", "Hiburan"]);
+
+    let (_, body) = send(&app, "GET", &list_uri, None).await;
+    assert_eq!(names(&body), ["Transport", "Tagihan", "Makanan", "Hiburan"]);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn kategori_create_stops_at_per_user_cap() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "batas-kategori@savior.test", "rahasia123").await.id;
+    let other_id = signup_and_signin(&app, "batas-lain@savior.test", "rahasia123").await.id;
+
+    // Isi kategori pribadi sampai satu di bawah batas default (200)
+    sqlx::query(
+        r#"
+        INSERT INTO categories (nama, user_id)
+        SELECT 'Isi ' || n, $1::uuid
+        FROM generate_series(1, 199 - (SELECT COUNT(*) FROM categories WHERE user_id = $1::uuid)) AS n
+        "#,
+    )
+    .bind(&user_id)
+    .execute(&db.pool)
+    .await
+    .unwrap();
+
+    let create = |user_id: &str, nama: &str| json!({ "nama": nama, "user_id": user_id });
+    let (status, _) = send(&app, "POST", "/api/kategori", Some(create(&user_id, "Terakhir"))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = send(&app, "POST", "/api/kategori", Some(create(&user_id, "Kelebihan"))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(body["message"].as_str().unwrap().contains("200"));
+
+    // Sync juga dibatasi: nama yang sudah ada tetap boleh, nama baru ditolak tanpa membuat apa pun
+    let sync = |categories: &[&str]| json!({ "user_id": user_id, "categories": categories });
+    let (status, _) = send(&app, "POST", "/api/kategori/sync", Some(sync(&["Terakhir", "isi 1"]))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = send(&app, "POST", "/api/kategori/sync", Some(sync(&["Terakhir", "Baru Sekali"]))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(body["message"].as_str().unwrap().contains("200"));
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM categories WHERE user_id = $1::uuid")
+        .bind(&user_id)
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 200);
+
+    // Batas dihitung per user
+    let (status, _) = send(&app, "POST", "/api/kategori", Some(create(&other_id, "Kelebihan"))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Request bersamaan saat tersisa satu slot: hanya satu yang berhasil
+    sqlx::query("DELETE FROM categories WHERE user_id = $1::uuid AND nama = 'Terakhir'")
+        .bind(&user_id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let (first, second) = tokio::join!(
+        send(&app, "POST", "/api/kategori", Some(create(&user_id, "Serentak A"))),
+        send(&app, "POST", "/api/kategori/sync", Some(sync(&["Serentak B"]))),
+    );
+    let mut statuses = [first.0, second.0];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::CONFLICT]);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn kategori_names_are_unique_ignoring_case() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "nama-unik@savior.test", "rahasia123").await.id;
+    let create = |nama: &str| json!({ "nama": nama, "user_id": user_id });
+
+    let (status, _) = send(&app, "POST", "/api/kategori", Some(create("Olahraga"))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = send(&app, "POST", "/api/kategori", Some(create("olahraga"))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["status"], "error");
+
+    // Request bersamaan melewati pengecekan di handler; unique index tetap menolak salah satunya
+    let (first, second) = tokio::join!(
+        send(&app, "POST", "/api/kategori", Some(create("Langganan"))),
+        send(&app, "POST", "/api/kategori", Some(create("langganan"))),
+    );
+    let mut statuses = [first.0, second.0];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::CONFLICT]);
+
+    let duplicate = sqlx::query("INSERT INTO categories (nama, user_id) VALUES ('OLAHRAGA', $1::uuid)")
+        .bind(&user_id)
+        .execute(&db.pool)
+        .await;
+    assert!(duplicate.is_err());
+
+    db.cleanup().await;
+}
@@ -0,0 +1,797 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Local, Months};
+use serde_json::json;
+
+use common::{send, signup_and_signin, TestDb};
+
+#[tokio::test]
+async fn split_moves_spent_between_budgets() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "split@savior.test", "rahasia123").await.id;
+
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori = body["data"].as_array().unwrap();
+    let belanja = kategori[0]["id"].as_i64().unwrap();
+    let rumah_tangga = kategori[1]["id"].as_i64().unwrap();
+
+    for kategori_id in [belanja, rumah_tangga] {
+        let (status, _) = send(
+            &app,
+            "POST",
+            &format!("/api/budget/{}", user_id),
+            Some(json!({ "kategori_id": kategori_id, "amount": 100000 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (_, body) = send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(json!({
+            "kategori_id": belanja,
+            "jumlah": 30000,
+            "deskripsi": "Struk supermarket",
+            "tanggal": Local::now().date_naive().format("%Y-%m-%d").to_string()
+        })),
+    )
+    .await;
+    let transaksi_id = body["data"]["id"].as_i64().unwrap();
+    let split_uri = format!("/api/transaksi/{}/{}/split", user_id, transaksi_id);
+
+    // Total split tidak sama dengan jumlah asal ditolak
+    let (status, body) = send(
+        &app,
+        "POST",
+        &split_uri,
+        Some(json!({ "splits": [
+            { "kategori_id": belanja, "jumlah": 20000 },
+            { "kategori_id": rumah_tangga, "jumlah": 5000 }
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["status"], "error");
+
+    let (status, body) = send(
+        &app,
+        "POST",
+        &split_uri,
+        Some(json!({ "splits": [
+            { "kategori_id": belanja, "jumlah": 20000 },
+            { "kategori_id": rumah_tangga, "jumlah": 10000, "deskripsi": "Sabun" }
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let splits = body["data"].as_array().unwrap();
+    assert_eq!(splits.len(), 2);
+    assert_eq!(splits[0]["deskripsi"], "Struk supermarket");
+    assert_eq!(splits[1]["deskripsi"], "Sabun");
+
+    // Transaksi asal sudah dihapus
+    let (status, _) = send(&app, "GET", &format!("/api/transaksi/{}/{}", user_id, transaksi_id), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}", user_id), None).await;
+    let spent_for = |kategori_id: i64| {
+        body["budgets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|budget| budget["kategori_id"].as_i64() == Some(kategori_id))
+            .and_then(|budget| budget["spent"].as_i64())
+    };
+    assert_eq!(spent_for(belanja), Some(20000));
+    assert_eq!(spent_for(rumah_tangga), Some(10000));
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn quick_input_creates_transaksi_from_text() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "quick@savior.test", "rahasia123").await.id;
+    let quick_uri = format!("/api/transaksi/{}/quick", user_id);
+
+    let (status, body) = send(&app, "POST", &quick_uri, Some(json!({ "text": "kopi 25000 10000 makanan" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["message"].as_str().unwrap().contains("ambigu"));
+
+    let (status, _) = send(&app, "POST", &quick_uri, Some(json!({ "text": "kopi 25rb jajan" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = send(
+        &app,
+        "POST",
+        &quick_uri,
+        Some(json!({ "text": "kopi susu 25.000 jajan", "create_kategori": true, "auto_budget_amount": 100000 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["jumlah"], 25000);
+    assert_eq!(body["data"]["deskripsi"], "kopi susu");
+    assert_eq!(body["parsed"]["kategori_nama"], "jajan");
+    assert_eq!(body["parsed"]["kategori_dibuat"], true);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn preview_projects_budget_without_writing() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "preview@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+    send(
+        &app,
+        "POST",
+        &format!("/api/budget/{}", user_id),
+        Some(json!({ "kategori_id": kategori_id, "amount": 100000 })),
+    )
+    .await;
+
+    let preview_uri = format!("/api/transaksi/{}/preview", user_id);
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let transaksi = |jumlah: i64| json!({ "kategori_id": kategori_id, "jumlah": jumlah, "deskripsi": "Makan siang", "tanggal": today });
+
+    let (status, body) = send(&app, "POST", &preview_uri, Some(transaksi(92000))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["budget"]["spent"], 92000);
+    assert_eq!(body["data"]["budget"]["percentage"], 92.0);
+    assert_eq!(body["data"]["would_exceed"], false);
+
+    let (_, body) = send(&app, "POST", &preview_uri, Some(transaksi(150000))).await;
+    assert_eq!(body["data"]["would_exceed"], true);
+
+    // Input tidak valid ditolak sama seperti create
+    let (status, _) = send(&app, "POST", &preview_uri, Some(transaksi(0))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Preview tidak membuat transaksi maupun mengubah spent
+    let (_, body) = send(&app, "GET", &format!("/api/transaksi/{}", user_id), None).await;
+    assert_eq!(body["transaksi"], json!([]));
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}", user_id), None).await;
+    assert_eq!(body["budgets"][0]["spent"], 0);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn moving_tanggal_across_period_rewindows_spent() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "periode-transaksi@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+    send(
+        &app,
+        "POST",
+        &format!("/api/budget/{}", user_id),
+        Some(json!({ "kategori_id": kategori_id, "amount": 100000 })),
+    )
+    .await;
+
+    // Transaksi bulan lalu (sebelum periode budget berjalan) tidak dihitung ke spent
+    let today = Local::now().date_naive();
+    let last_month = today - Months::new(1);
+    let transaksi_id: i32 = sqlx::query_scalar(
+        "INSERT INTO transaksi (user_id, kategori_id, jumlah, deskripsi, tanggal) VALUES ($1::uuid, $2, 20000, 'Belanja', $3) RETURNING id"
+    )
+    .bind(&user_id)
+    .bind(kategori_id as i32)
+    .bind(last_month)
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+
+    let transaksi_uri = format!("/api/transaksi/{}/{}", user_id, transaksi_id);
+    // (spent, transaction_count) budget; keduanya hanya menghitung transaksi di periode berjalan
+    let budget_spent = || async {
+        let (_, body) = send(&app, "GET", &format!("/api/budget/{}", user_id), None).await;
+        (body["budgets"][0]["spent"].as_i64().unwrap(), body["budgets"][0]["transaction_count"].as_i64().unwrap())
+    };
+
+    let (status, _) = send(&app, "PUT", &transaksi_uri, Some(json!({ "tanggal": today.format("%Y-%m-%d").to_string() }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(budget_spent().await, (20000, 1));
+
+    // Dipindah kembali ke bulan lalu: spent periode berjalan kembali 0
+    let (status, _) = send(&app, "PUT", &transaksi_uri, Some(json!({ "tanggal": last_month.format("%Y-%m-%d").to_string() }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(budget_spent().await, (0, 0));
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn categorization_rules_apply_on_create_and_in_bulk() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "aturan@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori = body["data"].as_array().unwrap();
+    let belanja = kategori[0]["id"].as_i64().unwrap();
+    let transportasi = kategori[1]["id"].as_i64().unwrap();
+
+    for kategori_id in [belanja, transportasi] {
+        send(
+            &app,
+            "POST",
+            &format!("/api/budget/{}", user_id),
+            Some(json!({ "kategori_id": kategori_id, "amount": 100000 })),
+        )
+        .await;
+    }
+
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let transaksi_uri = format!("/api/transaksi/{}", user_id);
+
+    // Transaksi lama yang salah kategori
+    let (status, _) = send(
+        &app,
+        "POST",
+        &transaksi_uri,
+        Some(json!({ "kategori_id": belanja, "jumlah": 15000, "deskripsi": "Gojek ke kantor", "tanggal": today })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Tanpa kategori dan tanpa aturan yang cocok ditolak
+    let (status, body) = send(
+        &app,
+        "POST",
+        &transaksi_uri,
+        Some(json!({ "jumlah": 20000, "deskripsi": "GOJEK pulang", "tanggal": today })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["fields"]["kategori_id"].is_string());
+
+    let rules_uri = format!("/api/categorization-rules/{}", user_id);
+    let (status, _) = send(&app, "POST", &rules_uri, Some(json!({ "pattern": "gojek", "kategori_id": transportasi }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&app, "POST", &rules_uri, Some(json!({ "pattern": "GoJek", "kategori_id": belanja }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Kategori diambil dari aturan (pencocokan tanpa membedakan huruf besar/kecil)
+    let (status, body) = send(
+        &app,
+        "POST",
+        &transaksi_uri,
+        Some(json!({ "jumlah": 20000, "deskripsi": "GOJEK pulang", "tanggal": today })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["kategori_id"], transportasi);
+
+    let (status, body) = send(&app, "POST", &format!("/api/transaksi/{}/auto-categorize", user_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["recategorized"], 1);
+
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}", user_id), None).await;
+    let spent_for = |kategori_id: i64| {
+        body["budgets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|budget| budget["kategori_id"].as_i64() == Some(kategori_id))
+            .and_then(|budget| budget["spent"].as_i64())
+    };
+    assert_eq!(spent_for(belanja), Some(0));
+    assert_eq!(spent_for(transportasi), Some(35000));
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn concurrent_updates_keep_budget_spent_consistent() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "bersamaan@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+    send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": kategori_id, "amount": 1000000 }))).await;
+
+    let (_, body) = send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(json!({
+            "kategori_id": kategori_id,
+            "jumlah": 10000,
+            "deskripsi": "Belanja",
+            "tanggal": Local::now().date_naive().format("%Y-%m-%d").to_string()
+        })),
+    )
+    .await;
+    let transaksi_uri = format!("/api/transaksi/{}/{}", user_id, body["data"]["id"]);
+
+    // Dua update yang tumpang tindih: selisih spent masing-masing harus dihitung dari jumlah terbaru
+    for round in 0..5 {
+        let (first, second) = tokio::join!(
+            send(&app, "PUT", &transaksi_uri, Some(json!({ "jumlah": 20000 + round }))),
+            send(&app, "PUT", &transaksi_uri, Some(json!({ "jumlah": 30000 + round }))),
+        );
+        assert_eq!(first.0, StatusCode::OK);
+        assert_eq!(second.0, StatusCode::OK);
+
+        let (_, transaksi) = send(&app, "GET", &transaksi_uri, None).await;
+        let (_, budgets) = send(&app, "GET", &format!("/api/budget/{}", user_id), None).await;
+        assert_eq!(budgets["budgets"][0]["spent"], transaksi["data"]["jumlah"]);
+    }
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn undo_last_removes_newest_transaksi_once() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "undo@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+    send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": kategori_id, "amount": 100000 }))).await;
+
+    let undo_uri = format!("/api/transaksi/{}/undo-last", user_id);
+    let (status, _) = send(&app, "POST", &undo_uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    for (jumlah, deskripsi) in [(10000, "Sarapan"), (15000, "Makan siang")] {
+        send(
+            &app,
+            "POST",
+            &format!("/api/transaksi/{}", user_id),
+            Some(json!({ "kategori_id": kategori_id, "jumlah": jumlah, "deskripsi": deskripsi, "tanggal": today })),
+        )
+        .await;
+    }
+
+    let (status, body) = send(&app, "POST", &undo_uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["deskripsi"], "Makan siang");
+
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}", user_id), None).await;
+    assert_eq!(body["budgets"][0]["spent"], 10000);
+
+    // Undo berturut-turut ditolak; transaksi baru membuat undo tersedia lagi
+    let (status, _) = send(&app, "POST", &undo_uri, None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(json!({ "kategori_id": kategori_id, "jumlah": 5000, "deskripsi": "Kopi", "tanggal": today })),
+    )
+    .await;
+    let (status, body) = send(&app, "POST", &undo_uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["deskripsi"], "Kopi");
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn concurrent_creates_respect_budget_and_keep_spent_consistent() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "serentak@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+    send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": kategori_id, "amount": 100000 }))).await;
+
+    let today = Local::now().date_naive();
+    let period_start = today.format("%Y-%m-01").to_string();
+    let create_all = |payload: serde_json::Value| {
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let app = app.clone();
+                let uri = format!("/api/transaksi/{}", user_id);
+                let payload = payload.clone();
+                tokio::spawn(async move { send(&app, "POST", &uri, Some(payload)).await.0 })
+            })
+            .collect();
+        async move {
+            let mut statuses = Vec::new();
+            for handle in handles {
+                statuses.push(handle.await.unwrap());
+            }
+            statuses
+        }
+    };
+
+    // Jalur hitung ulang (override ke periode berjalan): spent harus mencakup semua transaksi
+    let statuses = create_all(json!({
+        "kategori_id": kategori_id,
+        "jumlah": 5000,
+        "deskripsi": "Parkir",
+        "tanggal": today.to_string(),
+        "budget_period_start": period_start
+    }))
+    .await;
+    assert!(statuses.iter().all(|status| *status == StatusCode::OK));
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}", user_id), None).await;
+    assert_eq!(body["budgets"][0]["spent"], 40000);
+
+    // Sisa 60.000: dari 8 pengeluaran 20.000 bersamaan hanya 3 yang boleh lolos
+    let statuses = create_all(json!({
+        "kategori_id": kategori_id,
+        "jumlah": 20000,
+        "deskripsi": "Belanja",
+        "tanggal": today.to_string()
+    }))
+    .await;
+    assert_eq!(statuses.iter().filter(|status| **status == StatusCode::OK).count(), 3);
+    assert!(statuses.iter().all(|status| *status == StatusCode::OK || *status == StatusCode::BAD_REQUEST));
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}", user_id), None).await;
+    assert_eq!(body["budgets"][0]["spent"], 100000);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn transaksi_list_clamps_limit_and_offset() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "halaman-transaksi@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+    send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": kategori_id, "amount": 100000 }))).await;
+
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    for deskripsi in ["Sarapan", "Makan siang", "Makan malam"] {
+        send(
+            &app,
+            "POST",
+            &format!("/api/transaksi/{}", user_id),
+            Some(json!({ "kategori_id": kategori_id, "jumlah": 1000, "deskripsi": deskripsi, "tanggal": today })),
+        )
+        .await;
+    }
+    let list_uri = format!("/api/transaksi/{}", user_id);
+
+    // Offset negatif dijepit ke 0, limit 0 atau negatif menjadi 1
+    let (status, body) = send(&app, "GET", &format!("{}?limit=-3&offset=-10", list_uri), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["pagination"]["limit"], 1);
+    assert_eq!(body["pagination"]["offset"], 0);
+    assert_eq!(body["transaksi"].as_array().unwrap().len(), 1);
+    assert_eq!(body["transaksi"][0]["deskripsi"], "Makan malam");
+
+    // Limit yang terlalu besar dijepit ke MAX_PAGE_SIZE
+    let (status, body) = send(&app, "GET", &format!("{}?limit=1000000&offset=1", list_uri), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["pagination"]["limit"], 100);
+    assert_eq!(body["transaksi"].as_array().unwrap().len(), 2);
+
+    for query in ["limit=sepuluh", "offset=1.5", "limit=99999999999999999999"] {
+        let (status, body) = send(&app, "GET", &format!("{}?{}", list_uri, query), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["status"], "error");
+    }
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn create_transaksi_reports_every_invalid_field() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "banyak-error@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+
+    let (status, body) = send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(json!({
+            "kategori_id": kategori_id,
+            "jumlah": -500,
+            "deskripsi": "   ",
+            "tanggal": "16-10-2026",
+            "tipe": "hutang",
+            "auto_budget_amount": 0,
+            "budget_period_start": "2026-10-15"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["status"], "error");
+    for field in ["jumlah", "deskripsi", "tanggal", "tipe", "auto_budget_amount", "budget_period_start"] {
+        assert!(body["fields"][field].is_string(), "{} tidak dilaporkan: {}", field, body);
+    }
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn concurrent_first_transaksi_share_one_auto_budget() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "auto-serentak@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+
+    let today = Local::now().date_naive().to_string();
+    let handles: Vec<_> = (0..6)
+        .map(|_| {
+            let app = app.clone();
+            let uri = format!("/api/transaksi/{}", user_id);
+            let payload = json!({
+                "kategori_id": kategori_id,
+                "jumlah": 10000,
+                "deskripsi": "Makan siang",
+                "tanggal": today,
+                "auto_budget_amount": 200000
+            });
+            tokio::spawn(async move { send(&app, "POST", &uri, Some(payload)).await })
+        })
+        .collect();
+    // Hanya request yang benar-benar membuat budget yang melaporkannya di budget_baru
+    let mut budget_baru = 0;
+    for handle in handles {
+        let (status, body) = handle.await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        if !body["budget_baru"].is_null() {
+            budget_baru += 1;
+        }
+    }
+    assert_eq!(budget_baru, 1);
+
+    let (_, body) = send(&app, "GET", &format!("/api/budget/{}", user_id), None).await;
+    let budgets = body["budgets"].as_array().unwrap();
+    assert_eq!(budgets.len(), 1);
+    assert_eq!(budgets[0]["amount"], 200000);
+    assert_eq!(budgets[0]["spent"], 60000);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn categories_used_ignores_pemasukan() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "dipakai@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let belanja = body["data"][0]["id"].as_i64().unwrap();
+    let gaji = body["data"][1]["id"].as_i64().unwrap();
+    send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": belanja, "amount": 100000 }))).await;
+
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let transaksi_uri = format!("/api/transaksi/{}", user_id);
+    for (kategori_id, jumlah, tipe) in [(belanja, 20000, "pengeluaran"), (belanja, 500000, "pemasukan"), (gaji, 3000000, "pemasukan")] {
+        let (status, _) = send(
+            &app,
+            "POST",
+            &transaksi_uri,
+            Some(json!({ "kategori_id": kategori_id, "jumlah": jumlah, "deskripsi": "Transaksi", "tanggal": today, "tipe": tipe })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = send(&app, "GET", &format!("{}/categories-used", transaksi_uri), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let data = body["data"].as_array().unwrap();
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0]["kategori_id"], belanja);
+    assert_eq!(data[0]["jumlah_transaksi"], 1);
+    assert_eq!(data[0]["total"], 20000);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn recent_categories_follow_latest_pengeluaran() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "terbaru@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let makan = body["data"][0]["id"].as_i64().unwrap();
+    let transport = body["data"][1]["id"].as_i64().unwrap();
+    let gaji = body["data"][2]["id"].as_i64().unwrap();
+    for kategori_id in [makan, transport] {
+        send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": kategori_id, "amount": 100000 }))).await;
+    }
+
+    let today = Local::now().date_naive();
+    let transaksi_uri = format!("/api/transaksi/{}", user_id);
+    // Pemasukan terbaru (termasuk di kategori makan) tidak mempengaruhi urutan
+    for (kategori_id, days_ago, tipe) in [(makan, 3, "pengeluaran"), (transport, 2, "pengeluaran"), (makan, 0, "pemasukan"), (gaji, 0, "pemasukan")] {
+        let tanggal = (today - chrono::Duration::days(days_ago)).format("%Y-%m-%d").to_string();
+        let (status, _) = send(
+            &app,
+            "POST",
+            &transaksi_uri,
+            Some(json!({ "kategori_id": kategori_id, "jumlah": 10000, "deskripsi": "Transaksi", "tanggal": tanggal, "tipe": tipe })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = send(&app, "GET", &format!("{}/recent-categories", transaksi_uri), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let ids: Vec<i64> = body["data"].as_array().unwrap().iter().map(|k| k["kategori_id"].as_i64().unwrap()).collect();
+    assert_eq!(ids, vec![transport, makan]);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn update_can_clear_note_and_receipt_url() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "catatan@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+    send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": kategori_id, "amount": 100000 }))).await;
+
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let (status, body) = send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(json!({
+            "kategori_id": kategori_id,
+            "jumlah": 10000,
+            "deskripsi": "Makan siang",
+            "tanggal": today,
+            "note": "Bersama tim",
+            "receipt_url": "https://example.com/struk.jpg"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let transaksi_uri = format!("/api/transaksi/{}/{}", user_id, body["data"]["id"]);
+
+    // Field yang tidak dikirim tidak berubah
+    let (status, body) = send(&app, "PUT", &transaksi_uri, Some(json!({ "jumlah": 12000 }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["note"], "Bersama tim");
+    assert_eq!(body["data"]["receipt_url"], "https://example.com/struk.jpg");
+
+    // null dan string kosong sama-sama menghapus
+    let (status, body) = send(&app, "PUT", &transaksi_uri, Some(json!({ "note": null, "receipt_url": "  " }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["data"]["note"].is_null());
+    assert!(body["data"]["receipt_url"].is_null());
+
+    let (status, body) = send(&app, "PUT", &transaksi_uri, Some(json!({ "note": "Baru" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["note"], "Baru");
+    assert!(body["data"]["receipt_url"].is_null());
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn concurrent_deletes_subtract_spent_once() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "hapus-bersamaan@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let kategori_id = body["data"][0]["id"].as_i64().unwrap();
+    send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": kategori_id, "amount": 1000000 }))).await;
+
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let mut transaksi_ids = Vec::new();
+    for jumlah in [10000, 25000] {
+        let (_, body) = send(
+            &app,
+            "POST",
+            &format!("/api/transaksi/{}", user_id),
+            Some(json!({ "kategori_id": kategori_id, "jumlah": jumlah, "deskripsi": "Belanja", "tanggal": today })),
+        )
+        .await;
+        transaksi_ids.push(body["data"]["id"].clone());
+    }
+
+    let transaksi_uri = format!("/api/transaksi/{}/{}", user_id, transaksi_ids[0]);
+    let (first, second) = tokio::join!(
+        send(&app, "DELETE", &transaksi_uri, None),
+        send(&app, "DELETE", &transaksi_uri, None),
+    );
+    let mut statuses = [first.0, second.0];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::NOT_FOUND]);
+
+    let (_, budgets) = send(&app, "GET", &format!("/api/budget/{}", user_id), None).await;
+    assert_eq!(budgets["budgets"][0]["spent"], 25000);
+
+    db.cleanup().await;
+}
+
+#[tokio::test]
+async fn split_locks_original_and_keeps_earlier_periods_out_of_spent() {
+    let Some(db) = TestDb::new().await else { return };
+    let app = db.app();
+
+    let user_id = signup_and_signin(&app, "split-bersamaan@savior.test", "rahasia123").await.id;
+    let (_, body) = send(&app, "GET", &format!("/api/kategori?user_id={}", user_id), None).await;
+    let belanja = body["data"][0]["id"].as_i64().unwrap();
+    let rumah_tangga = body["data"][1]["id"].as_i64().unwrap();
+    for kategori_id in [belanja, rumah_tangga] {
+        send(&app, "POST", &format!("/api/budget/{}", user_id), Some(json!({ "kategori_id": kategori_id, "amount": 100000 }))).await;
+    }
+    let splits = json!({ "splits": [
+        { "kategori_id": belanja, "jumlah": 20000 },
+        { "kategori_id": rumah_tangga, "jumlah": 10000 }
+    ] });
+    let spent = || async {
+        let (_, body) = send(&app, "GET", &format!("/api/budget/{}", user_id), None).await;
+        let budgets = body["budgets"].as_array().unwrap().clone();
+        [belanja, rumah_tangga].map(|kategori_id| {
+            budgets.iter().find(|budget| budget["kategori_id"] == kategori_id).unwrap()["spent"].as_i64().unwrap()
+        })
+    };
+
+    // Dua split bersamaan atas transaksi yang sama: hanya satu yang berhasil
+    let (_, body) = send(
+        &app,
+        "POST",
+        &format!("/api/transaksi/{}", user_id),
+        Some(json!({
+            "kategori_id": belanja,
+            "jumlah": 30000,
+            "deskripsi": "Struk supermarket",
+            "tanggal": Local::now().date_naive().format("%Y-%m-%d").to_string()
+        })),
+    )
+    .await;
+    let split_uri = format!("/api/transaksi/{}/{}/split", user_id, body["data"]["id"]);
+    let (first, second) = tokio::join!(
+        send(&app, "POST", &split_uri, Some(splits.clone())),
+        send(&app, "POST", &split_uri, Some(splits.clone())),
+    );
+    let mut statuses = [first.0, second.0];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::NOT_FOUND]);
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM transaksi WHERE user_id = $1::uuid")
+        .bind(&user_id)
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+    assert_eq!(spent().await, [20000, 10000]);
+
+    // Split transaksi bulan lalu tidak mengubah spent periode berjalan
+    let last_month = Local::now().date_naive() - Months::new(1);
+    let transaksi_id: i32 = sqlx::query_scalar(
+        "INSERT INTO transaksi (user_id, kategori_id, jumlah, deskripsi, tanggal) VALUES ($1::uuid, $2, 30000, 'Belanja', $3) RETURNING id"
+    )
+    .bind(&user_id)
+    .bind(belanja as i32)
+    .bind(last_month)
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    let (status, _) = send(&app, "POST", &format!("/api/transaksi/{}/{}/split", user_id, transaksi_id), Some(splits)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(spent().await, [20000, 10000]);
+
+    db.cleanup().await;
+}